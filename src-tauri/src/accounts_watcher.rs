@@ -0,0 +1,92 @@
+//! 账户备份目录监听模块
+//! 监听当前配置档的账户目录，备份文件被新增、删除或修改时推送 accounts-changed 事件
+
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tracing::{error, info};
+
+/// 事件去抖时间，合并同一次保存产生的多个文件事件
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// 账户目录变化事件数据
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountsChangedEvent {
+    /// 发生变化的备份文件名
+    pub files: Vec<String>,
+}
+
+/// 账户目录监听器
+pub struct AccountsWatcher {
+    debouncer: Mutex<Option<Debouncer<RecommendedWatcher>>>,
+}
+
+impl AccountsWatcher {
+    /// 创建新的监听器（不会自动启动）
+    pub fn new() -> Self {
+        Self {
+            debouncer: Mutex::new(None),
+        }
+    }
+
+    /// 开始监听当前配置档的账户目录；已在监听时先停止旧的监听（用于切换配置档后重新监听）
+    pub fn start(&self, app_handle: AppHandle) -> Result<(), String> {
+        let accounts_dir = crate::directories::get_accounts_directory();
+
+        let mut debouncer = new_debouncer(DEBOUNCE_TIMEOUT, move |result: DebounceEventResult| {
+            match result {
+                Ok(events) => {
+                    // 只关心备份文件（.json / .json.gz），忽略 .blobs / pruned 等子目录和临时文件
+                    let mut files: Vec<String> = events
+                        .iter()
+                        .filter(|event| crate::directories::is_account_backup_file(&event.path))
+                        .filter_map(|event| event.path.file_name())
+                        .map(|name| name.to_string_lossy().to_string())
+                        .collect();
+                    files.sort();
+                    files.dedup();
+                    if files.is_empty() {
+                        return;
+                    }
+
+                    info!(target: "accounts_watcher", files = ?files, "📢 账户备份目录发生变化");
+                    if let Err(e) =
+                        app_handle.emit("accounts-changed", AccountsChangedEvent { files })
+                    {
+                        error!(target: "accounts_watcher", error = %e, "推送账户目录变化事件失败");
+                    }
+                }
+                Err(e) => {
+                    error!(target: "accounts_watcher", error = %e, "监听账户目录出错");
+                }
+            }
+        })
+        .map_err(|e| format!("创建账户目录监听器失败: {}", e))?;
+
+        debouncer
+            .watcher()
+            .watch(&accounts_dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("监听账户目录失败 {}: {}", accounts_dir.display(), e))?;
+
+        info!(target: "accounts_watcher", "🔧 开始监听账户备份目录: {}", accounts_dir.display());
+        // 替换旧的监听器，旧监听器在 drop 时自动停止
+        *self.debouncer.lock().unwrap() = Some(debouncer);
+        Ok(())
+    }
+
+    /// 停止监听
+    pub fn stop(&self) {
+        if self.debouncer.lock().unwrap().take().is_some() {
+            info!(target: "accounts_watcher", "⏹️ 停止监听账户备份目录");
+        }
+    }
+}
+
+impl Default for AccountsWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
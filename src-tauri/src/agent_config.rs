@@ -0,0 +1,169 @@
+//! 应用自身配置的导出/导入
+//! 把设置、路径配置、窗口状态等配置文件打包为一个 JSON 归档（不包含账户备份），
+//! 用于重装系统后恢复应用配置
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::directories;
+
+/// 归档格式标识
+const ARCHIVE_FORMAT: &str = "antigravity-agent-config";
+
+/// 当前归档版本
+const ARCHIVE_VERSION: u64 = 1;
+
+/// 配置归档内容
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AgentConfigArchive {
+    pub format: String,
+    pub version: u64,
+    /// 导出时间（RFC 3339）
+    pub exported_at: String,
+    /// 文件名 -> 文件 JSON 内容
+    pub files: Map<String, Value>,
+}
+
+/// 导入结果
+#[derive(Debug, Serialize)]
+pub struct AgentConfigImportResult {
+    /// 已导入的配置文件
+    pub imported: Vec<String>,
+    /// 覆盖前已备份为 .bak 的配置文件
+    pub backed_up: Vec<String>,
+}
+
+/// 参与导出/导入的配置文件（文件名与路径）
+fn config_files() -> Vec<(&'static str, PathBuf)> {
+    vec![
+        ("app_settings.json", directories::get_app_settings_file()),
+        (
+            "antigravity_path.json",
+            directories::get_antigravity_path_file(),
+        ),
+        ("window_state.json", directories::get_window_state_file()),
+        (
+            "active_profile.json",
+            directories::get_active_profile_file(),
+        ),
+    ]
+}
+
+/// 校验单个配置文件内容是否符合对应结构
+fn validate_config_file(name: &str, content: &Value) -> Result<(), String> {
+    let result = match name {
+        "app_settings.json" => {
+            serde_json::from_value::<crate::app_settings::AppSettings>(content.clone()).map(|_| ())
+        }
+        "antigravity_path.json" => serde_json::from_value::<
+            crate::antigravity::path_config::AntigravityPathConfig,
+        >(content.clone())
+        .map(|_| ()),
+        "window_state.json" => {
+            serde_json::from_value::<crate::window::state_manager::WindowState>(content.clone())
+                .map(|_| ())
+        }
+        "active_profile.json" => {
+            if let Some(profile) = content.get("active_profile").and_then(|v| v.as_str()) {
+                directories::validate_profile_name(profile)?;
+            }
+            Ok(())
+        }
+        _ => return Err(format!("归档中包含未知的配置文件: {}", name)),
+    };
+    result.map_err(|e| format!("配置文件 {} 格式无效: {}", name, e))
+}
+
+/// 导出应用配置到指定路径，返回实际导出的配置文件名
+pub fn export_agent_config(dest_path: &Path) -> Result<Vec<String>, String> {
+    let mut files = Map::new();
+    for (name, path) in config_files() {
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        match serde_json::from_str::<Value>(&content) {
+            Ok(value) => {
+                files.insert(name.to_string(), value);
+            }
+            Err(e) => tracing::warn!("⚠️ 跳过无法解析的配置文件 {}: {}", name, e),
+        }
+    }
+
+    let archive = AgentConfigArchive {
+        format: ARCHIVE_FORMAT.to_string(),
+        version: ARCHIVE_VERSION,
+        exported_at: chrono::Local::now().to_rfc3339(),
+        files,
+    };
+    let json =
+        serde_json::to_string_pretty(&archive).map_err(|e| format!("序列化配置归档失败: {}", e))?;
+    fs::write(dest_path, json).map_err(|e| format!("写入配置归档失败: {}", e))?;
+
+    let exported: Vec<String> = archive.files.keys().cloned().collect();
+    tracing::info!(
+        "✅ 已导出 {} 个配置文件到 {}",
+        exported.len(),
+        dest_path.display()
+    );
+    Ok(exported)
+}
+
+/// 读取并校验配置归档；全部文件校验通过才返回，避免只导入一部分
+pub fn read_agent_config_archive(src_path: &Path) -> Result<AgentConfigArchive, String> {
+    let content = fs::read_to_string(src_path).map_err(|e| format!("读取配置归档失败: {}", e))?;
+    let archive: AgentConfigArchive =
+        serde_json::from_str(&content).map_err(|e| format!("配置归档格式无效: {}", e))?;
+
+    if archive.format != ARCHIVE_FORMAT {
+        return Err(format!("不是应用配置归档（format = {}）", archive.format));
+    }
+    if archive.version > ARCHIVE_VERSION {
+        return Err(format!(
+            "配置归档版本 {} 高于当前支持的版本 {}，请升级应用",
+            archive.version, ARCHIVE_VERSION
+        ));
+    }
+    for (name, value) in &archive.files {
+        validate_config_file(name, value)?;
+    }
+
+    Ok(archive)
+}
+
+/// 覆盖前把已有配置文件复制为 .bak，返回是否进行了备份
+pub fn backup_existing_config(path: &Path) -> Result<bool, String> {
+    if !path.exists() {
+        return Ok(false);
+    }
+    let bak_path = path.with_extension("json.bak");
+    fs::copy(path, &bak_path)
+        .map_err(|e| format!("备份配置文件 {} 失败: {}", path.display(), e))?;
+    Ok(true)
+}
+
+/// 将归档中除 app_settings.json 以外的配置文件写入配置目录
+///
+/// app_settings.json 需要同步到运行中的设置管理器，由调用方单独处理
+pub fn write_config_files(
+    archive: &AgentConfigArchive,
+    result: &mut AgentConfigImportResult,
+) -> Result<(), String> {
+    for (name, path) in config_files() {
+        if name == "app_settings.json" {
+            continue;
+        }
+        let Some(value) = archive.files.get(name) else {
+            continue;
+        };
+        if backup_existing_config(&path)? {
+            result.backed_up.push(name.to_string());
+        }
+        let json = serde_json::to_string_pretty(value)
+            .map_err(|e| format!("序列化 {} 失败: {}", name, e))?;
+        fs::write(&path, json).map_err(|e| format!("写入 {} 失败: {}", name, e))?;
+        result.imported.push(name.to_string());
+    }
+    Ok(())
+}
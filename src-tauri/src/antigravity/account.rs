@@ -1,6 +1,26 @@
 use base64::Engine;
 use prost::Message;
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
 use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 账户操作（切换、登录新账户、清除数据）的全局互斥锁，防止并发操作损坏数据库
+static ACCOUNT_OPERATION_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// 开始一次账户操作；已有操作进行中时立即返回错误而不是排队等待
+///
+/// 返回的 guard 在操作结束（drop）时释放锁
+pub fn try_begin_account_operation() -> Result<tokio::sync::MutexGuard<'static, ()>, String> {
+    ACCOUNT_OPERATION_LOCK
+        .try_lock()
+        .map_err(|_| "已有账户操作正在进行中，请稍后再试".to_string())
+}
+
+/// 当前是否有账户操作正在进行
+pub fn is_account_operation_in_progress() -> bool {
+    ACCOUNT_OPERATION_LOCK.try_lock().is_err()
+}
 
 /// 将 jetskiStateSync.agentManagerInitState 作为 SessionResponse proto 解码
 pub fn decode_jetski_state_proto(b64: &str) -> Result<Value, String> {
@@ -29,6 +49,312 @@ pub fn decode_jetski_state_proto(b64: &str) -> Result<Value, String> {
     Ok(session_response_to_json(&msg))
 }
 
+/// 从 jetskiStateSync.agentManagerInitState 中提取登录邮箱
+pub fn decode_jetski_state_email(b64: &str) -> Result<String, String> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(b64.trim())
+        .map_err(|e| format!("jetskiStateSync Base64 解码失败: {}", e))?;
+    let msg = crate::proto::SessionResponse::decode(bytes.as_slice())
+        .map_err(|e| format!("jetskiStateSync Protobuf 解码失败: {}", e))?;
+
+    msg.context
+        .map(|c| c.email)
+        .filter(|email| !email.is_empty())
+        .ok_or_else(|| "jetskiStateSync 中未找到邮箱字段".to_string())
+}
+
+/// 从当前 Antigravity 数据库读取 jetskiStateSync 原始值，并解码出账户邮箱
+///
+/// 返回 `(jetski_state, email)`，用于生成备份文件
+pub fn read_current_jetski_state() -> Result<(String, String), String> {
+    // 连接到 SQLite 数据库并获取认证信息（数据库被锁时自动重试）
+    let conn = open_antigravity_db_read()?;
+
+    // jetski 状态（必需）
+    let jetski_state = query_jetski_state(&conn)?
+        .ok_or_else(|| "未找到 jetskiStateSync.agentManagerInitState".to_string())?;
+
+    // 从 jetski proto 解码邮箱（仅用于文件名）
+    let email = decode_jetski_state_email(&jetski_state)
+        .map_err(|e| format!("{}，无法确定备份文件名", e))?;
+
+    Ok((jetski_state, email))
+}
+
+/// 从指定数据库读取 jetskiStateSync 原始值及其邮箱
+///
+/// 用于同时存在多个安装时逐个读取；数据库中没有登录账户时返回 `None`
+pub fn read_jetski_state_at(db_path: &Path) -> Result<Option<(String, String)>, String> {
+    let conn = open_db_with_retry(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let Some(jetski_state) = query_jetski_state(&conn)? else {
+        return Ok(None);
+    };
+    let email = decode_jetski_state_email(&jetski_state)
+        .map_err(|e| format!("{}，无法确定备份文件名", e))?;
+    Ok(Some((jetski_state, email)))
+}
+
+fn query_jetski_state(conn: &Connection) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT value FROM ItemTable WHERE key = ?",
+        [crate::constants::database::AGENT_STATE],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| format!("查询 jetskiStateSync.agentManagerInitState 失败: {}", e))
+}
+
+/// 测试账户使用的占位令牌
+pub const TEST_ACCOUNT_PLACEHOLDER_TOKEN: &str = "test-placeholder-token";
+
+/// 为测试账户构造只包含邮箱和占位令牌的 jetskiStateSync（Base64 编码的 SessionResponse）
+pub fn encode_test_jetski_state(email: &str) -> String {
+    let msg = crate::proto::SessionResponse {
+        auth: Some(crate::proto::AuthInfo {
+            access_token: TEST_ACCOUNT_PLACEHOLDER_TOKEN.to_string(),
+            ..Default::default()
+        }),
+        context: Some(crate::proto::UserContext {
+            email: email.to_string(),
+            plan_name: "Test".to_string(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    base64::engine::general_purpose::STANDARD.encode(msg.encode_to_vec())
+}
+
+/// 打开数据库时的最大尝试次数
+const DB_OPEN_ATTEMPTS: u32 = 3;
+
+/// 每次尝试的 SQLite busy_timeout
+const DB_BUSY_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// 首次重试前的等待时间，之后每次翻倍（250ms、500ms），加上 busy_timeout 总计约 1.5s
+const DB_RETRY_BASE_DELAY: Duration = Duration::from_millis(250);
+
+/// 打开数据库并设置 busy_timeout，Antigravity 持有锁时按指数退避重试
+///
+/// 通过一次轻量查询确认数据库可访问，所有尝试都失败后才返回错误；
+/// 打开后检查表结构，与预期的 `ItemTable(key, value)` 不符时返回明确的错误。
+/// 重试期间会阻塞当前线程（最多约 1.5s），异步上下文中须放进 `spawn_blocking` 调用
+pub fn open_db_with_retry(db_path: &Path, flags: OpenFlags) -> Result<Connection, String> {
+    let conn = open_db_with_retry_unchecked(db_path, flags)?;
+    let compatibility = check_db_schema(&conn)?;
+    if !compatibility.compatible {
+        return Err(format!(
+            "不支持的 Antigravity 数据库结构 ({}): {}",
+            db_path.display(),
+            compatibility.message
+        ));
+    }
+    Ok(conn)
+}
+
+/// 同 [`open_db_with_retry`]，但不检查表结构（用于诊断结构本身）
+pub fn open_db_with_retry_unchecked(
+    db_path: &Path,
+    flags: OpenFlags,
+) -> Result<Connection, String> {
+    let mut delay = DB_RETRY_BASE_DELAY;
+    let mut last_error = String::new();
+
+    for attempt in 1..=DB_OPEN_ATTEMPTS {
+        let result = Connection::open_with_flags(db_path, flags).and_then(|conn| {
+            conn.busy_timeout(DB_BUSY_TIMEOUT)?;
+            conn.query_row("SELECT count(*) FROM sqlite_master", [], |_| Ok(()))?;
+            Ok(conn)
+        });
+        match result {
+            Ok(conn) => return Ok(conn),
+            Err(e) => {
+                tracing::warn!(
+                    target: "database::open",
+                    attempt,
+                    error = %e,
+                    "打开数据库失败，准备重试"
+                );
+                last_error = e.to_string();
+            }
+        }
+        if attempt < DB_OPEN_ATTEMPTS {
+            std::thread::sleep(delay);
+            delay *= 2;
+        }
+    }
+
+    Err(format!(
+        "数据库被占用，重试 {} 次后仍无法打开 ({}): {}",
+        DB_OPEN_ATTEMPTS,
+        db_path.display(),
+        last_error
+    ))
+}
+
+/// 状态数据库结构兼容性检查结果
+#[derive(Debug, serde::Serialize)]
+pub struct DbCompatibility {
+    /// 是否存在带 key/value 列的 ItemTable
+    pub compatible: bool,
+    /// 数据库中的所有表
+    pub found_tables: Vec<String>,
+    pub message: String,
+}
+
+/// 检查数据库是否为预期的 `ItemTable(key, value)` 结构
+pub fn check_db_schema(conn: &Connection) -> Result<DbCompatibility, String> {
+    let found_tables = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| format!("读取表列表失败: {}", e))?;
+
+    let message = if !found_tables.iter().any(|t| t == "ItemTable") {
+        format!(
+            "数据库中没有 ItemTable 表（现有表: {}）",
+            found_tables.join(", ")
+        )
+    } else {
+        let columns = conn
+            .prepare("SELECT name FROM pragma_table_info('ItemTable')")
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| row.get::<_, String>(0))?
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(|e| format!("读取表结构失败: {}", e))?;
+        if columns.iter().any(|c| c == "key") && columns.iter().any(|c| c == "value") {
+            return Ok(DbCompatibility {
+                compatible: true,
+                found_tables,
+                message: "数据库结构兼容".to_string(),
+            });
+        }
+        format!(
+            "ItemTable 缺少 key/value 列（实际列: {}）",
+            columns.join(", ")
+        )
+    };
+
+    Ok(DbCompatibility {
+        compatible: false,
+        found_tables,
+        message,
+    })
+}
+
+/// 检查当前 Antigravity 状态数据库的结构是否与预期一致（只读）
+pub fn check_antigravity_db_compatibility() -> Result<DbCompatibility, String> {
+    let db_path = existing_target_db_path(crate::platform::TargetApp::Antigravity)?;
+    let conn = open_db_with_retry_unchecked(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    check_db_schema(&conn)
+}
+
+/// 解析指定目标应用的状态数据库路径，并确认文件存在
+fn existing_target_db_path(target: crate::platform::TargetApp) -> Result<PathBuf, String> {
+    // Antigravity 区分未安装和未创建数据库，给出不同的处理建议
+    if target == crate::platform::TargetApp::Antigravity {
+        return crate::platform::detect_antigravity_db().into_result();
+    }
+
+    let app_name = target.display_name();
+    let db_path = target
+        .db_path()
+        .ok_or_else(|| format!("未找到{}安装位置", app_name))?;
+    if !db_path.exists() {
+        return Err(format!(
+            "{} 状态数据库文件不存在: {}",
+            app_name,
+            db_path.display()
+        ));
+    }
+    Ok(db_path)
+}
+
+/// 以只读方式打开 Antigravity 状态数据库（带重试）
+pub fn open_antigravity_db_read() -> Result<Connection, String> {
+    open_target_db_read(crate::platform::TargetApp::Antigravity)
+}
+
+/// 以读写方式打开 Antigravity 状态数据库（带重试）
+pub fn open_antigravity_db_write() -> Result<Connection, String> {
+    let db_path = existing_target_db_path(crate::platform::TargetApp::Antigravity)?;
+    open_db_write(&db_path)
+}
+
+/// 以读写方式打开指定路径的状态数据库（带重试和表结构检查）
+///
+/// 所有写入 ItemTable 的操作都应通过这里打开连接
+pub fn open_db_write(db_path: &Path) -> Result<Connection, String> {
+    open_db_with_retry(db_path, OpenFlags::default())
+}
+
+/// 以只读方式打开指定目标应用的状态数据库（带重试）
+pub fn open_target_db_read(target: crate::platform::TargetApp) -> Result<Connection, String> {
+    let db_path = existing_target_db_path(target)?;
+    open_db_with_retry(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+}
+
+/// 读取当前 Antigravity 数据库中登录账户的邮箱
+///
+/// 数据库不存在、未登录或解码失败时返回 `None`
+pub fn read_current_account_email() -> Option<String> {
+    let conn = open_antigravity_db_read().ok()?;
+    let state: String = conn
+        .query_row(
+            "SELECT value FROM ItemTable WHERE key = ?",
+            [crate::constants::database::AGENT_STATE],
+            |row| row.get(0),
+        )
+        .optional()
+        .ok()??;
+
+    decode_jetski_state_email(&state).ok()
+}
+
+/// 当前 Antigravity 数据库中是否存在登录状态（`antigravityAuthStatus`）
+///
+/// 恢复账户时会删除该键，由 Antigravity 启动登录后重新写入，
+/// 因此可用来判断恢复后的状态是否已被 Antigravity 实际接管
+pub fn has_auth_status() -> bool {
+    let Ok(conn) = open_antigravity_db_read() else {
+        return false;
+    };
+    conn.query_row(
+        "SELECT 1 FROM ItemTable WHERE key = ?",
+        [crate::constants::database::AUTH_STATUS],
+        |_| Ok(()),
+    )
+    .optional()
+    .ok()
+    .flatten()
+    .is_some()
+}
+
+/// 轻量读取邮箱时的 SQLite busy_timeout（只尝试一次，不重试）
+const QUICK_READ_BUSY_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// 快速读取当前登录账户的邮箱（用于托盘提示等频繁调用的场景）
+///
+/// 与 `read_current_account_email` 不同，只以只读方式打开一次、busy_timeout 很短，
+/// 数据库被占用时直接返回 `None` 而不是等待重试
+pub fn read_current_account_email_quick() -> Option<String> {
+    let db_path = existing_target_db_path(crate::platform::TargetApp::Antigravity).ok()?;
+    let conn = Connection::open_with_flags(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY).ok()?;
+    conn.busy_timeout(QUICK_READ_BUSY_TIMEOUT).ok()?;
+    let state: String = conn
+        .query_row(
+            "SELECT value FROM ItemTable WHERE key = ?",
+            [crate::constants::database::AGENT_STATE],
+            |row| row.get(0),
+        )
+        .optional()
+        .ok()??;
+
+    decode_jetski_state_email(&state).ok()
+}
+
 fn session_response_to_json(msg: &crate::proto::SessionResponse) -> Value {
     use crate::proto::*;
 
@@ -0,0 +1,69 @@
+//! 账户头像缓存
+//! 头像下载一次后按 URL 哈希缓存到本地，离线时直接使用缓存
+
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 从备份的 antigravityAuthStatus 中读取头像地址（profileUrl / picture）
+pub fn profile_url_from_backup(account_file: &Path) -> Option<String> {
+    let backup = crate::antigravity::backup_format::read_backup_file(account_file).ok()?;
+    let auth_status: serde_json::Value = backup
+        .get(crate::constants::database::AUTH_STATUS)
+        .and_then(|v| v.as_str())
+        .and_then(|s| serde_json::from_str(s).ok())?;
+    ["profileUrl", "picture"]
+        .iter()
+        .find_map(|key| auth_status.get(*key).and_then(|v| v.as_str()))
+        .filter(|url| url.starts_with("http"))
+        .map(str::to_string)
+}
+
+/// 头像缓存文件路径：avatars/{sha256(url)}
+fn cache_path_for(url: &str) -> PathBuf {
+    let hash = Sha256::digest(url.as_bytes());
+    let name: String = hash.iter().map(|b| format!("{:02x}", b)).collect();
+    crate::directories::get_avatar_cache_directory().join(name)
+}
+
+/// 获取头像的本地缓存路径，缓存不存在时下载；下载失败（如离线）返回 None
+///
+/// `timeout` 取自应用设置中的 http_timeout_ms
+pub async fn get_cached_avatar(url: &str, timeout: Duration) -> Option<PathBuf> {
+    let cache_path = cache_path_for(url);
+    if cache_path.exists() {
+        return Some(cache_path);
+    }
+
+    match download_avatar(url, &cache_path, timeout).await {
+        Ok(()) => Some(cache_path),
+        Err(e) => {
+            tracing::warn!(target: "account::avatar", error = %e, "下载头像失败，跳过");
+            None
+        }
+    }
+}
+
+async fn download_avatar(url: &str, cache_path: &Path, timeout: Duration) -> Result<(), String> {
+    let client = tauri_plugin_http::reqwest::Client::builder()
+        .timeout(timeout)
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("请求头像失败: {}", e))?;
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("读取头像数据失败: {}", e))?;
+
+    // 先写临时文件再重命名，避免中断时留下不完整的缓存
+    let tmp_path = cache_path.with_extension("tmp");
+    fs::write(&tmp_path, &bytes).map_err(|e| format!("写入头像缓存失败: {}", e))?;
+    fs::rename(&tmp_path, cache_path).map_err(|e| format!("保存头像缓存失败: {}", e))?;
+    Ok(())
+}
@@ -0,0 +1,588 @@
+//! 账户备份文件格式版本管理
+//! 新备份写入 format_version，读取旧备份时统一升级到当前格式；
+//! 增量备份（backup_kind = delta）在读取时还原为完整内容
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::constants::database;
+
+/// 当前备份格式版本
+///
+/// - 0：无版本标记。旧版本直接导出数据库键值，可能包含多余键，
+///   或把 jetskiStateSync 存为 `{"jetskiStateSync": {"agentManagerInitState": ...}}`
+/// - 1：仅保留 jetskiStateSync.agentManagerInitState（及可选的 antigravityAuthStatus / test 标记）
+pub const CURRENT_FORMAT_VERSION: u64 = 1;
+
+/// 备份文件中的版本字段名
+pub const FORMAT_VERSION_KEY: &str = "format_version";
+
+/// 备份文件中的类型字段名：full（默认，缺省即为 full）或 delta
+pub const BACKUP_KIND_KEY: &str = "backup_kind";
+
+/// 增量备份中引用共享状态数据的字段名（内容的 sha256）
+pub const AGENT_STATE_REF_KEY: &str = "agent_state_ref";
+
+/// 用户为账户添加的备注/标签，嵌入在备份中（不写入数据库）
+pub const AGENT_META_KEY: &str = "_agent_meta";
+
+/// 修复后仍缺少可用登录状态的备份标记，需要重新登录后再备份
+pub const NEEDS_RELOGIN_KEY: &str = "needs_relogin";
+
+/// 备份是否被标记为需要重新登录
+pub fn needs_relogin(backup: &Value) -> bool {
+    backup.get(NEEDS_RELOGIN_KEY).and_then(|v| v.as_bool()) == Some(true)
+}
+
+/// 登出状态备份标记：恢复时删除登录状态，其他设置保持不变
+pub const LOGGED_OUT_KEY: &str = "logged_out";
+
+/// 是否为登出状态备份
+pub fn is_logged_out_backup(backup: &Value) -> bool {
+    backup.get(LOGGED_OUT_KEY).and_then(|v| v.as_bool()) == Some(true)
+}
+
+/// 复制出的备份记录来源账户的邮箱，便于追溯副本来源
+pub const DUPLICATE_OF_KEY: &str = "duplicate_of";
+
+/// 账户备注与标签
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccountMeta {
+    pub notes: String,
+    pub tags: Vec<String>,
+}
+
+impl AccountMeta {
+    /// 从备份内容中读取备注与标签，不存在或格式无效时返回空值
+    pub fn from_backup(backup: &Value) -> Self {
+        backup
+            .get(AGENT_META_KEY)
+            .and_then(|meta| serde_json::from_value(meta.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// 是否没有任何备注或标签
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty() && self.tags.is_empty()
+    }
+}
+
+/// 增量备份共享数据目录：账户目录下的 .blobs/{sha256}
+fn blob_directory() -> PathBuf {
+    crate::directories::get_accounts_directory().join(".blobs")
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// 为新备份内容写入当前格式版本
+pub fn stamp_format_version(backup: &mut Value) {
+    if let Some(obj) = backup.as_object_mut() {
+        obj.insert(
+            FORMAT_VERSION_KEY.to_string(),
+            Value::from(CURRENT_FORMAT_VERSION),
+        );
+    }
+}
+
+/// 将任意版本的备份内容升级为当前格式（仅在内存中，不回写文件）
+pub fn upgrade_backup(backup: Value) -> Result<Value, String> {
+    let Value::Object(obj) = backup else {
+        return Err("备份文件格式无效：顶层不是 JSON 对象".to_string());
+    };
+
+    let version = obj
+        .get(FORMAT_VERSION_KEY)
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    if version > CURRENT_FORMAT_VERSION {
+        return Err(format!(
+            "备份格式版本 {} 高于当前支持的版本 {}，请升级应用",
+            version, CURRENT_FORMAT_VERSION
+        ));
+    }
+    if version == CURRENT_FORMAT_VERSION {
+        return Ok(Value::Object(obj));
+    }
+
+    Ok(Value::Object(upgrade_v0_to_v1(obj)))
+}
+
+/// v0 -> v1：规范化 jetskiStateSync 键名，丢弃其余数据库键
+fn upgrade_v0_to_v1(obj: Map<String, Value>) -> Map<String, Value> {
+    let agent_state = obj.get(database::AGENT_STATE).cloned().or_else(|| {
+        obj.get("jetskiStateSync")
+            .and_then(|v| v.get("agentManagerInitState"))
+            .cloned()
+    });
+
+    let mut upgraded = Map::new();
+    if let Some(state) = agent_state {
+        upgraded.insert(database::AGENT_STATE.to_string(), state);
+    }
+    for key in [database::AUTH_STATUS, "test", AGENT_META_KEY] {
+        if let Some(value) = obj.get(key) {
+            upgraded.insert(key.to_string(), value.clone());
+        }
+    }
+    upgraded.insert(
+        FORMAT_VERSION_KEY.to_string(),
+        Value::from(CURRENT_FORMAT_VERSION),
+    );
+    upgraded
+}
+
+/// 备份文件是否为 gzip 压缩格式（按 .json.gz 后缀判断）
+fn is_compressed(path: &Path) -> bool {
+    path.to_string_lossy()
+        .ends_with(crate::directories::COMPRESSED_BACKUP_SUFFIX)
+}
+
+/// 读取备份文件的原始 JSON 文本（.json.gz 自动解压）
+pub fn read_backup_text(path: &Path) -> Result<String, String> {
+    let read_error = |e: std::io::Error| format!("读取文件失败 {}: {}", path.display(), e);
+    if !is_compressed(path) {
+        return fs::read_to_string(path).map_err(read_error);
+    }
+
+    let file = fs::File::open(path).map_err(read_error)?;
+    let mut content = String::new();
+    GzDecoder::new(file)
+        .read_to_string(&mut content)
+        .map_err(|e| format!("解压备份文件失败 {}: {}", path.display(), e))?;
+    Ok(content)
+}
+
+/// 读取备份文件并升级到当前格式（增量备份会还原为完整内容，压缩备份自动解压）
+pub fn read_backup_file(path: &Path) -> Result<Value, String> {
+    let content = read_backup_text(path)?;
+    let value: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("解析 JSON 失败 {}: {}", path.display(), e))?;
+    resolve_delta(upgrade_backup(value)?)
+}
+
+/// 备份写入结果
+#[derive(Debug, Clone, Copy)]
+pub struct WrittenBackup {
+    /// 未压缩的 JSON 字节数
+    pub json_bytes: usize,
+    /// 实际写入磁盘的字节数
+    pub written_bytes: usize,
+}
+
+impl WrittenBackup {
+    /// 压缩率（写入字节数 / 未压缩字节数），未压缩时为 1.0
+    pub fn compression_ratio(&self) -> f64 {
+        if self.json_bytes == 0 {
+            return 1.0;
+        }
+        self.written_bytes as f64 / self.json_bytes as f64
+    }
+}
+
+/// 写入账户备份（自动写入格式版本）
+///
+/// `delta` 为 true 时，jetskiStateSync 数据按内容哈希存入共享目录，
+/// 备份文件只保存引用；内容未变化时复用已有数据，不再重复写入。
+/// 路径以 .json.gz 结尾时以 gzip 压缩写入
+pub fn write_backup_file(
+    path: &Path,
+    mut backup: Value,
+    delta: bool,
+) -> Result<WrittenBackup, String> {
+    stamp_format_version(&mut backup);
+    let obj = backup
+        .as_object_mut()
+        .ok_or_else(|| "备份内容必须是 JSON 对象".to_string())?;
+
+    if delta {
+        if let Some(Value::String(state)) = obj.remove(database::AGENT_STATE) {
+            let hash = sha256_hex(state.as_bytes());
+            let blob_dir = blob_directory();
+            let blob_path = blob_dir.join(&hash);
+            if !blob_path.exists() {
+                fs::create_dir_all(&blob_dir)
+                    .map_err(|e| format!("创建增量备份目录失败: {}", e))?;
+                // 先写临时文件再重命名，避免中断时留下不完整的数据
+                let tmp_path = blob_dir.join(format!("{hash}.tmp"));
+                fs::write(&tmp_path, &state)
+                    .and_then(|_| fs::rename(&tmp_path, &blob_path))
+                    .map_err(|e| format!("写入增量备份数据失败: {}", e))?;
+            }
+            obj.insert(AGENT_STATE_REF_KEY.to_string(), Value::String(hash));
+            obj.insert(BACKUP_KIND_KEY.to_string(), Value::from("delta"));
+        }
+    } else {
+        obj.insert(BACKUP_KIND_KEY.to_string(), Value::from("full"));
+    }
+
+    write_backup_raw(path, &backup)
+}
+
+/// 按原样写入备份内容（不处理格式版本和增量数据），路径以 .json.gz 结尾时以 gzip 压缩写入
+fn write_backup_raw(path: &Path, backup: &Value) -> Result<WrittenBackup, String> {
+    let json =
+        serde_json::to_string_pretty(backup).map_err(|e| format!("序列化备份失败: {}", e))?;
+    let bytes = if is_compressed(path) {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(json.as_bytes())
+            .and_then(|_| encoder.finish())
+            .map_err(|e| format!("压缩备份失败: {}", e))?
+    } else {
+        json.clone().into_bytes()
+    };
+    fs::write(path, &bytes).map_err(|e| format!("写入备份文件失败 {}: {}", path.display(), e))?;
+
+    Ok(WrittenBackup {
+        json_bytes: json.len(),
+        written_bytes: bytes.len(),
+    })
+}
+
+/// 备份前预留的额外空间（序列化开销、临时文件等）
+const BACKUP_SPACE_MARGIN: u64 = 1024 * 1024;
+
+/// 账户备份目录所在磁盘卷的可用空间（字节）
+pub fn accounts_volume_free_bytes() -> Result<u64, String> {
+    let accounts_dir = crate::directories::get_accounts_directory();
+    // 目录可能尚未创建，取最近的已存在上级目录来定位所在卷
+    let existing = accounts_dir
+        .ancestors()
+        .find(|p| p.exists())
+        .ok_or_else(|| format!("无法定位账户目录所在的磁盘: {}", accounts_dir.display()))?;
+    let existing = fs::canonicalize(existing).unwrap_or_else(|_| existing.to_path_buf());
+
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|disk| existing.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+        .ok_or_else(|| format!("无法定位账户目录所在的磁盘: {}", existing.display()))
+}
+
+/// 写入备份前检查磁盘空间，空间不足时直接返回错误，避免写到一半失败
+///
+/// 无法获取可用空间时只记录警告，不阻止备份
+pub fn ensure_backup_space(estimated_bytes: u64) -> Result<(), String> {
+    let free = match accounts_volume_free_bytes() {
+        Ok(free) => free,
+        Err(e) => {
+            tracing::warn!(error = %e, "获取磁盘可用空间失败，跳过空间检查");
+            return Ok(());
+        }
+    };
+
+    let required = estimated_bytes.saturating_add(BACKUP_SPACE_MARGIN);
+    if free < required {
+        return Err(format!(
+            "磁盘空间不足：备份约需 {} 字节，账户目录所在磁盘仅剩 {} 字节",
+            required, free
+        ));
+    }
+    Ok(())
+}
+
+/// 更新备份中的备注与标签，其余内容（包括增量引用、压缩格式）保持不变
+pub fn update_account_meta(path: &Path, meta: &AccountMeta) -> Result<(), String> {
+    let mut backup: Value = serde_json::from_str(&read_backup_text(path)?)
+        .map_err(|e| format!("解析 JSON 失败 {}: {}", path.display(), e))?;
+    let obj = backup
+        .as_object_mut()
+        .ok_or_else(|| "备份内容必须是 JSON 对象".to_string())?;
+
+    if meta.is_empty() {
+        obj.remove(AGENT_META_KEY);
+    } else {
+        let value = serde_json::to_value(meta).map_err(|e| format!("序列化备注失败: {}", e))?;
+        obj.insert(AGENT_META_KEY.to_string(), value);
+    }
+    write_backup_raw(path, &backup).map(|_| ())
+}
+
+/// 标记备份为 `email` 账户的副本，其余内容（包括增量引用、压缩格式）保持不变
+pub fn mark_duplicate_of(path: &Path, email: &str) -> Result<(), String> {
+    let mut backup: Value = serde_json::from_str(&read_backup_text(path)?)
+        .map_err(|e| format!("解析 JSON 失败 {}: {}", path.display(), e))?;
+    backup
+        .as_object_mut()
+        .ok_or_else(|| "备份内容必须是 JSON 对象".to_string())?
+        .insert(DUPLICATE_OF_KEY.to_string(), Value::from(email));
+    write_backup_raw(path, &backup).map(|_| ())
+}
+
+/// 将增量备份还原为完整内容（完整备份原样返回）
+fn resolve_delta(mut backup: Value) -> Result<Value, String> {
+    let Some(obj) = backup.as_object_mut() else {
+        return Ok(backup);
+    };
+    if obj.get(BACKUP_KIND_KEY).and_then(|v| v.as_str()) != Some("delta") {
+        return Ok(backup);
+    }
+
+    let hash = obj
+        .remove(AGENT_STATE_REF_KEY)
+        .and_then(|v| v.as_str().map(str::to_string))
+        .ok_or_else(|| format!("增量备份缺少 {}", AGENT_STATE_REF_KEY))?;
+    if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("增量备份引用无效: {}", hash));
+    }
+    let state = fs::read_to_string(blob_directory().join(&hash))
+        .map_err(|e| format!("读取增量备份数据 {} 失败: {}", hash, e))?;
+    if sha256_hex(state.as_bytes()) != hash {
+        return Err(format!("增量备份数据 {} 校验失败，可能已损坏", hash));
+    }
+
+    obj.insert(database::AGENT_STATE.to_string(), Value::String(state));
+    obj.insert(BACKUP_KIND_KEY.to_string(), Value::from("full"));
+    Ok(backup)
+}
+
+/// 删除不再被任何备份（含 pruned/ 下的备份）引用的增量备份数据，返回删除数量
+pub fn remove_unreferenced_blobs() -> usize {
+    let blob_dir = blob_directory();
+    let Ok(blobs) = fs::read_dir(&blob_dir) else {
+        return 0;
+    };
+
+    let accounts_dir = crate::directories::get_accounts_directory();
+    let mut referenced: HashSet<String> = HashSet::new();
+    for dir in [accounts_dir.clone(), accounts_dir.join("pruned")] {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if !crate::directories::is_account_backup_file(&path) {
+                continue;
+            }
+            let reference = read_backup_text(&path)
+                .ok()
+                .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+                .and_then(|v| v.get(AGENT_STATE_REF_KEY)?.as_str().map(str::to_string));
+            if let Some(hash) = reference {
+                referenced.insert(hash);
+            }
+        }
+    }
+
+    let mut removed = 0;
+    for path in blobs.flatten().map(|entry| entry.path()) {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !referenced.contains(&name) && fs::remove_file(&path).is_ok() {
+            removed += 1;
+        }
+    }
+    if removed > 0 {
+        tracing::info!("🧹 已删除 {} 个未被引用的增量备份数据", removed);
+    }
+    removed
+}
+
+/// 单个备份文件的校验结果
+#[derive(Debug, Serialize)]
+pub struct BackupVerification {
+    /// 备份名（文件名去掉 .json / .json.gz）
+    pub name: String,
+    pub valid: bool,
+    /// 发现的问题，valid 为 true 时为空
+    pub issues: Vec<String>,
+}
+
+/// 校验单个备份文件：JSON 可解析、jetskiStateSync 可解码出邮箱、
+/// antigravityAuthStatus（如存在）为合法 JSON
+pub fn verify_backup_file(path: &Path) -> BackupVerification {
+    let name = crate::directories::account_name_from_path(path).unwrap_or_default();
+    let mut issues = Vec::new();
+
+    match read_backup_file(path) {
+        Err(e) => issues.push(e),
+        Ok(backup) => {
+            match backup.get(database::AGENT_STATE).and_then(|v| v.as_str()) {
+                // 登出状态备份本就不含登录状态
+                None if is_logged_out_backup(&backup) => {}
+                None => issues.push(format!("缺少 {}", database::AGENT_STATE)),
+                Some(state) => {
+                    // 备份名不一定是邮箱（导入时的自定义名称、旧目录迁移的 -legacy-N 副本、
+                    // 复制出的备份），因此只要求能解码出邮箱，不与文件名比对
+                    if let Err(e) = crate::antigravity::account::decode_jetski_state_email(state) {
+                        issues.push(e);
+                    }
+                }
+            }
+
+            if let Some(auth_status) = backup.get(database::AUTH_STATUS) {
+                let parsed = auth_status
+                    .as_str()
+                    .and_then(|s| serde_json::from_str::<Value>(s).ok());
+                if parsed.is_none() {
+                    issues.push(format!("{} 不是合法的 JSON", database::AUTH_STATUS));
+                }
+            }
+        }
+    }
+
+    BackupVerification {
+        name,
+        valid: issues.is_empty(),
+        issues,
+    }
+}
+
+/// 单个备份文件的修复结果
+#[derive(Debug, Serialize)]
+pub struct BackupRepair {
+    /// 备份名（文件名去掉 .json / .json.gz）
+    pub name: String,
+    /// 执行的修复项，为空表示备份无需修复（文件未改动）
+    pub repaired: Vec<String>,
+    /// 修复后备份是否仍缺少可用的登录状态
+    pub needs_relogin: bool,
+}
+
+/// 检测并修复残缺或旧格式的备份，必要时回写文件（保持原压缩格式）
+///
+/// 旧格式升级到当前格式；缺失或无法解码的 jetskiStateSync 以空字符串占位，
+/// 并标记 `needs_relogin: true`，使该账户仍能出现在列表中
+pub fn repair_backup_file(path: &Path) -> Result<BackupRepair, String> {
+    let name = crate::directories::account_name_from_path(path).unwrap_or_default();
+    let raw: Value = serde_json::from_str(&read_backup_text(path)?)
+        .map_err(|e| format!("解析 JSON 失败 {}: {}，无法修复", path.display(), e))?;
+    let Value::Object(original) = raw else {
+        return Err("备份文件格式无效：顶层不是 JSON 对象，无法修复".to_string());
+    };
+
+    let mut repaired = Vec::new();
+    let version = original
+        .get(FORMAT_VERSION_KEY)
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    if version > CURRENT_FORMAT_VERSION {
+        return Err(format!(
+            "备份格式版本 {} 高于当前支持的版本 {}，请升级应用",
+            version, CURRENT_FORMAT_VERSION
+        ));
+    }
+
+    let mut obj = if version < CURRENT_FORMAT_VERSION {
+        let upgraded = upgrade_v0_to_v1(original.clone());
+        if !original.contains_key(database::AGENT_STATE)
+            && upgraded.contains_key(database::AGENT_STATE)
+        {
+            repaired.push("规范化嵌套的 jetskiStateSync 键名".to_string());
+        }
+        let dropped: Vec<&str> = original
+            .keys()
+            .map(String::as_str)
+            .filter(|key| !upgraded.contains_key(*key) && *key != "jetskiStateSync")
+            .collect();
+        if !dropped.is_empty() {
+            repaired.push(format!("移除多余的数据库键: {}", dropped.join(", ")));
+        }
+        repaired.push(format!(
+            "升级备份格式 v{} → v{}",
+            version, CURRENT_FORMAT_VERSION
+        ));
+        upgraded
+    } else {
+        original
+    };
+
+    // 登出状态备份不含登录状态，无需补全
+    if is_logged_out_backup(&Value::Object(obj.clone())) {
+        if !repaired.is_empty() {
+            write_backup_raw(path, &Value::Object(obj))?;
+        }
+        return Ok(BackupRepair {
+            name,
+            repaired,
+            needs_relogin: false,
+        });
+    }
+
+    // 增量备份从共享数据中取出状态；共享数据丢失时转为完整备份
+    let is_delta = obj.get(BACKUP_KIND_KEY).and_then(|v| v.as_str()) == Some("delta");
+    let state = if is_delta {
+        match resolve_delta(Value::Object(obj.clone())) {
+            Ok(full) => full
+                .get(database::AGENT_STATE)
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            Err(e) => {
+                obj.remove(BACKUP_KIND_KEY);
+                obj.remove(AGENT_STATE_REF_KEY);
+                repaired.push(format!("增量备份数据不可用（{}），已转换为完整备份", e));
+                None
+            }
+        }
+    } else {
+        obj.get(database::AGENT_STATE)
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    };
+
+    if state.is_none() {
+        let reason = if obj.contains_key(database::AGENT_STATE) {
+            "替换非字符串的"
+        } else {
+            "补全缺失的"
+        };
+        obj.insert(database::AGENT_STATE.to_string(), Value::from(""));
+        repaired.push(format!("{} {}（空占位）", reason, database::AGENT_STATE));
+    }
+
+    let auth_status_invalid = obj.get(database::AUTH_STATUS).is_some_and(|value| {
+        value
+            .as_str()
+            .and_then(|s| serde_json::from_str::<Value>(s).ok())
+            .is_none()
+    });
+    if auth_status_invalid {
+        obj.remove(database::AUTH_STATUS);
+        repaired.push(format!("移除无法解析的 {}", database::AUTH_STATUS));
+    }
+
+    let needs_relogin = !state.as_deref().is_some_and(|state| {
+        !state.is_empty() && crate::antigravity::account::decode_jetski_state_email(state).is_ok()
+    });
+    let flagged = obj.get(NEEDS_RELOGIN_KEY).and_then(|v| v.as_bool()) == Some(true);
+    if needs_relogin && !flagged {
+        obj.insert(NEEDS_RELOGIN_KEY.to_string(), Value::Bool(true));
+        repaired.push("登录状态不可用，标记为需要重新登录".to_string());
+    } else if !needs_relogin && obj.contains_key(NEEDS_RELOGIN_KEY) {
+        obj.remove(NEEDS_RELOGIN_KEY);
+        repaired.push("登录状态可用，清除需要重新登录标记".to_string());
+    }
+
+    if !repaired.is_empty() {
+        write_backup_raw(path, &Value::Object(obj))?;
+        tracing::info!(
+            file = %path.display(),
+            repaired = ?repaired,
+            "🔧 已修复备份文件"
+        );
+    }
+
+    Ok(BackupRepair {
+        name,
+        repaired,
+        needs_relogin,
+    })
+}
@@ -2,20 +2,55 @@
 // 负责清除 Antigravity 应用的所有用户认证和设置信息
 
 use rusqlite::{params, Connection};
+use serde::Serialize;
 use std::path::Path;
 
 // 导入 platform_utils 模块
 use crate::platform;
 
-fn clear_database(db_path: &Path, db_name: &str) -> Result<usize, String> {
+/// 单个数据库的清除结果
+#[derive(Debug, Serialize)]
+pub struct DbClearResult {
+    /// 数据库文件名（state.vscdb）
+    pub db: String,
+    /// 实际删除的键
+    pub deleted_keys: Vec<String>,
+    /// 写入的键（如跳过首次启动引导的 antigravityOnboarding）
+    pub written_keys: Vec<String>,
+    /// 受影响的总行数
+    pub rows_affected: usize,
+}
+
+/// 清除 Antigravity 登录数据的结果
+#[derive(Debug, Serialize)]
+pub struct ClearDataResult {
+    /// 各数据库的清除明细
+    pub databases: Vec<DbClearResult>,
+    /// 是否删除了 state.vscdb.backup 文件
+    pub backup_db_deleted: bool,
+    /// 汇总消息
+    pub message: String,
+}
+
+fn clear_database(conn: Connection, db_name: &str) -> Result<DbClearResult, String> {
     tracing::info!(target: "cleanup::database", db_name = %db_name, "开始清理数据库");
-    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+
+    let mut result = DbClearResult {
+        db: db_name.to_string(),
+        deleted_keys: Vec::new(),
+        written_keys: Vec::new(),
+        rows_affected: 0,
+    };
 
     // 仅删除 jetskiStateSync.agentManagerInitState
     let key = "jetskiStateSync.agentManagerInitState";
     let rows = conn
         .execute("DELETE FROM ItemTable WHERE key = ?", [key])
         .unwrap_or(0);
+    if rows > 0 {
+        tracing::debug!(target: "cleanup::database", key = %key, "已删除字段");
+        result.deleted_keys.push(key.to_string());
+    }
 
     // 把 antigravityOnboarding 设置为布尔值 true（写为字符串 "true"） 以跳过首次启动引导
     let onboarding_key = "antigravityOnboarding";
@@ -25,55 +60,49 @@ fn clear_database(db_path: &Path, db_name: &str) -> Result<usize, String> {
             params![onboarding_key, "true"],
         )
         .unwrap_or(0);
-
-    if rows > 0 {
-        tracing::debug!(target: "cleanup::database", key = %key, "已删除字段");
+    if onboarding_rows > 0 {
+        result.written_keys.push(onboarding_key.to_string());
     }
 
-    Ok(rows + onboarding_rows)
+    result.rows_affected = rows + onboarding_rows;
+    Ok(result)
 }
 
-pub async fn clear_all_antigravity_data() -> Result<String, String> {
-    tracing::info!(target: "cleanup::main", "开始清除 Antigravity 用户认证数据（保留设备指纹）");
+pub async fn clear_all_antigravity_data() -> Result<ClearDataResult, String> {
+    // 打开数据库时可能阻塞重试，放到阻塞线程池中执行
+    tokio::task::spawn_blocking(clear_all_antigravity_data_blocking)
+        .await
+        .map_err(|e| format!("清除任务执行失败: {}", e))?
+}
 
-    let app_data = match platform::get_antigravity_db_path() {
-        Some(p) => p,
-        None => {
-            let possible_paths = platform::get_all_antigravity_db_paths();
-            if possible_paths.is_empty() {
-                return Err("未找到 Antigravity 安装位置".to_string());
-            }
-            possible_paths[0].clone()
-        }
-    };
+fn clear_all_antigravity_data_blocking() -> Result<ClearDataResult, String> {
+    tracing::info!(target: "cleanup::main", "开始清除 Antigravity 用户认证数据（保留设备指纹）");
 
-    if !app_data.exists() {
-        return Err(format!(
-            "Antigravity 状态数据库不存在: {}",
-            app_data.display()
-        ));
-    }
+    let app_data = platform::detect_antigravity_db().into_result()?;
 
     let mut msg = String::new();
+    let mut databases = Vec::new();
 
     // 清理主库
     tracing::info!(target: "cleanup::main", "步骤1: 清除 state.vscdb 数据库");
-    match clear_database(&app_data, "state.vscdb") {
-        Ok(c) => {
-            tracing::info!(target: "cleanup::main", cleaned_count = %c, "主数据库已清除");
-            msg.push_str(&format!("主库清理 {} 项", c));
-        }
-        Err(e) => return Err(e),
-    }
+    let main_result = clear_database(
+        crate::antigravity::account::open_antigravity_db_write()?,
+        "state.vscdb",
+    )?;
+    tracing::info!(target: "cleanup::main", cleaned_count = %main_result.rows_affected, "主数据库已清除");
+    msg.push_str(&format!("主库清理 {} 项", main_result.rows_affected));
+    databases.push(main_result);
 
     // 删除备份库
     tracing::info!(target: "cleanup::main", "步骤2: 删除 state.vscdb.backup（如存在）");
     let backup_db = app_data.with_extension("vscdb.backup");
+    let mut backup_db_deleted = false;
     if backup_db.exists() {
         match std::fs::remove_file(&backup_db) {
             Ok(_) => {
                 tracing::info!(target: "cleanup::main", file = %backup_db.display(), "已删除备份数据库文件");
                 msg.push_str("; 备份库文件已删除");
+                backup_db_deleted = true;
             }
             Err(e) => {
                 tracing::warn!(target: "cleanup::main", file = %backup_db.display(), error = %e, "删除备份数据库文件失败")
@@ -83,5 +112,50 @@ pub async fn clear_all_antigravity_data() -> Result<String, String> {
         tracing::debug!(target: "cleanup::main", "备份数据库不存在，跳过");
     }
 
-    Ok(format!("✅ 登出成功: {}", msg))
+    Ok(ClearDataResult {
+        databases,
+        backup_db_deleted,
+        message: format!("✅ 登出成功: {}", msg),
+    })
+}
+
+/// 数据库压缩结果
+#[derive(Debug, Serialize)]
+pub struct CompactResult {
+    pub db_path: String,
+    /// 压缩前的文件大小（字节）
+    pub size_before: u64,
+    /// 压缩后的文件大小（字节）
+    pub size_after: u64,
+    /// 回收的字节数
+    pub bytes_reclaimed: u64,
+}
+
+/// 对 Antigravity 状态数据库执行 VACUUM，回收清除数据后留下的空闲页
+///
+/// VACUUM 期间会独占锁定数据库文件，调用方需确保 Antigravity 未在运行
+pub fn compact_antigravity_db() -> Result<CompactResult, String> {
+    let db_path = platform::get_antigravity_db_path()
+        .filter(|p| p.exists())
+        .ok_or_else(|| "未找到 Antigravity 状态数据库".to_string())?;
+
+    let file_size = |path: &Path| std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    let size_before = file_size(&db_path);
+
+    tracing::info!(target: "cleanup::compact", db = %db_path.display(), size_before, "开始压缩数据库");
+    let conn = crate::antigravity::account::open_db_write(&db_path)?;
+    conn.execute_batch("VACUUM")
+        .map_err(|e| format!("压缩数据库失败: {}", e))?;
+    drop(conn);
+
+    let size_after = file_size(&db_path);
+    let bytes_reclaimed = size_before.saturating_sub(size_after);
+    tracing::info!(target: "cleanup::compact", size_after, bytes_reclaimed, "数据库压缩完成");
+
+    Ok(CompactResult {
+        db_path: db_path.to_string_lossy().to_string(),
+        size_before,
+        size_after,
+        bytes_reclaimed,
+    })
 }
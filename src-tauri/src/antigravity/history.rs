@@ -0,0 +1,63 @@
+//! 账户切换历史模块
+//! 以追加方式把每次成功的切换/登录记录到 switch_history.jsonl，便于回查某个时间使用的账户
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// 单条切换历史
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwitchHistoryEntry {
+    /// 记录时间（RFC 3339）
+    pub timestamp: String,
+    /// 操作类型：switch（切换到已有备份）/ sign_in（登录新账户）
+    pub operation: String,
+    /// 目标账户邮箱；登录新账户时尚未知，为 None
+    pub email: Option<String>,
+    pub success: bool,
+}
+
+fn history_file() -> PathBuf {
+    crate::directories::get_config_directory().join("switch_history.jsonl")
+}
+
+/// 追加一条切换历史；写入失败只记日志，不影响切换本身
+pub fn append_switch_history(operation: &str, email: Option<String>, success: bool) {
+    let entry = SwitchHistoryEntry {
+        timestamp: chrono::Local::now().to_rfc3339(),
+        operation: operation.to_string(),
+        email,
+        success,
+    };
+
+    let result = serde_json::to_string(&entry)
+        .map_err(|e| e.to_string())
+        .and_then(|line| {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(history_file())
+                .map_err(|e| e.to_string())?;
+            writeln!(file, "{}", line).map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        tracing::warn!(target: "account::history", error = %e, "写入切换历史失败");
+    }
+}
+
+/// 读取最近的 `limit` 条切换历史（最新的在前），跳过无法解析的行
+pub fn read_switch_history(limit: usize) -> Result<Vec<SwitchHistoryEntry>, String> {
+    let content = match fs::read_to_string(history_file()) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("读取切换历史失败: {}", e)),
+    };
+
+    Ok(content
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(limit)
+        .collect())
+}
@@ -1,5 +1,11 @@
 pub mod account;
+pub mod avatar;
+pub mod backup_format;
 pub mod cleanup;
+pub mod history;
 pub mod path_config;
+pub mod prune;
 pub mod restore;
+pub mod snapshot;
 pub mod starter;
+pub mod version;
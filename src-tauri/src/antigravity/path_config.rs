@@ -11,6 +11,9 @@ use std::path::PathBuf;
 pub struct AntigravityPathConfig {
     /// 用户自定义的 Antigravity 可执行文件路径
     pub custom_executable_path: Option<String>,
+    /// 额外的可执行文件搜索路径（便携版/自定义安装），优先于内置路径尝试
+    #[serde(default)]
+    pub extra_executable_paths: Vec<String>,
 }
 
 /// 获取配置文件路径
@@ -64,6 +67,93 @@ pub fn get_custom_executable_path() -> Result<Option<String>, String> {
     Ok(config.custom_executable_path)
 }
 
+/// 读取额外的可执行文件搜索路径，配置读取失败时返回空列表
+pub fn get_extra_executable_paths() -> Vec<String> {
+    read_config()
+        .map(|config| config.extra_executable_paths)
+        .unwrap_or_default()
+}
+
+/// 保存额外的可执行文件搜索路径（去除空白项和重复项，保持原有顺序）
+pub fn save_extra_executable_paths(paths: Vec<String>) -> Result<Vec<String>, String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for path in paths {
+        let path = path.trim().to_string();
+        if !path.is_empty() && !cleaned.contains(&path) {
+            cleaned.push(path);
+        }
+    }
+
+    let config_file = get_config_file_path();
+    let mut config = read_config().unwrap_or_default();
+    config.extra_executable_paths = cleaned.clone();
+    write_config(&config_file, &config)?;
+
+    tracing::info!("✅ 已保存 {} 个额外的 Antigravity 搜索路径", cleaned.len());
+    Ok(cleaned)
+}
+
+/// 路径重新检测结果
+#[derive(Debug, Clone, Serialize)]
+pub struct RevalidatedPaths {
+    /// 已失效并被清除的自定义可执行文件路径
+    pub cleared_custom_executable_path: Option<String>,
+    /// 不存在的额外搜索路径（仅提示，不会自动删除）
+    pub missing_extra_executable_paths: Vec<String>,
+    /// 重新检测后实际使用的可执行文件路径
+    pub executable_path: Option<String>,
+    /// 重新检测后的状态数据库
+    pub db_status: crate::platform::AntigravityDbStatus,
+}
+
+/// 检查已保存的路径：清除失效的自定义可执行文件路径，然后重新自动检测
+///
+/// 用于卸载后重新安装到其他位置时，避免一直尝试旧的自定义路径
+pub fn revalidate_paths() -> Result<RevalidatedPaths, String> {
+    let mut config = read_config()?;
+
+    let cleared_custom_executable_path = match config.custom_executable_path.take() {
+        Some(path) if !validate_executable_path(&path) => {
+            tracing::warn!(path = %path, "⚠️ 自定义 Antigravity 可执行文件路径已失效，已清除");
+            write_config(&get_config_file_path(), &config)?;
+            Some(path)
+        }
+        valid => {
+            config.custom_executable_path = valid;
+            None
+        }
+    };
+
+    let missing_extra_executable_paths: Vec<String> = config
+        .extra_executable_paths
+        .iter()
+        .filter(|path| !PathBuf::from(path).exists())
+        .cloned()
+        .collect();
+    if !missing_extra_executable_paths.is_empty() {
+        tracing::warn!(
+            paths = ?missing_extra_executable_paths,
+            "⚠️ 部分额外的 Antigravity 搜索路径不存在"
+        );
+    }
+
+    let executable_path = crate::antigravity::starter::detect_antigravity_executable()
+        .map(|path| path.to_string_lossy().to_string());
+    let db_status = crate::platform::detect_antigravity_db();
+    tracing::info!(
+        executable = ?executable_path,
+        db_status = ?db_status,
+        "🔍 已重新检测 Antigravity 路径"
+    );
+
+    Ok(RevalidatedPaths {
+        cleared_custom_executable_path,
+        missing_extra_executable_paths,
+        executable_path,
+        db_status,
+    })
+}
+
 /// 清除自定义路径配置
 #[allow(dead_code)]
 pub fn clear_custom_path() -> Result<(), String> {
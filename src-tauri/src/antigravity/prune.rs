@@ -0,0 +1,92 @@
+//! 账户备份保留策略模块
+//! 按修改时间保留最新的若干个备份，其余移动到 pruned/ 子目录（不直接删除）
+
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// 清理结果
+#[derive(Debug, Serialize)]
+pub struct PruneResult {
+    /// 保留的备份文件名
+    pub kept: Vec<String>,
+    /// 移动到 pruned/ 的备份文件名
+    pub pruned: Vec<String>,
+    /// 因属于当前登录账户而强制保留的备份
+    pub protected: Option<String>,
+}
+
+/// 保留最新的 `keep_latest` 个账户备份，其余移动到 pruned/ 子目录
+///
+/// 当前登录账户对应的备份永远不会被移动
+pub fn prune_account_backups(keep_latest: usize) -> Result<PruneResult, String> {
+    let accounts_dir = crate::directories::get_accounts_directory();
+    let pruned_dir = accounts_dir.join("pruned");
+
+    let mut backups: Vec<(SystemTime, PathBuf)> = Vec::new();
+    for entry in fs::read_dir(&accounts_dir).map_err(|e| format!("读取备份目录失败: {}", e))?
+    {
+        let entry = entry.map_err(|e| format!("读取目录项失败: {}", e))?;
+        let path = entry.path();
+        if path.is_file() && crate::directories::is_account_backup_file(&path) {
+            let modified = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            backups.push((modified, path));
+        }
+    }
+
+    // 最新的在前
+    backups.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+
+    let current_email = crate::antigravity::account::read_current_account_email();
+
+    let mut result = PruneResult {
+        kept: Vec::new(),
+        pruned: Vec::new(),
+        protected: None,
+    };
+
+    for (index, (_, path)) in backups.into_iter().enumerate() {
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        if index < keep_latest {
+            result.kept.push(file_name);
+            continue;
+        }
+
+        if current_email.is_some()
+            && crate::directories::account_name_from_path(&path) == current_email
+        {
+            tracing::info!(target: "backup::prune", file = %file_name, "跳过当前登录账户的备份");
+            result.protected = Some(file_name.clone());
+            result.kept.push(file_name);
+            continue;
+        }
+
+        fs::create_dir_all(&pruned_dir).map_err(|e| format!("创建 pruned 目录失败: {}", e))?;
+        let target = pruned_dir.join(&file_name);
+        if target.exists() {
+            fs::remove_file(&target)
+                .map_err(|e| format!("移除旧的已清理备份失败 {}: {}", file_name, e))?;
+        }
+        fs::rename(&path, &target).map_err(|e| format!("移动备份失败 {}: {}", file_name, e))?;
+
+        tracing::info!(target: "backup::prune", file = %file_name, "备份已移动到 pruned/");
+        result.pruned.push(file_name);
+    }
+
+    tracing::info!(
+        target: "backup::prune",
+        kept = result.kept.len(),
+        pruned = result.pruned.len(),
+        "备份清理完成"
+    );
+
+    Ok(result)
+}
@@ -1,9 +1,8 @@
 // Antigravity 用户数据恢复模块
 // 负责将备份数据恢复到 Antigravity 应用数据库
 
-use rusqlite::{params, Connection};
-use serde_json::Value;
-use std::fs;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde::Serialize;
 use std::path::PathBuf;
 
 // 导入相关模块
@@ -23,43 +22,55 @@ use crate::platform;
 pub async fn save_antigravity_account_to_file(
     account_file_path: PathBuf,
 ) -> Result<String, String> {
+    // 打开数据库时可能阻塞重试，放到阻塞线程池中执行
+    tokio::task::spawn_blocking(move || restore_account_file_blocking(account_file_path))
+        .await
+        .map_err(|e| format!("恢复任务执行失败: {}", e))?
+}
+
+fn restore_account_file_blocking(account_file_path: PathBuf) -> Result<String, String> {
     println!("📂 账户文件: {}", account_file_path.display());
 
     if !account_file_path.exists() {
         return Err(format!("账户文件不存在: {}", account_file_path.display()));
     }
 
-    let content = fs::read_to_string(&account_file_path).map_err(|e| e.to_string())?;
-    let account_data: Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    let account_data = crate::antigravity::backup_format::read_backup_file(&account_file_path)?;
+    if crate::antigravity::backup_format::needs_relogin(&account_data) {
+        return Err(format!(
+            "备份 {} 不含可用的登录状态，请重新登录该账户后再备份",
+            account_file_path.display()
+        ));
+    }
 
     println!("✅ 账户文件读取成功");
 
-    let app_data = match platform::get_antigravity_db_path() {
-        Some(p) => p,
-        None => {
-            let possible_paths = platform::get_all_antigravity_db_paths();
-            if possible_paths.is_empty() {
-                return Err("未找到 Antigravity 安装位置".to_string());
-            }
-            possible_paths[0].clone()
-        }
-    };
-
-    // 确保数据库目录存在
-    if let Some(parent) = app_data.parent() {
-        fs::create_dir_all(parent).map_err(|e| format!("创建数据库目录失败: {}", e))?;
-    }
+    let app_data = platform::detect_antigravity_db().into_result()?;
 
     let mut msg = String::new();
 
     // 内联恢复逻辑：仅写回 AGENT_STATE 并删除 AUTH_STATUS
-    let restore_db = |db_path: &PathBuf, db_name: &str| -> Result<usize, String> {
+    let restore_db = |conn: Connection, db_name: &str| -> Result<(usize, Vec<String>), String> {
         tracing::info!(target: "restore::database", db_name = %db_name, "开始恢复数据库（仅 jetskiStateSync.agentManagerInitState，移除 antigravityAuthStatus）");
-        let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
 
         let mut restored_count = 0;
+        let logged_out = crate::antigravity::backup_format::is_logged_out_backup(&account_data);
 
-        if let Some(val) = account_data.get(database::AGENT_STATE) {
+        if logged_out {
+            // 登出状态备份：删除登录状态，其他键保持不变
+            match conn.execute(
+                "DELETE FROM ItemTable WHERE key = ?",
+                [database::AGENT_STATE],
+            ) {
+                Ok(rows) => {
+                    tracing::debug!(target: "restore::database", key = %database::AGENT_STATE, "登出状态备份，已删除字段");
+                    restored_count += rows;
+                }
+                Err(e) => {
+                    tracing::error!(target: "restore::database", key = %database::AGENT_STATE, error = %e, "删除字段失败");
+                }
+            }
+        } else if let Some(val) = account_data.get(database::AGENT_STATE) {
             if let Some(val_str) = val.as_str() {
                 match conn.execute(
                     "INSERT OR REPLACE INTO ItemTable (key, value) VALUES (?, ?)",
@@ -89,16 +100,49 @@ pub async fn save_antigravity_account_to_file(
             tracing::debug!(target: "restore::database", "已删除 antigravityAuthStatus");
         }
 
-        Ok(restored_count)
+        // 回读确认写入的值确实落盘
+        let expected: Vec<(&str, &str)> = account_data
+            .get(database::AGENT_STATE)
+            .and_then(|v| v.as_str())
+            .map(|value| (database::AGENT_STATE, value))
+            .into_iter()
+            .collect();
+        let mut issues = verify_restored_keys(&conn, &expected);
+        if logged_out {
+            let still_present = conn
+                .query_row(
+                    "SELECT 1 FROM ItemTable WHERE key = ?",
+                    [database::AGENT_STATE],
+                    |_| Ok(()),
+                )
+                .optional()
+                .map_or(true, |row| row.is_some());
+            if still_present {
+                issues.push(format!("{} 未删除", database::AGENT_STATE));
+            }
+        }
+        for issue in &issues {
+            tracing::warn!(target: "restore::verify", db_name = %db_name, issue = %issue, "恢复后校验未通过");
+        }
+
+        Ok((restored_count, issues))
     };
 
     // 恢复主库
     println!("📊 步骤1: 恢复 state.vscdb 数据库");
-    match restore_db(&app_data, "state.vscdb") {
-        Ok(count) => {
+    let mut warnings = Vec::new();
+    match crate::antigravity::account::open_antigravity_db_write()
+        .and_then(|conn| restore_db(conn, "state.vscdb"))
+    {
+        Ok((count, issues)) => {
             let status = format!("主库恢复 {} 项", count);
             println!("  ✅ {}", status);
             msg.push_str(&status);
+            warnings.extend(
+                issues
+                    .into_iter()
+                    .map(|issue| format!("state.vscdb: {}", issue)),
+            );
         }
         Err(e) => return Err(e),
     }
@@ -107,14 +151,294 @@ pub async fn save_antigravity_account_to_file(
     println!("💾 步骤2: 恢复 state.vscdb.backup");
     let backup_db = app_data.with_extension("vscdb.backup");
     if backup_db.exists() {
-        if let Ok(count) = restore_db(&backup_db, "state.vscdb.backup") {
+        if let Ok((count, issues)) = crate::antigravity::account::open_db_write(&backup_db)
+            .and_then(|conn| restore_db(conn, "state.vscdb.backup"))
+        {
             let status = format!("; 账户库恢复 {} 项", count);
             println!("  ✅ {}", status);
             msg.push_str(&status);
+            warnings.extend(
+                issues
+                    .into_iter()
+                    .map(|issue| format!("state.vscdb.backup: {}", issue)),
+            );
         }
     } else {
         println!("  ℹ️ 账户数据库不存在，跳过");
     }
 
+    if !warnings.is_empty() {
+        return Ok(format!(
+            "⚠️ 恢复完成，但校验发现问题: {}; {}",
+            msg,
+            warnings.join("; ")
+        ));
+    }
+
     Ok(format!("✅ 恢复成功! {}", msg))
 }
+
+/// 回读数据库，确认期望的键已存在且值与备份一致，返回发现的问题
+fn verify_restored_keys(conn: &Connection, expected: &[(&str, &str)]) -> Vec<String> {
+    let mut issues = Vec::new();
+    for (key, expected_value) in expected {
+        let stored: Result<Option<String>, _> = conn
+            .query_row("SELECT value FROM ItemTable WHERE key = ?", [key], |row| {
+                row.get(0)
+            })
+            .optional();
+        match stored {
+            Ok(None) => issues.push(format!("{} 未写入", key)),
+            Ok(Some(value)) if value.len() != expected_value.len() => issues.push(format!(
+                "{} 长度不一致（期望 {}，实际 {}）",
+                key,
+                expected_value.len(),
+                value.len()
+            )),
+            Ok(Some(value)) if value != *expected_value => {
+                issues.push(format!("{} 内容与备份不一致", key))
+            }
+            Ok(Some(_)) => {}
+            Err(e) => issues.push(format!("{} 回读失败: {}", key, e)),
+        }
+    }
+    issues
+}
+
+/// 备份文件中的元数据字段，不属于数据库键
+const BACKUP_METADATA_KEYS: &[&str] = &[
+    crate::antigravity::backup_format::FORMAT_VERSION_KEY,
+    crate::antigravity::backup_format::BACKUP_KIND_KEY,
+    crate::antigravity::backup_format::AGENT_STATE_REF_KEY,
+    crate::antigravity::backup_format::AGENT_META_KEY,
+    crate::antigravity::backup_format::NEEDS_RELOGIN_KEY,
+    crate::antigravity::backup_format::LOGGED_OUT_KEY,
+    crate::antigravity::backup_format::DUPLICATE_OF_KEY,
+    "test",
+];
+
+/// 完整恢复（save_antigravity_account_to_file）会写回数据库的键
+const RESTORED_KEYS: &[&str] = &[database::AGENT_STATE];
+
+/// 列出备份文件中存在、但完整恢复不会写回数据库的键
+///
+/// 直接读取原始 JSON（不经过格式升级，升级会丢弃未知键），
+/// 用于发现备份与恢复之间的字段差异
+pub fn list_unrestored_keys(account_file_path: &std::path::Path) -> Result<Vec<String>, String> {
+    let content = crate::antigravity::backup_format::read_backup_text(account_file_path)?;
+    let backup: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("解析 JSON 失败 {}: {}", account_file_path.display(), e))?;
+    let obj = backup
+        .as_object()
+        .ok_or_else(|| "备份文件格式无效：顶层不是 JSON 对象".to_string())?;
+
+    let mut keys: Vec<String> = obj
+        .keys()
+        .filter(|key| {
+            !BACKUP_METADATA_KEYS.contains(&key.as_str())
+                && !RESTORED_KEYS.contains(&key.as_str())
+                // 旧格式的嵌套写法，升级后按 jetskiStateSync.agentManagerInitState 恢复
+                && key.as_str() != "jetskiStateSync"
+        })
+        .cloned()
+        .collect();
+    keys.sort();
+    Ok(keys)
+}
+
+/// 部分恢复中被跳过的键
+#[derive(Debug, Serialize)]
+pub struct SkippedKey {
+    pub key: String,
+    pub reason: String,
+}
+
+/// 部分恢复结果
+#[derive(Debug, Serialize)]
+pub struct PartialRestoreResult {
+    /// 已写入数据库的键
+    pub applied: Vec<String>,
+    /// 未写入的键及原因
+    pub skipped: Vec<SkippedKey>,
+}
+
+/// 只恢复备份中指定的键（在事务中写入主库和账户库）
+pub fn restore_keys_from_file(
+    account_file_path: PathBuf,
+    keys: &[String],
+) -> Result<PartialRestoreResult, String> {
+    if !account_file_path.exists() {
+        return Err(format!("账户文件不存在: {}", account_file_path.display()));
+    }
+    let account_data = crate::antigravity::backup_format::read_backup_file(&account_file_path)?;
+    // 修复后标记为需要重新登录的备份只剩占位数据，任何键都不应写回数据库
+    if crate::antigravity::backup_format::needs_relogin(&account_data) {
+        return Err(format!(
+            "备份 {} 不含可用的登录状态，请重新登录该账户后再备份",
+            account_file_path.display()
+        ));
+    }
+
+    // 校验每个键都存在于备份中且为字符串值
+    let mut to_apply: Vec<(&str, &str)> = Vec::new();
+    let mut skipped = Vec::new();
+    for key in keys {
+        let reason = if BACKUP_METADATA_KEYS.contains(&key.as_str()) {
+            Some("备份元数据字段，不能写入数据库")
+        } else {
+            match account_data.get(key) {
+                None => Some("备份中不存在该键"),
+                Some(value) => match value.as_str() {
+                    // 修复时写入的空占位值，写入数据库会破坏登录状态
+                    Some("") => Some("备份中的值为空占位（需要重新登录）"),
+                    Some(value) => {
+                        to_apply.push((key.as_str(), value));
+                        None
+                    }
+                    None => Some("备份中的值不是字符串"),
+                },
+            }
+        };
+        if let Some(reason) = reason {
+            skipped.push(SkippedKey {
+                key: key.clone(),
+                reason: reason.to_string(),
+            });
+        }
+    }
+
+    if to_apply.is_empty() {
+        return Ok(PartialRestoreResult {
+            applied: Vec::new(),
+            skipped,
+        });
+    }
+
+    let app_data = platform::detect_antigravity_db().into_result()?;
+
+    let write_keys = |mut conn: Connection| -> Result<(), String> {
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("开启事务失败: {}", e))?;
+        for (key, value) in &to_apply {
+            tx.execute(
+                "INSERT OR REPLACE INTO ItemTable (key, value) VALUES (?, ?)",
+                params![key, value],
+            )
+            .map_err(|e| format!("写入 {} 失败: {}", key, e))?;
+        }
+        tx.commit().map_err(|e| format!("提交事务失败: {}", e))
+    };
+
+    write_keys(crate::antigravity::account::open_antigravity_db_write()?)?;
+    let backup_db = app_data.with_extension("vscdb.backup");
+    if backup_db.exists() {
+        if let Err(e) = crate::antigravity::account::open_db_write(&backup_db).and_then(write_keys)
+        {
+            tracing::warn!(target: "restore::partial", error = %e, "写入 state.vscdb.backup 失败（忽略）");
+        }
+    }
+
+    let applied: Vec<String> = to_apply.iter().map(|(key, _)| key.to_string()).collect();
+    tracing::info!(target: "restore::partial", applied = ?applied, skipped = skipped.len(), "部分恢复完成");
+
+    Ok(PartialRestoreResult { applied, skipped })
+}
+
+/// 恢复预览中单个数据库键的变更情况
+#[derive(Debug, Serialize)]
+pub struct RestorePreviewEntry {
+    /// 目标数据库（state.vscdb / state.vscdb.backup）
+    pub db: String,
+    pub key: String,
+    /// 当前数据库中是否存在该键
+    pub current_present: bool,
+    /// 恢复时会新建该键
+    pub will_create: bool,
+    /// 恢复时会覆盖已有的不同值
+    pub will_overwrite: bool,
+    /// 恢复时会删除该键
+    pub will_delete: bool,
+}
+
+/// 预览恢复操作将修改的数据库键（只读，不写入任何数据）
+pub fn preview_restore_from_file(
+    account_file_path: PathBuf,
+) -> Result<Vec<RestorePreviewEntry>, String> {
+    if !account_file_path.exists() {
+        return Err(format!("账户文件不存在: {}", account_file_path.display()));
+    }
+
+    let account_data = crate::antigravity::backup_format::read_backup_file(&account_file_path)?;
+    // 与恢复一致：不含可用登录状态的备份不会被恢复
+    if crate::antigravity::backup_format::needs_relogin(&account_data) {
+        return Err(format!(
+            "备份 {} 不含可用的登录状态，请重新登录该账户后再备份",
+            account_file_path.display()
+        ));
+    }
+    let logged_out = crate::antigravity::backup_format::is_logged_out_backup(&account_data);
+
+    let app_data = platform::detect_antigravity_db().into_result()?;
+
+    let mut entries = Vec::new();
+    for (db_path, db_name) in [
+        (app_data.clone(), "state.vscdb"),
+        (
+            app_data.with_extension("vscdb.backup"),
+            "state.vscdb.backup",
+        ),
+    ] {
+        // 与恢复一致：主库必须存在（上面已检查），账户库不存在时跳过
+        if !db_path.exists() {
+            continue;
+        }
+
+        let read_value = |key: &str| -> Result<Option<String>, String> {
+            let conn = Connection::open_with_flags(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+                .map_err(|e| format!("连接数据库失败 ({}): {}", db_path.display(), e))?;
+            conn.query_row("SELECT value FROM ItemTable WHERE key = ?", [key], |row| {
+                row.get(0)
+            })
+            .optional()
+            .map_err(|e| format!("查询 {} 失败: {}", key, e))
+        };
+
+        // 恢复写回 RESTORED_KEYS 中的键（登出状态备份则删除这些键），并删除 AUTH_STATUS
+        for key in RESTORED_KEYS {
+            let current = read_value(key)?;
+            let new_value = account_data.get(*key).and_then(|v| v.as_str());
+            if logged_out {
+                entries.push(RestorePreviewEntry {
+                    db: db_name.to_string(),
+                    key: key.to_string(),
+                    current_present: current.is_some(),
+                    will_create: false,
+                    will_overwrite: false,
+                    will_delete: current.is_some(),
+                });
+            } else if let Some(new_value) = new_value {
+                entries.push(RestorePreviewEntry {
+                    db: db_name.to_string(),
+                    key: key.to_string(),
+                    current_present: current.is_some(),
+                    will_create: current.is_none(),
+                    will_overwrite: current.as_deref().is_some_and(|v| v != new_value),
+                    will_delete: false,
+                });
+            }
+        }
+
+        let current_auth = read_value(database::AUTH_STATUS)?;
+        entries.push(RestorePreviewEntry {
+            db: db_name.to_string(),
+            key: database::AUTH_STATUS.to_string(),
+            current_present: current_auth.is_some(),
+            will_create: false,
+            will_overwrite: false,
+            will_delete: current_auth.is_some(),
+        });
+    }
+
+    Ok(entries)
+}
@@ -0,0 +1,120 @@
+//! Antigravity 数据库快照
+//! 在按键备份之外，保存和恢复 state.vscdb 的完整物理副本
+
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+use crate::platform::{self, AntigravityDbStatus};
+
+/// 数据库快照结果
+#[derive(Debug, Serialize)]
+pub struct DbSnapshot {
+    /// 快照文件路径
+    pub path: String,
+    /// 快照文件大小（字节）
+    pub size_bytes: u64,
+}
+
+/// 校验文件是可打开的 SQLite 数据库且包含 ItemTable
+fn ensure_item_table(path: &Path) -> Result<(), String> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("打开数据库 {} 失败: {}", path.display(), e))?;
+    conn.query_row("SELECT count(*) FROM ItemTable", [], |_| Ok(()))
+        .map_err(|e| format!("{} 不是有效的 Antigravity 数据库: {}", path.display(), e))
+}
+
+/// 将当前 Antigravity 数据库完整复制到 `dest_path`
+///
+/// 始终使用 SQLite 在线备份 API：无论 Antigravity 是否运行，都能包含尚未写回主文件的 WAL 内容
+pub fn snapshot_db(dest_path: &Path) -> Result<DbSnapshot, String> {
+    let db_path = platform::detect_antigravity_db().into_result()?;
+
+    if dest_path.exists() {
+        return Err(format!("目标文件已存在: {}", dest_path.display()));
+    }
+    match dest_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        Some(parent) if parent.is_dir() => {}
+        _ => return Err(format!("目标文件的父目录不存在: {}", dest_path.display())),
+    }
+
+    let conn = crate::antigravity::account::open_db_with_retry(&db_path, OpenFlags::default())?;
+    conn.backup(DatabaseName::Main, dest_path, None)
+        .map_err(|e| format!("在线备份数据库失败: {}", e))?;
+
+    let size_bytes = fs::metadata(dest_path).map(|m| m.len()).unwrap_or(0);
+    tracing::info!(
+        source = %db_path.display(),
+        dest = %dest_path.display(),
+        size_bytes = size_bytes,
+        "📸 已保存数据库快照"
+    );
+    Ok(DbSnapshot {
+        path: dest_path.display().to_string(),
+        size_bytes,
+    })
+}
+
+/// 用快照文件替换当前 Antigravity 数据库（仅在 Antigravity 未运行时允许）
+///
+/// 覆盖前先用在线备份 API 把原数据库（含 WAL 中的改动）另存为 `state.vscdb.pre-snapshot`，
+/// 再通过 SQLite 恢复 API 写入快照，由 SQLite 负责处理原有的 -wal / -shm 文件
+pub fn restore_db_snapshot(src_path: &Path) -> Result<String, String> {
+    if platform::is_antigravity_running() {
+        return Err("Antigravity 正在运行，请先关闭后再恢复数据库快照".to_string());
+    }
+    if !src_path.is_file() {
+        return Err(format!("快照文件不存在: {}", src_path.display()));
+    }
+    ensure_item_table(src_path)?;
+
+    let db_path = match platform::detect_antigravity_db() {
+        AntigravityDbStatus::Ready(path) | AntigravityDbStatus::InstalledButNoDb(path) => path,
+        status => status.into_result()?,
+    };
+
+    let mut message = String::new();
+    let mut conn = if db_path.exists() {
+        // 整库替换，原数据库结构可能已损坏或不兼容，因此不经过 open_db_write 的结构检查
+        let conn = crate::antigravity::account::open_db_with_retry_unchecked(
+            &db_path,
+            OpenFlags::default(),
+        )?;
+        let previous = db_path.with_extension("vscdb.pre-snapshot");
+        if previous.exists() {
+            fs::remove_file(&previous)
+                .map_err(|e| format!("删除旧的 {} 失败: {}", previous.display(), e))?;
+        }
+        conn.backup(DatabaseName::Main, &previous, None)
+            .map_err(|e| format!("保存原数据库失败: {}", e))?;
+        message.push_str(&format!("原数据库已另存为 {}；", previous.display()));
+        conn
+    } else {
+        Connection::open(&db_path).map_err(|e| format!("创建数据库文件失败: {}", e))?
+    };
+
+    conn.restore(
+        DatabaseName::Main,
+        src_path,
+        None::<fn(rusqlite::backup::Progress)>,
+    )
+    .map_err(|e| format!("写入数据库失败: {}", e))?;
+    // 合并 WAL 并截断，确保恢复后的内容全部落到主文件
+    if let Err(e) = conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE)") {
+        tracing::warn!(error = %e, "恢复快照后合并 WAL 失败（忽略）");
+    }
+    drop(conn);
+
+    tracing::info!(
+        source = %src_path.display(),
+        target = %db_path.display(),
+        "✅ 已从快照恢复数据库"
+    );
+    message.push_str(&format!(
+        "已从 {} 恢复数据库 {}",
+        src_path.display(),
+        db_path.display()
+    ));
+    Ok(message)
+}
@@ -2,11 +2,79 @@
 ///
 /// 提供跨平台的 Antigravity 应用程序启动功能
 /// 支持 Windows、macOS 和 Linux 系统
-use std::path::PathBuf;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
+/// 启动前检查结果（不实际启动进程）
+#[derive(Debug, Serialize)]
+pub struct LaunchCheck {
+    /// 是否可以启动
+    pub ok: bool,
+    /// 实际会使用的可执行文件路径
+    pub resolved_path: Option<String>,
+    /// 检查结论或失败原因
+    pub reason: String,
+}
+
+/// 启动目标的解析结果，`start_antigravity` 和 `can_start_antigravity` 共用
+#[derive(Debug, Clone, PartialEq)]
+pub enum LaunchTarget {
+    /// 环境变量 `ANTIGRAVITY_AGENT_EXE_PATH` 指定的可执行文件，只尝试这一个
+    Env(PathBuf),
+    /// 用户配置的自定义可执行文件，只尝试这一个
+    Custom(PathBuf),
+    /// 自动检测到的候选，按顺序尝试
+    Detected(Vec<LaunchCandidate>),
+}
+
+/// 自动检测的单个启动候选
+#[derive(Debug, Clone, PartialEq)]
+pub enum LaunchCandidate {
+    /// 直接启动的可执行文件（macOS 上为 .app bundle）
+    Path(PathBuf),
+    /// 通过 `flatpak run` 启动的 Flatpak 应用
+    Flatpak(&'static str),
+}
+
+/// 按优先级解析启动目标：环境变量 > 自定义路径 > 自动检测
+///
+/// 自动检测的候选来自 [`AppPaths::antigravity_executable_paths`]（已包含用户登记的额外搜索路径），
+/// Linux 上再追加已安装的 Flatpak 应用
+///
+/// [`AppPaths::antigravity_executable_paths`]: crate::path_utils::AppPaths::antigravity_executable_paths
+pub fn resolve_launch_target() -> LaunchTarget {
+    if let Some(path) = crate::path_utils::AppPaths::env_executable_path() {
+        return LaunchTarget::Env(path);
+    }
+
+    if let Ok(Some(custom_exec)) = crate::antigravity::path_config::get_custom_executable_path() {
+        let path = PathBuf::from(&custom_exec);
+        if path.is_file() || (cfg!(target_os = "macos") && path.is_dir()) {
+            return LaunchTarget::Custom(path);
+        }
+        tracing::warn!("⚠️ 自定义可执行文件路径无效: {}", custom_exec);
+    }
+
+    let mut candidates: Vec<LaunchCandidate> =
+        crate::path_utils::AppPaths::antigravity_executable_paths()
+            .into_iter()
+            .map(LaunchCandidate::Path)
+            .collect();
+    if std::env::consts::OS == "linux" {
+        if let Some(app_id) = crate::platform::flatpak::installed_flatpak_app_id() {
+            candidates.push(LaunchCandidate::Flatpak(app_id));
+        }
+    }
+    LaunchTarget::Detected(candidates)
+}
+
 /// 启动 Antigravity 应用程序（主入口函数）
 ///
+/// # 参数
+///
+/// * `args` - 追加给 Antigravity 的启动参数（如 `--disable-gpu` 或要打开的目录），可为空
+///
 /// # 返回值
 ///
 /// * `Ok(String)` - 启动成功，返回成功消息
@@ -15,97 +83,56 @@ use std::process::{Command, Stdio};
 /// # 示例
 ///
 /// ```rust
-/// match antigravity_starter::start_antigravity() {
+/// match antigravity_starter::start_antigravity(&[]) {
 ///     Ok(msg) => println!("启动成功: {}", msg),
 ///     Err(e) => println!("启动失败: {}", e),
 /// }
 /// ```
-pub fn start_antigravity() -> Result<String, String> {
-    // 优先使用用户配置的可执行文件路径
-    if let Ok(Some(custom_exec)) = crate::antigravity::path_config::get_custom_executable_path() {
-        let path = PathBuf::from(&custom_exec);
-        if path.exists() && path.is_file() {
-            tracing::info!("📁 使用自定义 Antigravity 可执行文件: {}", custom_exec);
-            return try_start_from_path(&path)
+pub fn start_antigravity(args: &[String]) -> Result<String, String> {
+    let candidates = match resolve_launch_target() {
+        LaunchTarget::Env(path) => {
+            tracing::info!(
+                "📁 使用环境变量指定的 Antigravity 可执行文件: {}",
+                path.display()
+            );
+            return try_start_from_path(&path, args);
+        }
+        LaunchTarget::Custom(path) => {
+            tracing::info!("📁 使用自定义 Antigravity 可执行文件: {}", path.display());
+            return try_start_from_path(&path, args)
                 .map_err(|e| format!("无法启动自定义 Antigravity: {}. 请检查路径是否正确", e));
-        } else {
-            tracing::warn!("⚠️ 自定义可执行文件路径无效: {}", custom_exec);
         }
-    }
-
-    // 回退到自动检测
-    match std::env::consts::OS {
-        "windows" => start_antigravity_windows(),
-        "macos" => start_antigravity_macos(),
-        "linux" => start_antigravity_linux(),
-        _ => Err("不支持的操作系统".to_string()),
-    }
-}
+        LaunchTarget::Detected(candidates) => candidates,
+    };
 
-/// 在 Windows 平台启动 Antigravity
-fn start_antigravity_windows() -> Result<String, String> {
     let mut errors = Vec::new();
-    let antigravity_paths = crate::path_utils::AppPaths::antigravity_executable_paths();
-
-    // 尝试所有推测的路径
-    for path in &antigravity_paths {
-        if path.exists() {
-            match try_start_from_path(path) {
-                Ok(_) => {
-                    return Ok("Antigravity 已启动".to_string());
+    for candidate in &candidates {
+        match candidate {
+            LaunchCandidate::Path(path) => {
+                if !path.exists() {
+                    errors.push(format!("{}: 文件不存在", path.display()));
+                    continue;
                 }
-                Err(e) => {
-                    errors.push(format!("{}: {}", path.display(), e));
+                match try_start_from_path(path, args) {
+                    Ok(result) => return Ok(result),
+                    Err(e) => errors.push(format!("{}: {}", path.display(), e)),
                 }
             }
-        } else {
-            errors.push(format!("{}: 文件不存在", path.display()));
-        }
-    }
-
-    // 尝试从系统 PATH 启动命令
-    let commands = vec!["Antigravity", "antigravity"];
-    match try_start_from_commands(commands) {
-        Ok(msg) => Ok(msg),
-        Err(e) => {
-            errors.push(e);
-            Err(format!(
-                "无法启动Antigravity。请手动启动Antigravity应用。\n尝试的方法：\n{}",
-                errors.join("\n")
-            ))
-        }
-    }
-}
-
-/// 在 macOS 平台启动 Antigravity
-fn start_antigravity_macos() -> Result<String, String> {
-    let mut errors = Vec::new();
-    let antigravity_paths = crate::path_utils::AppPaths::antigravity_executable_paths();
-
-    // 尝试所有推测的路径
-    for path in &antigravity_paths {
-        if path.exists() {
-            match try_start_from_path(path) {
-                Ok(_) => {
-                    return Ok("Antigravity 已启动".to_string());
-                }
-                Err(e) => {
-                    errors.push(format!("{}: {}", path.display(), e));
+            LaunchCandidate::Flatpak(app_id) => {
+                tracing::info!("📦 使用 Flatpak 启动 Antigravity: {}", app_id);
+                match crate::platform::flatpak::start_flatpak(app_id, args) {
+                    Ok(result) => return Ok(result),
+                    Err(e) => errors.push(format!("flatpak run {}: {}", app_id, e)),
                 }
             }
-        } else {
-            errors.push(format!("{}: 文件不存在", path.display()));
         }
     }
 
-    // 尝试系统 PATH 命令
-    let commands = vec!["Antigravity", "antigravity"];
-    match try_start_from_commands(commands) {
-        Ok(msg) => Ok(msg),
-        Err(e) => {
-            errors.push(e);
-            Err(format!(
-                "无法启动Antigravity。\n\n建议解决方案:\n\
+    match std::env::consts::OS {
+        "windows" => start_from_path_commands(args, errors),
+        "macos" => start_from_path_commands(args, errors).map_err(|e| {
+            format!(
+                "{}\n\n建议解决方案:\n\
                 1. 确保已正确安装 Antigravity 应用程序\n\
                 2. 检查应用程序是否位于以下位置之一:\n\
                    - /Applications/Antigravity.app\n\
@@ -113,61 +140,53 @@ fn start_antigravity_macos() -> Result<String, String> {
                    - /Applications/Antigravity-electron.app\n\
                    - ~/Applications/Antigravity-electron.app\n\
                 3. 尝试从 Finder 手动启动 Antigravity\n\
-                4. 检查应用程序权限设置\n\n\
-                尝试的方法:\n{}",
-                errors.join("\n")
-            ))
-        }
+                4. 检查应用程序权限设置",
+                e
+            )
+        }),
+        "linux" => Err(format!(
+            "Antigravity 未安装。请先安装 Antigravity 应用。\n尝试的方法：\n{}",
+            errors.join("\n")
+        )),
+        _ => Err("不支持的操作系统".to_string()),
     }
 }
 
-/// 在 Linux 平台启动 Antigravity
-fn start_antigravity_linux() -> Result<String, String> {
-    let antigravity_path = std::path::PathBuf::from("/usr/share/antigravity/antigravity");
-
-    if !antigravity_path.exists() {
-        return Err("Antigravity 未安装。请先安装 Antigravity 应用。".to_string());
-    }
-
-    let mut cmd = std::process::Command::new(&antigravity_path);
-
-    // 设置桌面环境变量
-    cmd.env("XDG_SESSION_TYPE", "wayland");
-
-    // 如果当前有 DISPLAY，使用它；否则尝试常见值
-    if let Ok(display) = std::env::var("DISPLAY") {
-        cmd.env("DISPLAY", display);
-    } else {
-        cmd.env("DISPLAY", ":0");
-    }
-
-    // 设置其他必要的环境变量
-    if let Ok(xauthority) = std::env::var("XAUTHORITY") {
-        cmd.env("XAUTHORITY", xauthority);
-    }
-
-    match cmd.spawn() {
-        Ok(_) => Ok("Antigravity 已启动".to_string()),
-        Err(e) => Err(format!("启动 Antigravity 失败: {}", e)),
+/// 所有候选路径都失败后，尝试从系统 PATH 启动（Windows / macOS）
+fn start_from_path_commands(args: &[String], mut errors: Vec<String>) -> Result<String, String> {
+    let commands = vec!["Antigravity", "antigravity"];
+    match try_start_from_commands(commands, args) {
+        Ok(msg) => Ok(msg),
+        Err(e) => {
+            errors.push(e);
+            Err(format!(
+                "无法启动Antigravity。请手动启动Antigravity应用。\n尝试的方法：\n{}",
+                errors.join("\n")
+            ))
+        }
     }
 }
 
 /// 尝试从指定路径启动应用程序
-fn try_start_from_path(path: &PathBuf) -> Result<String, String> {
+fn try_start_from_path(path: &PathBuf, args: &[String]) -> Result<String, String> {
     // macOS 需要特殊处理：使用 open 命令启动 .app 应用
     #[cfg(target_os = "macos")]
     {
         // 确保路径是 .app bundle 格式
-        let app_bundle_path = if path.to_str().unwrap_or("").contains(".app") {
+        let app_bundle_path = if is_app_bundle_path(path) {
             path.clone()
         } else {
             return Err(format!("路径不是有效的 .app bundle: {}", path.display()));
         };
 
         // 方法1: 尝试不带 -n 参数的 open 命令（更兼容）
-        match Command::new("open")
-            .arg("-g") // 在后台启动应用
-            .arg(&app_bundle_path)
+        let mut open_cmd = Command::new("open");
+        open_cmd.arg("-g").arg(&app_bundle_path); // 在后台启动应用
+        if !args.is_empty() {
+            // open 需要通过 --args 把参数传给应用
+            open_cmd.arg("--args").args(args);
+        }
+        match open_cmd
             .stdout(std::process::Stdio::null())
             .stderr(std::process::Stdio::null())
             .spawn()
@@ -182,6 +201,7 @@ fn try_start_from_path(path: &PathBuf) -> Result<String, String> {
                     let exec_path = app_bundle_path.join("Contents/MacOS").join(exec_name);
                     if exec_path.exists() {
                         match Command::new(&exec_path)
+                            .args(args)
                             .stdout(std::process::Stdio::null())
                             .stderr(std::process::Stdio::null())
                             .spawn()
@@ -197,8 +217,12 @@ fn try_start_from_path(path: &PathBuf) -> Result<String, String> {
                 }
 
                 // 方法3: 最后尝试不带任何参数的 open 命令
-                match Command::new("open")
-                    .arg(&app_bundle_path)
+                let mut open_cmd = Command::new("open");
+                open_cmd.arg(&app_bundle_path);
+                if !args.is_empty() {
+                    open_cmd.arg("--args").args(args);
+                }
+                match open_cmd
                     .stdout(std::process::Stdio::null())
                     .stderr(std::process::Stdio::null())
                     .spawn()
@@ -221,20 +245,28 @@ fn try_start_from_path(path: &PathBuf) -> Result<String, String> {
         #[cfg(target_os = "windows")]
         {
             Command::new(path)
+                .args(args)
                 .stdout(Stdio::null())
                 .stderr(Stdio::null())
                 .spawn()
                 .map_err(|e| format!("启动失败: {}", e))?;
         }
 
-        // Linux：重定向输出到 null 设备
+        // Linux：重定向输出到 null 设备，并补齐桌面环境变量
         #[cfg(target_os = "linux")]
         {
-            Command::new(path)
-                .stdout(Stdio::null())
-                .stderr(Stdio::null())
-                .spawn()
-                .map_err(|e| format!("启动失败: {}", e))?;
+            let mut cmd = Command::new(path);
+            cmd.args(args).stdout(Stdio::null()).stderr(Stdio::null());
+
+            // 设置桌面环境变量
+            cmd.env("XDG_SESSION_TYPE", "wayland");
+
+            // 如果当前有 DISPLAY，使用它；否则尝试常见值
+            if std::env::var_os("DISPLAY").is_none() {
+                cmd.env("DISPLAY", ":0");
+            }
+
+            cmd.spawn().map_err(|e| format!("启动失败: {}", e))?;
         }
 
         Ok("Antigravity 已启动".to_string())
@@ -242,11 +274,12 @@ fn try_start_from_path(path: &PathBuf) -> Result<String, String> {
 }
 
 /// 尝试从系统命令启动应用程序（静默启动）
-fn try_start_from_commands(commands: Vec<&str>) -> Result<String, String> {
+fn try_start_from_commands(commands: Vec<&str>, args: &[String]) -> Result<String, String> {
     let mut errors = Vec::new();
 
     for cmd in commands {
         match Command::new(cmd)
+            .args(args)
             .stdout(std::process::Stdio::null())
             .stderr(std::process::Stdio::null())
             .spawn()
@@ -263,6 +296,122 @@ fn try_start_from_commands(commands: Vec<&str>) -> Result<String, String> {
     Err(format!("所有命令尝试失败: {}", errors.join(", ")))
 }
 
+/// 判断路径是否为 .app bundle
+#[cfg(target_os = "macos")]
+fn is_app_bundle_path(path: &Path) -> bool {
+    path.to_str().unwrap_or("").contains(".app")
+}
+
+/// 检查指定路径是否可以被启动（不实际启动）
+///
+/// - Unix：文件存在且具有可执行权限
+/// - Windows：文件存在且具有有效的 PE 头（MZ）
+/// - macOS：路径为 .app bundle 且包含 Contents/MacOS 下的可执行文件
+pub fn check_launchable(path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        return Err(format!("文件不存在: {}", path.display()));
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        if !is_app_bundle_path(path) {
+            return Err(format!("路径不是有效的 .app bundle: {}", path.display()));
+        }
+        let exec_names = ["Electron", "Antigravity", "antigravity"];
+        if !exec_names
+            .iter()
+            .any(|name| path.join("Contents/MacOS").join(name).is_file())
+        {
+            return Err(format!(
+                ".app bundle 中未找到可执行文件: {}",
+                path.join("Contents/MacOS").display()
+            ));
+        }
+        Ok(())
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::io::Read;
+
+        let mut header = [0u8; 2];
+        std::fs::File::open(path)
+            .and_then(|mut f| f.read_exact(&mut header))
+            .map_err(|e| format!("读取文件失败: {}", e))?;
+        if &header != b"MZ" {
+            return Err(format!("不是有效的 Windows 可执行文件: {}", path.display()));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let metadata = std::fs::metadata(path).map_err(|e| format!("读取文件信息失败: {}", e))?;
+        if !metadata.is_file() {
+            return Err(format!("不是普通文件: {}", path.display()));
+        }
+        if metadata.permissions().mode() & 0o111 == 0 {
+            return Err(format!("文件没有可执行权限: {}", path.display()));
+        }
+        Ok(())
+    }
+}
+
+/// 按 start_antigravity 的路径解析顺序检查是否可以启动（不实际启动）
+pub fn can_start_antigravity() -> LaunchCheck {
+    let launchable = |path: &Path| -> LaunchCheck {
+        match check_launchable(path) {
+            Ok(()) => LaunchCheck {
+                ok: true,
+                resolved_path: Some(path.to_string_lossy().to_string()),
+                reason: "可以启动".to_string(),
+            },
+            Err(e) => LaunchCheck {
+                ok: false,
+                resolved_path: None,
+                reason: e,
+            },
+        }
+    };
+
+    let candidates = match resolve_launch_target() {
+        LaunchTarget::Env(path) | LaunchTarget::Custom(path) => return launchable(&path),
+        LaunchTarget::Detected(candidates) => candidates,
+    };
+
+    let mut reasons = Vec::new();
+    for candidate in candidates {
+        match candidate {
+            LaunchCandidate::Path(path) => {
+                let check = launchable(&path);
+                if check.ok {
+                    return check;
+                }
+                reasons.push(check.reason);
+            }
+            LaunchCandidate::Flatpak(app_id) => {
+                return LaunchCheck {
+                    ok: true,
+                    resolved_path: Some(format!("flatpak run {}", app_id)),
+                    reason: "可以通过 Flatpak 启动".to_string(),
+                };
+            }
+        }
+    }
+
+    LaunchCheck {
+        ok: false,
+        resolved_path: None,
+        reason: if reasons.is_empty() {
+            "没有可尝试的 Antigravity 可执行文件路径".to_string()
+        } else {
+            reasons.join("\n")
+        },
+    }
+}
+
 /// 检测 Antigravity 可执行文件路径（不启动，只检测）
 pub fn detect_antigravity_executable() -> Option<PathBuf> {
     tracing::info!("🔍 开始自动检测 Antigravity 可执行文件...");
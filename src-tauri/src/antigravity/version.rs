@@ -0,0 +1,108 @@
+//! Antigravity 版本检测模块
+//! 从安装目录读取 Antigravity 的版本号（macOS Info.plist / 安装目录下的 product.json、package.json）
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 版本检测结果
+#[derive(Debug, Clone, Serialize)]
+pub struct AntigravityVersion {
+    /// 检测到的版本号，无法确定时为 None
+    pub version: Option<String>,
+    /// 版本号来源：Info.plist / product.json / package.json / none
+    pub source: String,
+    /// 读取版本号的文件路径
+    pub path: Option<String>,
+}
+
+impl AntigravityVersion {
+    fn not_found() -> Self {
+        Self {
+            version: None,
+            source: "none".to_string(),
+            path: None,
+        }
+    }
+}
+
+/// 检测当前 Antigravity 安装的版本号
+///
+/// 按 start_antigravity 的路径解析顺序定位可执行文件，再从安装目录读取版本信息；
+/// 任何一步失败都返回 `version: None`，不会报错
+pub fn detect_antigravity_version() -> AntigravityVersion {
+    let Some(executable) = crate::antigravity::starter::can_start_antigravity().resolved_path
+    else {
+        tracing::info!("ℹ️ 未找到 Antigravity 可执行文件，无法检测版本");
+        return AntigravityVersion::not_found();
+    };
+
+    // 解析符号链接（如 /usr/bin/antigravity -> /usr/share/antigravity/antigravity）
+    let executable = PathBuf::from(executable);
+    let executable = fs::canonicalize(&executable).unwrap_or(executable);
+
+    if let Some(bundle) = app_bundle_root(&executable) {
+        let plist = bundle.join("Contents").join("Info.plist");
+        if let Some(version) = read_plist_short_version(&plist) {
+            return found(version, "Info.plist", &plist);
+        }
+        // 二进制格式的 plist 无法解析时，退回到 bundle 内的资源文件
+        if let Some(result) = read_resources_version(&bundle.join("Contents").join("Resources")) {
+            return result;
+        }
+    }
+
+    let install_dir = if executable.is_dir() {
+        Some(executable.as_path())
+    } else {
+        executable.parent()
+    };
+    if let Some(result) = install_dir.and_then(|dir| read_resources_version(&dir.join("resources")))
+    {
+        return result;
+    }
+
+    tracing::info!("ℹ️ 未能从 {} 检测到 Antigravity 版本", executable.display());
+    AntigravityVersion::not_found()
+}
+
+fn found(version: String, source: &str, path: &Path) -> AntigravityVersion {
+    tracing::info!("✅ 检测到 Antigravity 版本 {}（来源: {}）", version, source);
+    AntigravityVersion {
+        version: Some(version),
+        source: source.to_string(),
+        path: Some(path.to_string_lossy().to_string()),
+    }
+}
+
+/// 查找路径所属的 .app bundle 根目录（macOS）
+fn app_bundle_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|p| p.extension().is_some_and(|ext| ext == "app"))
+        .map(Path::to_path_buf)
+}
+
+/// 从 XML 格式的 Info.plist 中读取 CFBundleShortVersionString
+fn read_plist_short_version(plist: &Path) -> Option<String> {
+    let content = fs::read_to_string(plist).ok()?;
+    let after_key = content
+        .split("<key>CFBundleShortVersionString</key>")
+        .nth(1)?;
+    let value = after_key.trim_start().strip_prefix("<string>")?;
+    let version = value.split("</string>").next()?.trim();
+    (!version.is_empty()).then(|| version.to_string())
+}
+
+/// 从 Electron 资源目录（resources/app）下的 product.json 或 package.json 读取 version
+fn read_resources_version(resources_dir: &Path) -> Option<AntigravityVersion> {
+    let app_dir = resources_dir.join("app");
+    ["product.json", "package.json"]
+        .into_iter()
+        .find_map(|file| {
+            let path = app_dir.join(file);
+            let content = fs::read_to_string(&path).ok()?;
+            let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+            let version = json.get("version")?.as_str()?.trim();
+            (!version.is_empty()).then(|| found(version.to_string(), file, &path))
+        })
+}
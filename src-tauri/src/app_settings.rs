@@ -2,18 +2,90 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Mutex;
-use tauri::AppHandle;
+use tauri::{AppHandle, Emitter};
 
 /// 应用程序设置
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppSettings {
     /// 是否启用系统托盘
     pub system_tray_enabled: bool,
     /// 是否启用静默启动（启动时最小化到托盘或后台）
     pub silent_start_enabled: bool,
+    /// 是否在检测到登录账户变化时自动备份新账户
+    pub auto_backup_on_change: bool,
+    /// 是否在启动时自动清理旧备份
+    pub auto_prune_enabled: bool,
+    /// 自动清理时保留的最新备份数量
+    pub auto_prune_keep_latest: usize,
+    /// 是否随系统登录自动启动
+    pub autostart_enabled: bool,
+    /// JSON 日志文件的级别过滤指令（重启后生效，可被 ANTIGRAVITY_AGENT_JSON_LOG 覆盖）
+    pub json_log_level: String,
+    /// JSON 日志文件保留的天数
+    pub log_retention_days: usize,
+    /// HTTP 请求超时时间（毫秒），范围见 HTTP_TIMEOUT_RANGE_MS
+    pub http_timeout_ms: u64,
+    /// default 配置档的账户备份目录（为空时使用配置目录下的 antigravity-accounts）
+    pub accounts_directory: Option<String>,
+    /// 是否以 gzip 压缩格式（.json.gz）保存新的账户备份
+    pub compress_backups: bool,
+    /// 是否使用增量备份（状态数据按内容共享存储，备份文件只保存引用）
+    pub delta_backups_enabled: bool,
+    /// 备份超过多少天视为过期（提示重新登录备份），0 表示不提示
+    pub stale_backup_days: u64,
+    /// 是否监控 Antigravity 进程启动/退出并推送事件
+    pub process_watch_enabled: bool,
+    /// 数据库变化事件的防抖间隔（毫秒），范围见 DB_MONITOR_DEBOUNCE_RANGE_MS
+    pub db_monitor_debounce_ms: u64,
+    /// 固定操作的 Antigravity 安装（state.vscdb 路径），为空或文件不存在时自动检测
+    pub active_install_db: Option<String>,
+    /// 是否在启动时重新检测 Antigravity 路径并清除失效的自定义路径
+    pub revalidate_paths_on_startup: bool,
+    /// 只读模式：禁用切换、清除、删除等破坏性操作（只能通过编辑配置文件修改）
+    pub read_only: bool,
+}
+
+/// HTTP 请求超时的允许范围（毫秒）
+pub const HTTP_TIMEOUT_RANGE_MS: std::ops::RangeInclusive<u64> = 500..=60000;
+
+/// 数据库变化事件防抖间隔的允许范围（毫秒）
+pub const DB_MONITOR_DEBOUNCE_RANGE_MS: std::ops::RangeInclusive<u64> = 0..=60000;
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            system_tray_enabled: false,
+            silent_start_enabled: false,
+            auto_backup_on_change: false,
+            auto_prune_enabled: false,
+            auto_prune_keep_latest: 20,
+            autostart_enabled: false,
+            json_log_level: "info".to_string(),
+            log_retention_days: 7,
+            http_timeout_ms: 4000,
+            accounts_directory: None,
+            compress_backups: false,
+            delta_backups_enabled: false,
+            stale_backup_days: 30,
+            process_watch_enabled: false,
+            db_monitor_debounce_ms: 500,
+            active_install_db: None,
+            revalidate_paths_on_startup: false,
+            read_only: false,
+        }
+    }
 }
 
 impl AppSettings {
+    /// 从设置文件读取设置，文件不存在或解析失败时返回默认值
+    pub fn load_from_file(config_path: &std::path::Path) -> Self {
+        fs::read_to_string(config_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
     /// 验证设置的有效性，确保不会出现危险的配置组合
     pub fn validate(&mut self) -> bool {
         let mut changed = false;
@@ -29,31 +101,164 @@ impl AppSettings {
             changed = true;
         }
 
+        // HTTP 超时超出允许范围时（如手动编辑配置文件）修正到边界值
+        let clamped = self
+            .http_timeout_ms
+            .clamp(*HTTP_TIMEOUT_RANGE_MS.start(), *HTTP_TIMEOUT_RANGE_MS.end());
+        if clamped != self.http_timeout_ms {
+            tracing::warn!(
+                target: "app_settings::validate",
+                "HTTP 超时 {}ms 超出允许范围，已修正为 {}ms",
+                self.http_timeout_ms,
+                clamped
+            );
+            self.http_timeout_ms = clamped;
+            changed = true;
+        }
+
+        let clamped = self.db_monitor_debounce_ms.clamp(
+            *DB_MONITOR_DEBOUNCE_RANGE_MS.start(),
+            *DB_MONITOR_DEBOUNCE_RANGE_MS.end(),
+        );
+        if clamped != self.db_monitor_debounce_ms {
+            tracing::warn!(
+                target: "app_settings::validate",
+                "数据库监控防抖间隔 {}ms 超出允许范围，已修正为 {}ms",
+                self.db_monitor_debounce_ms,
+                clamped
+            );
+            self.db_monitor_debounce_ms = clamped;
+            changed = true;
+        }
+
         changed
     }
+
+    /// 按字段名读取单个设置
+    pub fn get_field(&self, key: &str) -> Result<serde_json::Value, String> {
+        let value = serde_json::to_value(self).map_err(|e| format!("序列化设置失败: {}", e))?;
+        value
+            .get(key)
+            .cloned()
+            .ok_or_else(|| format!("未知的设置项: {}", key))
+    }
+
+    /// 按字段名写入单个设置，拒绝未知字段和类型不匹配的值
+    pub fn set_field(&mut self, key: &str, new_value: serde_json::Value) -> Result<(), String> {
+        let mut value =
+            serde_json::to_value(&*self).map_err(|e| format!("序列化设置失败: {}", e))?;
+        let fields = value
+            .as_object_mut()
+            .ok_or_else(|| "设置格式无效".to_string())?;
+        if !fields.contains_key(key) {
+            return Err(format!("未知的设置项: {}", key));
+        }
+        match key {
+            "read_only" => return Err("只读模式只能通过编辑配置文件修改".to_string()),
+            // 这两项修改时需要校验和迁移，只能走专用命令
+            "accounts_directory" => {
+                return Err("账户目录请通过 set_accounts_directory 命令修改".to_string())
+            }
+            "active_install_db" => {
+                return Err("当前使用的安装请通过 set_active_install 命令修改".to_string())
+            }
+            // 这两项需要同步注册系统自启动、启停进程监控，只能走专用命令
+            "autostart_enabled" => {
+                return Err("系统自启动请通过 set_autostart 命令修改".to_string())
+            }
+            "process_watch_enabled" => {
+                return Err("进程监控请通过 save_process_watch_state 命令修改".to_string())
+            }
+            _ => {}
+        }
+        fields.insert(key.to_string(), new_value);
+
+        let updated: AppSettings =
+            serde_json::from_value(value).map_err(|e| format!("设置项 {} 的值无效: {}", key, e))?;
+        updated.check()?;
+        *self = updated;
+        Ok(())
+    }
+
+    /// 检查 `validate` 会修正的问题，有问题时返回错误而不是修正
+    ///
+    /// 用于按字段写入设置，避免无效值被保存并报告成功
+    fn check(&self) -> Result<(), String> {
+        if self.silent_start_enabled && !self.system_tray_enabled {
+            return Err("静默启动需要先启用系统托盘".to_string());
+        }
+        if !HTTP_TIMEOUT_RANGE_MS.contains(&self.http_timeout_ms) {
+            return Err(format!(
+                "HTTP 超时必须在 {}–{} 毫秒之间",
+                HTTP_TIMEOUT_RANGE_MS.start(),
+                HTTP_TIMEOUT_RANGE_MS.end()
+            ));
+        }
+        if !DB_MONITOR_DEBOUNCE_RANGE_MS.contains(&self.db_monitor_debounce_ms) {
+            return Err(format!(
+                "防抖间隔必须在 {}–{} 毫秒之间",
+                DB_MONITOR_DEBOUNCE_RANGE_MS.start(),
+                DB_MONITOR_DEBOUNCE_RANGE_MS.end()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 从配置文件读取 read_only 字段
+///
+/// 文件不存在或未设置该字段时为 `Some(false)`；文件存在但无法读取或解析时为 `None`
+fn read_only_from_file() -> Option<bool> {
+    let config_path = crate::directories::get_app_settings_file();
+    let content = match fs::read_to_string(&config_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Some(false),
+        Err(e) => {
+            tracing::warn!(target: "app_settings::read_only", error = %e, "读取设置文件失败");
+            return None;
+        }
+    };
+    match serde_json::from_str::<serde_json::Value>(&content) {
+        Ok(value) => Some(value.get("read_only").and_then(|v| v.as_bool()) == Some(true)),
+        Err(e) => {
+            tracing::warn!(target: "app_settings::read_only", error = %e, "解析设置文件失败");
+            None
+        }
+    }
+}
+
+/// 是否处于只读模式
+///
+/// 直接读取配置文件，编辑文件后立即生效，且不受运行时设置命令影响；
+/// 配置文件无法读取或解析时按只读处理，避免误执行破坏性操作
+pub fn is_read_only() -> bool {
+    read_only_from_file().unwrap_or(true)
+}
+
+/// 破坏性命令入口检查：只读模式下返回错误
+pub fn ensure_not_read_only() -> Result<(), String> {
+    if is_read_only() {
+        return Err("当前处于只读模式，无法执行此操作".to_string());
+    }
+    Ok(())
 }
 
 /// 应用程序设置管理器
 pub struct AppSettingsManager {
     settings: Mutex<AppSettings>,
     config_path: PathBuf,
+    app_handle: AppHandle,
 }
 
 impl AppSettingsManager {
     /// 创建新的设置管理器
-    pub fn new(_app_handle: &AppHandle) -> Self {
+    pub fn new(app_handle: &AppHandle) -> Self {
         // 使用统一的配置目录
         let config_path = crate::directories::get_app_settings_file();
 
         // 尝试加载现有设置
-        let mut settings = if config_path.exists() {
-            match fs::read_to_string(&config_path) {
-                Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
-                Err(_) => AppSettings::default(),
-            }
-        } else {
-            AppSettings::default()
-        };
+        let mut settings = AppSettings::load_from_file(&config_path);
+        settings.read_only = is_read_only();
 
         // 验证并修正已存在的设置
         if settings.validate() {
@@ -66,6 +271,7 @@ impl AppSettingsManager {
         Self {
             settings: Mutex::new(settings),
             config_path,
+            app_handle: app_handle.clone(),
         }
     }
 
@@ -78,14 +284,37 @@ impl AppSettingsManager {
     pub fn update_settings<F>(&self, update_fn: F) -> Result<(), String>
     where
         F: FnOnce(&mut AppSettings),
+    {
+        self.try_update_settings(|settings| {
+            update_fn(settings);
+            Ok(())
+        })
+    }
+
+    /// 更新设置，`update_fn` 返回错误时不修改也不保存任何设置
+    ///
+    /// 修改在持有锁的期间完成，不会覆盖其他命令同时写入的设置
+    pub fn try_update_settings<F>(&self, update_fn: F) -> Result<(), String>
+    where
+        F: FnOnce(&mut AppSettings) -> Result<(), String>,
     {
         let mut settings = self.settings.lock().unwrap();
 
+        // 只读模式以配置文件为准，避免运行时保存覆盖用户对文件的修改；
+        // 文件无法解析时无法确定该值，拒绝保存，而不是写入一个猜测的值
+        let read_only = read_only_from_file().ok_or_else(|| {
+            "设置文件无法读取或解析，为避免覆盖其中的只读模式设置，已拒绝保存；请先修复或删除该文件"
+                .to_string()
+        })?;
+
         // 记录更新前的状态用于日志
         let old_silent_start = settings.silent_start_enabled;
         let old_system_tray = settings.system_tray_enabled;
 
-        update_fn(&mut settings);
+        let mut updated = settings.clone();
+        update_fn(&mut updated)?;
+        updated.read_only = read_only;
+        *settings = updated;
 
         // 验证设置的有效性，如果返回 true 表示有修改
         if settings.validate() {
@@ -107,8 +336,57 @@ impl AppSettingsManager {
             fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
         }
 
-        fs::write(&self.config_path, json).map_err(|e| format!("写入设置文件失败: {}", e))?;
+        // 先写临时文件再重命名，避免写入中途失败导致设置文件损坏
+        let tmp_path = self.config_path.with_extension("json.tmp");
+        fs::write(&tmp_path, json).map_err(|e| format!("写入设置文件失败: {}", e))?;
+        fs::rename(&tmp_path, &self.config_path).map_err(|e| format!("替换设置文件失败: {}", e))?;
+
+        // 通知所有窗口设置已变化
+        if let Err(e) = self.app_handle.emit("settings-changed", &*settings) {
+            tracing::warn!(target: "app_settings::update", error = %e, "推送设置变化事件失败");
+        }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::AppSettings;
+    use serde_json::json;
+
+    #[test]
+    fn set_field_rejects_invalid_values_without_saving() {
+        let mut settings = AppSettings::default();
+
+        assert!(settings.set_field("http_timeout_ms", json!(10)).is_err());
+        assert!(settings
+            .set_field("db_monitor_debounce_ms", json!(600_000))
+            .is_err());
+        assert!(settings
+            .set_field("silent_start_enabled", json!(true))
+            .is_err());
+        assert_eq!(
+            settings.http_timeout_ms,
+            AppSettings::default().http_timeout_ms
+        );
+        assert!(!settings.silent_start_enabled);
+
+        settings.set_field("http_timeout_ms", json!(1000)).unwrap();
+        assert_eq!(settings.http_timeout_ms, 1000);
+    }
+
+    #[test]
+    fn set_field_refuses_keys_with_dedicated_commands() {
+        let mut settings = AppSettings::default();
+        for key in [
+            "read_only",
+            "accounts_directory",
+            "active_install_db",
+            "autostart_enabled",
+            "process_watch_enabled",
+        ] {
+            assert!(settings.set_field(key, json!(null)).is_err(), "{}", key);
+        }
+    }
+}
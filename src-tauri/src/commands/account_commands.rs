@@ -1,90 +1,224 @@
 //! 账户基础命令：查询、备份、恢复、切换、清理
 
 use crate::antigravity::account::decode_jetski_state_proto;
-use base64::Engine;
-use prost::Message;
-use rusqlite::{Connection, OptionalExtension};
-use serde_json::{from_str, Value};
+use crate::utils::tracing_config::timed_step;
+use rusqlite::OptionalExtension;
+use serde_json::Value;
 use std::fs;
-use tauri::State;
+use tauri::{AppHandle, Manager};
 use tracing::instrument;
 
-/// 获取所有 Antigravity 账户（解码 jetskiStateSync.agentManagerInitState，返回完整 SessionResponse JSON）
-#[tauri::command]
-#[instrument]
-pub async fn get_antigravity_accounts(
-    state: State<'_, crate::AppState>,
-) -> Result<Vec<Value>, String> {
-    tracing::debug!("📋 开始获取所有 Antigravity 账户");
+/// 同时读取解析的最大备份文件数
+const ACCOUNT_LIST_CONCURRENCY: usize = 8;
 
-    let start_time = std::time::Instant::now();
+/// 读取并解码单个备份文件，返回备份修改时间和附带列表字段的账户信息
+fn load_account_entry(
+    path: &std::path::Path,
+    file_name: &str,
+    current_email: Option<&str>,
+    stale_backup_days: u64,
+) -> Result<(std::time::SystemTime, Value), String> {
+    tracing::debug!("📄 正在解析备份文件: {}", file_name);
 
-    let result = async {
-        let mut accounts: Vec<(std::time::SystemTime, Value)> = Vec::new();
+    // 读取并解析 JSON 文件（旧格式自动升级）
+    let backup_data = crate::antigravity::backup_format::read_backup_file(path)?;
 
-        // 获取备份目录路径
-        let antigravity_dir = state.config_dir.join("antigravity-accounts");
+    let mut decoded = if crate::antigravity::backup_format::is_logged_out_backup(&backup_data) {
+        // 登出状态备份：没有登录状态，以备份名显示
+        serde_json::json!({
+            "context": { "email": file_name },
+            "logged_out": true
+        })
+    } else if crate::antigravity::backup_format::needs_relogin(&backup_data) {
+        // 修复后仍缺少登录状态的备份：以备份名作为邮箱显示，提示重新登录
+        serde_json::json!({
+            "context": { "email": file_name },
+            "needs_relogin": true
+        })
+    } else {
+        let jetski_state = backup_data
+            .get("jetskiStateSync.agentManagerInitState")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                format!(
+                    "备份文件 {} 缺少 jetskiStateSync.agentManagerInitState",
+                    file_name
+                )
+            })?;
+        decode_jetski_state_proto(jetski_state)?
+    };
 
-        if !antigravity_dir.exists() {
-            tracing::info!("📂 备份目录不存在，返回空列表");
-            return Ok(Vec::new());
+    let modified_time = fs::metadata(path)
+        .and_then(|m| m.modified())
+        .unwrap_or(std::time::SystemTime::UNIX_EPOCH);
+
+    if let Some(obj) = decoded.as_object_mut() {
+        // 测试账户带上 test 标记，供前端显示徽标
+        if backup_data.get("test").and_then(|v| v.as_bool()) == Some(true) {
+            obj.insert("test".to_string(), Value::Bool(true));
         }
 
-        // 读取目录中的所有 JSON 文件
-        let entries =
-            fs::read_dir(&antigravity_dir).map_err(|e| format!("读取备份目录失败: {}", e))?;
+        let is_current = current_email.is_some()
+            && obj
+                .get("context")
+                .and_then(|c| c.get("email"))
+                .and_then(|e| e.as_str())
+                == current_email;
+        obj.insert("is_current".to_string(), Value::Bool(is_current));
+        // 备份文件名（不含后缀），复制出的备份与原备份邮箱相同，靠它区分
+        obj.insert(
+            "backup_name".to_string(),
+            Value::String(file_name.to_string()),
+        );
 
-        for entry in entries {
-            let entry = entry.map_err(|e| format!("读取目录项失败: {}", e))?;
-            let path = entry.path();
+        let meta = crate::antigravity::backup_format::AccountMeta::from_backup(&backup_data);
+        obj.insert("notes".to_string(), Value::String(meta.notes));
+        obj.insert("tags".to_string(), Value::from(meta.tags));
 
-            // 只处理 JSON 文件
-            if path.extension().is_some_and(|ext| ext == "json") {
-                let file_name = match path.file_stem() {
-                    Some(name) => name.to_string_lossy().to_string(),
-                    None => continue,
-                };
+        // 以备份文件修改时间计算备份年龄，过旧的备份令牌可能已失效
+        let age_days = std::time::SystemTime::now()
+            .duration_since(modified_time)
+            .map(|d| d.as_secs() / 86400)
+            .unwrap_or(0);
+        let stale = stale_backup_days > 0 && age_days >= stale_backup_days;
+        obj.insert("age_days".to_string(), Value::from(age_days));
+        obj.insert("stale".to_string(), Value::Bool(stale));
+        if stale {
+            tracing::info!("⚠️ 账户备份已过期: {}（{} 天）", file_name, age_days);
+        }
+    }
 
-                tracing::debug!("📄 正在解析备份文件: {}", file_name);
+    tracing::info!("✅ 成功解析账户: {}", file_name);
+    Ok((modified_time, decoded))
+}
+
+/// 账户列表及无法解析的备份文件
+#[derive(Debug, serde::Serialize)]
+pub struct AccountListResult {
+    pub accounts: Vec<Value>,
+    /// 每个无法解析的备份文件一条说明：`{备份名}: {错误}`
+    pub warnings: Vec<String>,
+    /// 无法读取当前登录账户时的说明（此时所有账户 is_current 均为 false）
+    pub note: Option<String>,
+}
+
+/// 无法读取当前登录账户时附在账户列表上的说明
+const CURRENT_ACCOUNT_UNKNOWN_NOTE: &str =
+    "无法读取当前登录账户（Antigravity 未登录或数据库不可读），所有账户均未标记为当前账户";
+
+/// 读取并解码账户目录中的所有备份，按备份修改时间排序（最新的在前）
+///
+/// 每个账户附带 is_current、age_days、stale、backup_name、notes、tags 字段；
+/// 备份文件并发读取，无法解析的文件跳过并记入 warnings，不影响其他账户
+async fn load_account_list(stale_backup_days: u64) -> Result<AccountListResult, String> {
+    // 读取一次当前登录账户的邮箱，用于标记 is_current
+    let current_email =
+        tokio::task::spawn_blocking(crate::antigravity::account::read_current_account_email)
+            .await
+            .unwrap_or(None);
+    let note = if current_email.is_none() {
+        tracing::info!("ℹ️ {}", CURRENT_ACCOUNT_UNKNOWN_NOTE);
+        Some(CURRENT_ACCOUNT_UNKNOWN_NOTE.to_string())
+    } else {
+        None
+    };
 
-                // 读取并解析 JSON 文件
-                let content = fs::read_to_string(&path)
-                    .map_err(|e| format!("读取文件失败 {}: {}", file_name, e))?;
+    // 获取备份目录路径
+    let antigravity_dir = crate::directories::get_accounts_directory();
 
-                let backup_data: Value = from_str(&content)
-                    .map_err(|e| format!("解析 JSON 失败 {}: {}", file_name, e))?;
+    if !antigravity_dir.exists() {
+        tracing::info!("📂 备份目录不存在，返回空列表");
+        return Ok(AccountListResult {
+            accounts: Vec::new(),
+            warnings: Vec::new(),
+            note,
+        });
+    }
+
+    // 只处理备份文件（.json / .json.gz）
+    let entries = fs::read_dir(&antigravity_dir).map_err(|e| format!("读取备份目录失败: {}", e))?;
+    let backup_files: Vec<(std::path::PathBuf, String)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter_map(|path| {
+            let name = crate::directories::account_name_from_path(&path)?;
+            Some((path, name))
+        })
+        .collect();
 
-                let jetski_state = backup_data
-                    .get("jetskiStateSync.agentManagerInitState")
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| {
-                        format!(
-                            "备份文件 {} 缺少 jetskiStateSync.agentManagerInitState",
-                            file_name
-                        )
-                    })?;
+    let (mut accounts, warnings) =
+        load_account_entries(backup_files, current_email, stale_backup_days).await;
 
-                let decoded = decode_jetski_state_proto(jetski_state)?;
+    // 按文件修改时间排序（最新的在前），仅返回解码后的对象
+    accounts.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    let decoded_only: Vec<Value> = accounts.into_iter().map(|(_, decoded)| decoded).collect();
 
-                let modified_time = fs::metadata(&path)
-                    .and_then(|m| m.modified())
-                    .unwrap_or(std::time::SystemTime::UNIX_EPOCH);
+    tracing::debug!(
+        "🎉 成功加载 {} 个账户，跳过 {} 个无法解析的备份",
+        decoded_only.len(),
+        warnings.len()
+    );
+
+    Ok(AccountListResult {
+        accounts: decoded_only,
+        warnings,
+        note,
+    })
+}
 
-                accounts.push((modified_time, decoded));
+/// 并发读取一组备份文件，返回成功解析的账户和按文件名排序的 warnings（`{备份名}: {错误}`）
+async fn load_account_entries(
+    backup_files: Vec<(std::path::PathBuf, String)>,
+    current_email: Option<String>,
+    stale_backup_days: u64,
+) -> (Vec<(std::time::SystemTime, Value)>, Vec<String>) {
+    let file_names: Vec<String> = backup_files.iter().map(|(_, name)| name.clone()).collect();
+    let results = crate::utils::bounded_parallel::map_blocking(
+        backup_files,
+        ACCOUNT_LIST_CONCURRENCY,
+        move |(path, file_name)| {
+            load_account_entry(
+                &path,
+                &file_name,
+                current_email.as_deref(),
+                stale_backup_days,
+            )
+        },
+    )
+    .await;
 
-                tracing::info!("✅ 成功解析账户: {}", file_name);
+    let mut accounts = Vec::new();
+    let mut warnings = Vec::new();
+    for (file_name, result) in file_names.into_iter().zip(results) {
+        match result.and_then(|loaded| loaded) {
+            Ok(account) => accounts.push(account),
+            Err(e) => {
+                tracing::warn!(file = %file_name, error = %e, "⚠️ 跳过无法解析的备份文件");
+                warnings.push(format!("{}: {}", file_name, e));
             }
         }
+    }
+    warnings.sort();
+    (accounts, warnings)
+}
 
-        // 按文件修改时间排序（最新的在前），仅返回解码后的对象
-        accounts.sort_by(|a, b| b.0.cmp(&a.0));
-        let decoded_only: Vec<Value> = accounts.into_iter().map(|(_, decoded)| decoded).collect();
+/// 获取所有 Antigravity 账户（解码 jetskiStateSync.agentManagerInitState，返回完整 SessionResponse JSON）
+#[tauri::command]
+#[instrument(skip(app))]
+pub async fn get_antigravity_accounts(app: AppHandle) -> Result<Vec<Value>, String> {
+    tracing::debug!("📋 开始获取所有 Antigravity 账户");
 
-        tracing::debug!("🎉 成功加载 {} 个账户", decoded_only.len());
+    let start_time = std::time::Instant::now();
 
-        Ok(decoded_only)
-    }
-    .await;
+    // 备份过期阈值（天），0 表示不标记过期
+    let stale_backup_days = app
+        .state::<crate::app_settings::AppSettingsManager>()
+        .get_settings()
+        .stale_backup_days;
+    let result = load_account_list(stale_backup_days)
+        .await
+        .map(|list| list.accounts);
 
     let duration = start_time.elapsed();
 
@@ -108,6 +242,22 @@ pub async fn get_antigravity_accounts(
     }
 }
 
+/// 获取所有 Antigravity 账户，并返回无法解析的备份文件列表
+///
+/// 与 `get_antigravity_accounts` 相同，但 UI 可以据此提示哪些备份已损坏
+#[tauri::command]
+pub async fn get_antigravity_accounts_with_warnings(
+    app: AppHandle,
+) -> Result<AccountListResult, String> {
+    crate::log_async_command!("get_antigravity_accounts_with_warnings", async {
+        let stale_backup_days = app
+            .state::<crate::app_settings::AppSettingsManager>()
+            .get_settings()
+            .stale_backup_days;
+        load_account_list(stale_backup_days).await
+    })
+}
+
 /// 获取当前 Antigravity 账户信息
 #[tauri::command]
 #[instrument]
@@ -117,32 +267,12 @@ pub async fn get_current_antigravity_account_info() -> Result<Value, String> {
     let start_time = std::time::Instant::now();
 
     let result = async {
-        // 尝试获取 Antigravity 状态数据库路径
-        let app_data = match crate::platform::get_antigravity_db_path() {
-            Some(path) => path,
-            None => {
-                // 如果主路径不存在，尝试其他可能的位置
-                let possible_paths = crate::platform::get_all_antigravity_db_paths();
-                if possible_paths.is_empty() {
-                    return Err("未找到Antigravity安装位置".to_string());
-                }
-                possible_paths[0].clone()
-            }
-        };
-
-        if !app_data.exists() {
-            return Err(format!(
-                "Antigravity 状态数据库文件不存在: {}",
-                app_data.display()
-            ));
-        }
+        tokio::task::spawn_blocking(move || -> Result<Value, String> {
+            // 连接到 SQLite 数据库并获取认证信息（数据库被锁时自动重试）
+            let conn = crate::antigravity::account::open_antigravity_db_read()?;
 
-        // 连接到 SQLite 数据库并获取认证信息
-        let conn = Connection::open(&app_data)
-            .map_err(|e| format!("连接数据库失败 ({}): {}", app_data.display(), e))?;
-
-        // jetski 状态（可选）
-        let jetski_state: Option<String> = conn
+            // jetski 状态（可选）
+            let jetski_state: Option<String> = conn
             .query_row(
                 "SELECT value FROM ItemTable WHERE key = 'jetskiStateSync.agentManagerInitState'",
                 [],
@@ -151,13 +281,16 @@ pub async fn get_current_antigravity_account_info() -> Result<Value, String> {
             .optional()
             .map_err(|e| format!("查询 jetskiStateSync.agentManagerInitState 失败: {}", e))?;
 
-        let state_str = jetski_state
-            .ok_or_else(|| "未找到 jetskiStateSync.agentManagerInitState".to_string())?;
+            let state_str = jetski_state
+                .ok_or_else(|| "未找到 jetskiStateSync.agentManagerInitState".to_string())?;
 
-        // 解码 jetski 状态（base64 + proto）；失败直接报错
-        let decoded = decode_jetski_state_proto(&state_str)?;
+            // 解码 jetski 状态（base64 + proto）；失败直接报错
+            let decoded = decode_jetski_state_proto(&state_str)?;
 
-        Ok(serde_json::json!(decoded))
+            Ok(serde_json::json!(decoded))
+        })
+        .await
+        .map_err(|e| format!("数据库任务执行失败: {}", e))?
     }
     .await;
 
@@ -182,91 +315,312 @@ pub async fn get_current_antigravity_account_info() -> Result<Value, String> {
     }
 }
 
-/// 备份当前 Antigravity 账户
-#[tauri::command]
-#[instrument]
-pub async fn save_antigravity_current_account() -> Result<String, String> {
-    tracing::info!("📥 开始保存 jetskiStateSync.agentManagerInitState");
+/// 账户是否匹配搜索词（query 已转为小写）：邮箱、显示名称、备份名称或任一标签包含搜索词
+fn account_matches_query(account: &Value, query: &str) -> bool {
+    let context = account.get("context");
+    let email = context
+        .and_then(|c| c.get("email"))
+        .and_then(|e| e.as_str())
+        .unwrap_or_default();
+    // 前端以 plan_name 作为显示名称
+    let display_name = context
+        .and_then(|c| c.get("plan_name"))
+        .and_then(|n| n.as_str())
+        .unwrap_or_default();
+    let backup_name = account
+        .get("backup_name")
+        .and_then(|n| n.as_str())
+        .unwrap_or_default();
+    let tags = account
+        .get("tags")
+        .and_then(|t| t.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|tag| tag.as_str());
 
-    let start_time = std::time::Instant::now();
+    [email, display_name, backup_name]
+        .into_iter()
+        .chain(tags)
+        .any(|field| field.to_lowercase().contains(query))
+}
 
-    let result = async {
-        // 尝试获取 Antigravity 状态数据库路径
-        let app_data = match crate::platform::get_antigravity_db_path() {
-            Some(path) => path,
-            None => {
-                // 如果主路径不存在，尝试其他可能的位置
-                let possible_paths = crate::platform::get_all_antigravity_db_paths();
-                if possible_paths.is_empty() {
-                    return Err("未找到Antigravity安装位置".to_string());
-                }
-                possible_paths[0].clone()
-            }
-        };
+/// 按邮箱、名称或标签搜索账户（不区分大小写），空搜索词返回全部账户
+#[tauri::command]
+pub async fn search_accounts(app: AppHandle, query: String) -> Result<Vec<Value>, String> {
+    crate::log_async_command!("search_accounts", async {
+        let stale_backup_days = app
+            .state::<crate::app_settings::AppSettingsManager>()
+            .get_settings()
+            .stale_backup_days;
+        let accounts = load_account_list(stale_backup_days).await?.accounts;
 
-        if !app_data.exists() {
-            return Err(format!(
-                "Antigravity 状态数据库文件不存在: {}",
-                app_data.display()
-            ));
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Ok(accounts);
         }
+        Ok(accounts
+            .into_iter()
+            .filter(|account| account_matches_query(account, &query))
+            .collect())
+    })
+}
 
-        // 连接到 SQLite 数据库并获取认证信息
-        let conn = Connection::open(&app_data)
-            .map_err(|e| format!("连接数据库失败 ({}): {}", app_data.display(), e))?;
+/// 获取当前登录账户的邮箱（轻量版本，未登录或数据库不可用时返回 None，不报错）
+#[tauri::command]
+pub async fn get_current_account_email() -> Result<Option<String>, String> {
+    // 即使只尝试一次，打开 SQLite 仍可能阻塞，放到阻塞线程中执行
+    Ok(
+        tokio::task::spawn_blocking(crate::antigravity::account::read_current_account_email_quick)
+            .await
+            .unwrap_or(None),
+    )
+}
 
-        // jetski 状态（必需）
-        let jetski_state: String = conn
-            .query_row(
-                "SELECT value FROM ItemTable WHERE key = 'jetskiStateSync.agentManagerInitState'",
-                [],
-                |row| row.get(0),
-            )
-            .optional()
-            .map_err(|e| format!("查询 jetskiStateSync.agentManagerInitState 失败: {}", e))?
-            .ok_or_else(|| "未找到 jetskiStateSync.agentManagerInitState".to_string())?;
-
-        // 从 jetski proto 解码邮箱（仅用于文件名）
-        let bytes = base64::engine::general_purpose::STANDARD
-            .decode(jetski_state.trim())
-            .map_err(|e| format!("jetskiStateSync Base64 解码失败: {}", e))?;
-        let msg = crate::proto::SessionResponse::decode(bytes.as_slice())
-            .map_err(|e| format!("jetskiStateSync Protobuf 解码失败: {}", e))?;
-
-        let email = msg
-            .context
-            .as_ref()
-            .and_then(|c| {
-                if c.email.is_empty() {
-                    None
-                } else {
-                    Some(c.email.as_str())
-                }
-            })
-            .ok_or_else(|| "jetskiStateSync 中未找到邮箱字段，无法确定备份文件名".to_string())?;
+/// 获取当前 Antigravity 账户信息（敏感字段已脱敏，可直接附在问题反馈中）
+#[tauri::command]
+pub async fn get_current_antigravity_account_info_redacted() -> Result<Value, String> {
+    crate::log_async_command!("get_current_antigravity_account_info_redacted", async {
+        let mut info = get_current_antigravity_account_info().await?;
+        crate::utils::log_sanitizer::redact_json_secrets(&mut info);
 
-        // 直接保存原始字符串，不解码，文件名与原逻辑保持：{email}.json
-        let accounts_dir = crate::directories::get_accounts_directory();
-        if let Err(e) = std::fs::create_dir_all(&accounts_dir) {
-            return Err(format!("创建账户目录失败: {}", e));
+        let db_path = crate::platform::get_antigravity_db_path().or_else(|| {
+            crate::platform::get_all_antigravity_db_paths()
+                .into_iter()
+                .next()
+        });
+        if let (Some(obj), Some(db_path)) = (info.as_object_mut(), db_path) {
+            obj.insert(
+                "db_path".to_string(),
+                Value::String(db_path.display().to_string()),
+            );
         }
 
-        let account_file = accounts_dir.join(format!("{email}.json"));
-        let content = serde_json::json!({
-            "jetskiStateSync.agentManagerInitState": jetski_state
+        Ok(info)
+    })
+}
+
+/// 列出 Antigravity（或 `target_app` 指定的应用）数据库 ItemTable 中的所有键（不读取值）
+#[tauri::command]
+pub async fn list_antigravity_db_keys(
+    target_app: Option<crate::platform::TargetApp>,
+) -> Result<Vec<String>, String> {
+    crate::log_async_command!("list_antigravity_db_keys", async {
+        tokio::task::spawn_blocking(move || -> Result<Vec<String>, String> {
+            let conn =
+                crate::antigravity::account::open_target_db_read(target_app.unwrap_or_default())?;
+            let mut stmt = conn
+                .prepare("SELECT key FROM ItemTable ORDER BY key")
+                .map_err(|e| format!("查询数据库键失败: {}", e))?;
+            let keys = stmt
+                .query_map([], |row| row.get::<_, String>(0))
+                .map_err(|e| format!("查询数据库键失败: {}", e))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("读取数据库键失败: {}", e))?;
+            Ok(keys)
+        })
+        .await
+        .map_err(|e| format!("数据库任务执行失败: {}", e))?
+    })
+}
+
+/// 数据库校验结果
+#[derive(Debug, serde::Serialize)]
+pub struct DbValidation {
+    /// 是否为可用的 Antigravity 状态数据库
+    pub valid: bool,
+    /// 是否包含 antigravityAuthStatus（已登录）
+    pub has_auth_status: bool,
+    /// ItemTable 中的键数量
+    pub key_count: usize,
+    /// 不可用时的原因
+    pub reason: Option<String>,
+}
+
+/// Antigravity 特有的键前缀，用于区分其他 VS Code 系编辑器的数据库
+const ANTIGRAVITY_KEY_PREFIXES: &[&str] = &["jetskiStateSync.", "antigravity"];
+
+/// 检查指定的 state.vscdb 是否为有效的 Antigravity 数据库（只读打开，不做修改）
+fn validate_db_file(db_file: &std::path::Path) -> Result<DbValidation, String> {
+    let conn = crate::antigravity::account::open_db_with_retry_unchecked(
+        db_file,
+        rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
+    )?;
+
+    let schema = crate::antigravity::account::check_db_schema(&conn)?;
+    if !schema.compatible {
+        return Ok(DbValidation {
+            valid: false,
+            has_auth_status: false,
+            key_count: 0,
+            reason: Some(schema.message),
         });
-        std::fs::write(
-            &account_file,
-            serde_json::to_string_pretty(&content).unwrap(),
+    }
+
+    let keys: Vec<String> = conn
+        .prepare("SELECT key FROM ItemTable")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| format!("读取键列表失败: {}", e))?;
+
+    let has_auth_status = keys
+        .iter()
+        .any(|k| k == crate::constants::database::AUTH_STATUS);
+    let has_antigravity_key = keys.iter().any(|k| {
+        ANTIGRAVITY_KEY_PREFIXES
+            .iter()
+            .any(|prefix| k.starts_with(prefix))
+    });
+
+    Ok(DbValidation {
+        valid: has_antigravity_key,
+        has_auth_status,
+        key_count: keys.len(),
+        reason: (!has_antigravity_key)
+            .then(|| "未找到 Antigravity 特有的键，可能不是 Antigravity 的数据库".to_string()),
+    })
+}
+
+/// 校验指定路径的 state.vscdb 是否为有效的 Antigravity 数据库
+///
+/// 只读打开，确认存在带 key/value 列的 ItemTable 且包含 Antigravity 特有的键；
+/// 文件无法打开时也返回 `valid: false` 而不是报错
+#[tauri::command]
+pub async fn validate_antigravity_db(path: String) -> Result<DbValidation, String> {
+    crate::log_async_command!("validate_antigravity_db", async {
+        let db_file = std::path::PathBuf::from(&path);
+        if !db_file.is_file() {
+            return Ok(DbValidation {
+                valid: false,
+                has_auth_status: false,
+                key_count: 0,
+                reason: Some(format!("文件不存在: {}", path)),
+            });
+        }
+
+        Ok(validate_db_file(&db_file).unwrap_or_else(|e| DbValidation {
+            valid: false,
+            has_auth_status: false,
+            key_count: 0,
+            reason: Some(e),
+        }))
+    })
+}
+
+/// 检查当前 Antigravity 状态数据库结构是否为预期的 ItemTable(key, value)
+///
+/// Antigravity 更新后存储格式变化时，切换/备份/恢复会返回“不支持的数据库结构”错误，
+/// 可用此命令查看数据库中实际的表
+#[tauri::command]
+pub async fn check_db_compatibility() -> Result<crate::antigravity::account::DbCompatibility, String>
+{
+    crate::log_async_command!("check_db_compatibility", async {
+        tokio::task::spawn_blocking(
+            move || -> Result<crate::antigravity::account::DbCompatibility, String> {
+                crate::antigravity::account::check_antigravity_db_compatibility()
+            },
         )
-        .map_err(|e| format!("写入 jetski 状态失败: {}", e))?;
+        .await
+        .map_err(|e| format!("数据库任务执行失败: {}", e))?
+    })
+}
 
-        let message = format!(
-            "已保存 jetskiStateSync.agentManagerInitState 到 {}",
-            account_file.display()
-        );
-        tracing::info!(file = %account_file.display(), "✅ 保存 jetski 状态完成");
-        Ok(message)
+/// 数据库单个键的值
+#[derive(Debug, serde::Serialize)]
+pub struct DbValue {
+    pub key: String,
+    /// 文本值；二进制值以 Base64 表示；键不存在时为 None
+    pub value: Option<String>,
+    /// 原始值的字节数
+    pub size: usize,
+    /// 值是否为二进制（已 Base64 编码）
+    pub is_binary: bool,
+    /// 值是否因超过大小上限而被截断
+    pub truncated: bool,
+}
+
+/// 默认返回的最大字节数
+const DEFAULT_DB_VALUE_MAX_BYTES: usize = 64 * 1024;
+
+/// 读取 Antigravity（或 `target_app` 指定的应用）数据库中单个键的值，超过 `max_bytes`（默认 64KB）时截断
+#[tauri::command]
+pub async fn get_antigravity_db_value(
+    key: String,
+    max_bytes: Option<usize>,
+    target_app: Option<crate::platform::TargetApp>,
+) -> Result<DbValue, String> {
+    use base64::Engine;
+    use rusqlite::types::ValueRef;
+
+    crate::log_async_command!("get_antigravity_db_value", async {
+        tokio::task::spawn_blocking(move || -> Result<DbValue, String> {
+            let max_bytes = max_bytes.unwrap_or(DEFAULT_DB_VALUE_MAX_BYTES);
+            let conn =
+                crate::antigravity::account::open_target_db_read(target_app.unwrap_or_default())?;
+
+            let raw: Option<(Vec<u8>, bool)> = conn
+                .query_row("SELECT value FROM ItemTable WHERE key = ?", [&key], |row| {
+                    Ok(match row.get_ref(0)? {
+                        ValueRef::Blob(bytes) => (bytes.to_vec(), true),
+                        ValueRef::Text(bytes) => (bytes.to_vec(), false),
+                        ValueRef::Null => (Vec::new(), false),
+                        ValueRef::Integer(i) => (i.to_string().into_bytes(), false),
+                        ValueRef::Real(f) => (f.to_string().into_bytes(), false),
+                    })
+                })
+                .optional()
+                .map_err(|e| format!("查询 {} 失败: {}", key, e))?;
+
+            let Some((bytes, is_binary)) = raw else {
+                return Ok(DbValue {
+                    key,
+                    value: None,
+                    size: 0,
+                    is_binary: false,
+                    truncated: false,
+                });
+            };
+
+            let size = bytes.len();
+            let truncated = size > max_bytes;
+            let shown = &bytes[..size.min(max_bytes)];
+            let value = if is_binary {
+                base64::engine::general_purpose::STANDARD.encode(shown)
+            } else {
+                // 截断位置可能落在多字节字符中间，按有损方式解码
+                String::from_utf8_lossy(shown).to_string()
+            };
+
+            Ok(DbValue {
+                key,
+                value: Some(value),
+                size,
+                is_binary,
+                truncated,
+            })
+        })
+        .await
+        .map_err(|e| format!("数据库任务执行失败: {}", e))?
+    })
+}
+
+/// 备份当前 Antigravity 账户
+#[tauri::command]
+#[instrument]
+pub async fn save_antigravity_current_account() -> Result<String, String> {
+    tracing::info!("📥 开始保存 jetskiStateSync.agentManagerInitState");
+
+    let start_time = std::time::Instant::now();
+
+    let result = async {
+        tokio::task::spawn_blocking(move || -> Result<String, String> {
+            let (jetski_state, email) = crate::antigravity::account::read_current_jetski_state()?;
+
+            write_account_backup(&jetski_state, &email)
+        })
+        .await
+        .map_err(|e| format!("备份任务执行失败: {}", e))?
     }
     .await;
 
@@ -292,63 +646,733 @@ pub async fn save_antigravity_current_account() -> Result<String, String> {
     }
 }
 
+/// 将 jetskiStateSync 原始值写入 `{email}.json`（压缩时为 `{email}.json.gz`），返回结果描述
+fn write_account_backup(jetski_state: &str, email: &str) -> Result<String, String> {
+    // 直接保存原始字符串，不解码，文件名与原逻辑保持：{email}.json（压缩时为 {email}.json.gz）
+    let accounts_dir = crate::directories::get_accounts_directory();
+    if let Err(e) = std::fs::create_dir_all(&accounts_dir) {
+        return Err(format!("创建账户目录失败: {}", e));
+    }
+
+    // 写入前检查磁盘空间，预估大小为状态数据长度
+    crate::antigravity::backup_format::ensure_backup_space(jetski_state.len() as u64)?;
+
+    let email = crate::directories::sanitize_account_name(email)?;
+    let mut content = serde_json::json!({
+        "jetskiStateSync.agentManagerInitState": jetski_state
+    });
+    // 覆盖备份时保留用户添加的备注与标签
+    let existing_meta = crate::directories::get_account_file(&email)
+        .ok()
+        .filter(|path| path.exists())
+        .and_then(|path| crate::antigravity::backup_format::read_backup_text(&path).ok())
+        .and_then(|text| serde_json::from_str::<Value>(&text).ok())
+        .and_then(|backup| {
+            backup
+                .get(crate::antigravity::backup_format::AGENT_META_KEY)
+                .cloned()
+        });
+    if let (Some(meta), Some(obj)) = (existing_meta, content.as_object_mut()) {
+        obj.insert(
+            crate::antigravity::backup_format::AGENT_META_KEY.to_string(),
+            meta,
+        );
+    }
+    // 增量/压缩备份均为可选项，设置文件读取失败时按完整、未压缩备份处理
+    let settings = crate::app_settings::AppSettings::load_from_file(
+        &crate::directories::get_app_settings_file(),
+    );
+
+    let (plain_file, compressed_file) = crate::directories::get_account_file_variants(&email)?;
+    let (account_file, stale_file) = if settings.compress_backups {
+        (compressed_file, plain_file)
+    } else {
+        (plain_file, compressed_file)
+    };
+
+    let written = crate::antigravity::backup_format::write_backup_file(
+        &account_file,
+        content,
+        settings.delta_backups_enabled,
+    )
+    .map_err(|e| format!("写入 jetski 状态失败: {}", e))?;
+    // 切换压缩设置后，删除同一账户另一种格式的旧备份，避免重复
+    if stale_file.exists() {
+        if let Err(e) = std::fs::remove_file(&stale_file) {
+            tracing::warn!(file = %stale_file.display(), error = %e, "删除旧格式备份失败");
+        }
+    }
+    // 覆盖旧备份后，之前引用的增量数据可能已不再使用
+    crate::antigravity::backup_format::remove_unreferenced_blobs();
+
+    let mut message = format!(
+        "已保存 jetskiStateSync.agentManagerInitState 到 {}",
+        account_file.display()
+    );
+    if settings.compress_backups {
+        message.push_str(&format!(
+            "（压缩后 {} 字节，为原大小的 {:.1}%）",
+            written.written_bytes,
+            written.compression_ratio() * 100.0
+        ));
+    }
+    tracing::info!(file = %account_file.display(), "✅ 保存 jetski 状态完成");
+    Ok(message)
+}
+
+/// 单个安装的备份结果
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct InstallBackupResult {
+    pub db_path: String,
+    pub email: Option<String>,
+    /// "backed_up" / "skipped" / "failed"
+    pub status: String,
+    pub message: String,
+}
+
+/// 一次性备份所有检测到的 Antigravity 安装中已登录的账户
+///
+/// 未登录的安装会被跳过，单个安装失败不影响其他安装
+#[tauri::command]
+pub async fn backup_all_installs() -> Result<Vec<InstallBackupResult>, String> {
+    crate::log_async_command!("backup_all_installs", async {
+        tokio::task::spawn_blocking(move || -> Result<Vec<InstallBackupResult>, String> {
+            let mut db_paths: Vec<std::path::PathBuf> = Vec::new();
+            for path in crate::platform::get_all_antigravity_db_paths() {
+                if path.is_file() && !db_paths.contains(&path) {
+                    db_paths.push(path);
+                }
+            }
+            if db_paths.is_empty() {
+                return Err("未检测到任何 Antigravity 安装".to_string());
+            }
+
+            let mut results = Vec::with_capacity(db_paths.len());
+            for db_path in db_paths {
+                let db_display = db_path.display().to_string();
+                let result = match crate::antigravity::account::read_jetski_state_at(&db_path) {
+                    Ok(Some((jetski_state, email))) => {
+                        match write_account_backup(&jetski_state, &email) {
+                            Ok(message) => InstallBackupResult {
+                                db_path: db_display,
+                                email: Some(email),
+                                status: "backed_up".to_string(),
+                                message,
+                            },
+                            Err(e) => InstallBackupResult {
+                                db_path: db_display,
+                                email: Some(email),
+                                status: "failed".to_string(),
+                                message: e,
+                            },
+                        }
+                    }
+                    Ok(None) => InstallBackupResult {
+                        db_path: db_display,
+                        email: None,
+                        status: "skipped".to_string(),
+                        message: "该安装未登录账户".to_string(),
+                    },
+                    Err(e) => InstallBackupResult {
+                        db_path: db_display,
+                        email: None,
+                        status: "failed".to_string(),
+                        message: e,
+                    },
+                };
+                tracing::info!(
+                    db_path = %result.db_path,
+                    status = %result.status,
+                    "安装备份结果: {}",
+                    result.message
+                );
+                results.push(result);
+            }
+            Ok(results)
+        })
+        .await
+        .map_err(|e| format!("备份任务执行失败: {}", e))?
+    })
+}
+
+/// 从外部 state.vscdb 文件导入账户（如无法再启动的旧安装）
+///
+/// 以只读方式打开数据库，提取与备份相同的字段写入账户目录下的 `{label}.json`；
+/// `label` 为空时使用账户邮箱
+#[tauri::command]
+pub async fn import_account_from_db(db_path: String, label: String) -> Result<String, String> {
+    crate::log_async_command!("import_account_from_db", async {
+        tokio::task::spawn_blocking(move || -> Result<String, String> {
+        crate::app_settings::ensure_not_read_only()?;
+
+        let db_file = std::path::PathBuf::from(&db_path);
+        if !db_file.is_file() {
+            return Err(format!("数据库文件不存在: {}", db_path));
+        }
+        let conn = crate::antigravity::account::open_db_with_retry(
+            &db_file,
+            rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
+        )?;
+
+        let read_key = |key: &str| -> Result<Option<String>, String> {
+            conn.query_row("SELECT value FROM ItemTable WHERE key = ?", [key], |row| {
+                row.get(0)
+            })
+            .optional()
+            .map_err(|e| format!("查询 {} 失败: {}", key, e))
+        };
+
+        if read_key(crate::constants::database::AUTH_STATUS)?.is_none() {
+            return Err("数据库中未找到 antigravityAuthStatus，可能未登录".to_string());
+        }
+        let jetski_state = read_key(crate::constants::database::AGENT_STATE)?
+            .ok_or_else(|| "数据库中未找到 jetskiStateSync.agentManagerInitState".to_string())?;
+        let email = crate::antigravity::account::decode_jetski_state_email(&jetski_state)?;
+
+        let label = if label.trim().is_empty() {
+            email.clone()
+        } else {
+            label
+        };
+        let account_file = crate::directories::get_account_file(&label)?;
+        if account_file.exists() {
+            return Err(format!("账户备份已存在: {}", label));
+        }
+
+        let mut content = serde_json::json!({
+            crate::constants::database::AGENT_STATE: jetski_state
+        });
+        crate::antigravity::backup_format::stamp_format_version(&mut content);
+        let json =
+            serde_json::to_string_pretty(&content).map_err(|e| format!("序列化备份失败: {}", e))?;
+        fs::write(&account_file, json).map_err(|e| format!("写入备份文件失败: {}", e))?;
+
+        tracing::info!(target: "account::import", file = %account_file.display(), "已从外部数据库导入账户");
+        Ok(format!("已导入账户 {}（{}）", label, email))
+    })
+        .await
+        .map_err(|e| format!("导入任务执行失败: {}", e))?
+    })
+}
+
+/// 外部备份结果
+#[derive(Debug, serde::Serialize)]
+pub struct ExternalBackupResult {
+    /// 实际写入（或因内容相同而跳过）的文件路径
+    pub path: String,
+    pub email: String,
+    /// 是否实际写入了文件（去重跳过时为 false）
+    pub written: bool,
+}
+
+/// 备份当前账户到用户指定的路径
+///
+/// `dedup` 为 true 时，若目标文件已存在且内容相同则跳过写入
+#[tauri::command]
+pub async fn backup_current_account_to(
+    dest_path: String,
+    dedup: Option<bool>,
+) -> Result<ExternalBackupResult, String> {
+    crate::log_async_command!("backup_current_account_to", async {
+        tokio::task::spawn_blocking(move || -> Result<ExternalBackupResult, String> {
+        let dest = std::path::PathBuf::from(&dest_path);
+        let parent = dest
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .ok_or_else(|| format!("目标路径无效: {}", dest_path))?;
+        let parent_meta = fs::metadata(parent)
+            .map_err(|e| format!("目标目录不存在 {}: {}", parent.display(), e))?;
+        if !parent_meta.is_dir() {
+            return Err(format!("目标目录不是文件夹: {}", parent.display()));
+        }
+        crate::directories::probe_dir_writable(parent)
+            .map_err(|e| format!("目标目录不可写 {}: {}", parent.display(), e))?;
+
+        let (jetski_state, email) = crate::antigravity::account::read_current_jetski_state()?;
+
+        let mut content = serde_json::json!({
+            crate::constants::database::AGENT_STATE: jetski_state
+        });
+        crate::antigravity::backup_format::stamp_format_version(&mut content);
+
+        if dedup.unwrap_or(false) {
+            let existing = crate::antigravity::backup_format::read_backup_file(&dest).ok();
+            if existing.as_ref() == Some(&content) {
+                tracing::info!(target: "account::backup", path = %dest.display(), "目标文件内容相同，跳过写入");
+                return Ok(ExternalBackupResult {
+                    path: dest.display().to_string(),
+                    email,
+                    written: false,
+                });
+            }
+        }
+
+        let json =
+            serde_json::to_string_pretty(&content).map_err(|e| format!("序列化备份失败: {}", e))?;
+        fs::write(&dest, json).map_err(|e| format!("写入备份文件失败: {}", e))?;
+
+        Ok(ExternalBackupResult {
+            path: dest.display().to_string(),
+            email,
+            written: true,
+        })
+    })
+        .await
+        .map_err(|e| format!("备份任务执行失败: {}", e))?
+    })
+}
+
+/// 获取账户头像的本地缓存路径
+///
+/// 头像地址优先使用传入的 `profile_url`，其次读取备份中的 antigravityAuthStatus；
+/// 无地址或离线下载失败时返回 None
+#[tauri::command]
+pub async fn get_account_avatar(
+    app: tauri::AppHandle,
+    account_name: String,
+    profile_url: Option<String>,
+) -> Result<Option<String>, String> {
+    use tauri::Manager;
+    let account_file = crate::directories::get_account_file(&account_name)?;
+    let Some(url) = profile_url
+        .filter(|url| url.starts_with("http"))
+        .or_else(|| crate::antigravity::avatar::profile_url_from_backup(&account_file))
+    else {
+        return Ok(None);
+    };
+
+    let timeout_ms = app
+        .state::<crate::app_settings::AppSettingsManager>()
+        .get_settings()
+        .http_timeout_ms;
+    Ok(crate::antigravity::avatar::get_cached_avatar(
+        &url,
+        std::time::Duration::from_millis(timeout_ms),
+    )
+    .await
+    .map(|path| path.display().to_string()))
+}
+
 /// 清除所有 Antigravity 数据
 #[tauri::command]
-pub async fn clear_all_antigravity_data() -> Result<String, String> {
+pub async fn clear_all_antigravity_data(
+) -> Result<crate::antigravity::cleanup::ClearDataResult, String> {
+    crate::app_settings::ensure_not_read_only()?;
+    let _operation = crate::antigravity::account::try_begin_account_operation()?;
     crate::antigravity::cleanup::clear_all_antigravity_data().await
 }
 
+/// 列出备份中存在、但恢复时不会写回数据库的键（如 antigravityAuthStatus 会被删除而非恢复）
+#[tauri::command]
+pub async fn list_unrestored_keys(account_name: String) -> Result<Vec<String>, String> {
+    let account_file = crate::directories::get_account_file(&account_name)?;
+    crate::antigravity::restore::list_unrestored_keys(&account_file)
+}
+
+/// 设置账户备注与标签（保存在备份文件中，重新备份同一账户时保留）
+///
+/// 标签会去除首尾空白、空标签和重复项；备注和标签都为空时删除元数据
+#[tauri::command]
+pub async fn set_account_meta(
+    name: String,
+    notes: String,
+    tags: Vec<String>,
+) -> Result<crate::antigravity::backup_format::AccountMeta, String> {
+    crate::log_async_command!("set_account_meta", async {
+        crate::app_settings::ensure_not_read_only()?;
+
+        let account_file = crate::directories::get_account_file(&name)?;
+        if !account_file.exists() {
+            return Err(format!("账户备份不存在: {}", name));
+        }
+
+        let mut normalized_tags: Vec<String> = Vec::new();
+        for tag in tags
+            .iter()
+            .map(|tag| tag.trim())
+            .filter(|tag| !tag.is_empty())
+        {
+            if !normalized_tags.iter().any(|existing| existing == tag) {
+                normalized_tags.push(tag.to_string());
+            }
+        }
+        let meta = crate::antigravity::backup_format::AccountMeta {
+            notes: notes.trim().to_string(),
+            tags: normalized_tags,
+        };
+
+        crate::antigravity::backup_format::update_account_meta(&account_file, &meta)?;
+        Ok(meta)
+    })
+}
+
+/// 获取账户备份目录所在磁盘卷的可用空间（字节）
+#[tauri::command]
+pub async fn get_accounts_volume_free_bytes() -> Result<u64, String> {
+    crate::antigravity::backup_format::accounts_volume_free_bytes()
+}
+
+/// 压缩 Antigravity 状态数据库（VACUUM），回收清除数据后未释放的空间
+///
+/// VACUUM 会锁定数据库文件，必须传入 `confirm: true`，且 Antigravity 未在运行
+#[tauri::command]
+pub async fn compact_antigravity_db(
+    confirm: bool,
+) -> Result<crate::antigravity::cleanup::CompactResult, String> {
+    crate::log_async_command!("compact_antigravity_db", async {
+        if !confirm {
+            return Err("压缩数据库会锁定文件，请确认后再执行（confirm = true）".to_string());
+        }
+        crate::app_settings::ensure_not_read_only()?;
+        if crate::platform::is_antigravity_running() {
+            return Err("Antigravity 正在运行，请先关闭后再压缩数据库".to_string());
+        }
+        let _operation = crate::antigravity::account::try_begin_account_operation()?;
+
+        tokio::task::spawn_blocking(crate::antigravity::cleanup::compact_antigravity_db)
+            .await
+            .map_err(|e| format!("压缩任务执行失败: {}", e))?
+    })
+}
+
+/// 是否处于只读模式，供前端隐藏破坏性操作按钮
+#[tauri::command]
+pub async fn is_read_only() -> Result<bool, String> {
+    Ok(crate::app_settings::is_read_only())
+}
+
+/// 是否有账户操作（切换、登录新账户、清除数据）正在进行，供前端禁用按钮
+#[tauri::command]
+pub async fn is_account_operation_in_progress() -> Result<bool, String> {
+    Ok(crate::antigravity::account::is_account_operation_in_progress())
+}
+
+/// 创建测试账户备份（无需真实登录，带 test 标记）
+#[tauri::command]
+pub async fn create_test_account(email: String) -> Result<String, String> {
+    crate::log_async_command!("create_test_account", async {
+        let email = email.trim().to_string();
+        if email.is_empty() || !email.contains('@') {
+            return Err(format!("邮箱格式无效: {}", email));
+        }
+
+        let account_file = crate::directories::get_account_file(&email)?;
+        if account_file.exists() {
+            return Err(format!("账户备份已存在: {}", email));
+        }
+
+        let auth_status = serde_json::json!({
+            "name": "Test Account",
+            "email": email,
+            "apiKey": crate::antigravity::account::TEST_ACCOUNT_PLACEHOLDER_TOKEN,
+        });
+        let mut content = serde_json::json!({
+            crate::constants::database::AGENT_STATE: crate::antigravity::account::encode_test_jetski_state(&email),
+            crate::constants::database::AUTH_STATUS: auth_status.to_string(),
+            "test": true,
+        });
+        crate::antigravity::backup_format::stamp_format_version(&mut content);
+        let json = serde_json::to_string_pretty(&content)
+            .map_err(|e| format!("序列化测试账户失败: {}", e))?;
+        fs::write(&account_file, json).map_err(|e| format!("写入测试账户失败: {}", e))?;
+
+        tracing::info!(target: "account::test", file = %account_file.display(), "已创建测试账户");
+        Ok(format!("已创建测试账户: {}", email))
+    })
+}
+
+/// 创建登出状态备份，恢复后 Antigravity 处于未登录状态，其他设置保持不变
+///
+/// 备份带有 `logged_out: true` 标记，账户列表以此与真实账户区分
+#[tauri::command]
+pub async fn create_logged_out_backup(name: String) -> Result<String, String> {
+    crate::log_async_command!("create_logged_out_backup", async {
+        let account_file = crate::directories::get_account_file(&name)?;
+        if account_file.exists() {
+            return Err(format!("账户备份已存在: {}", name));
+        }
+
+        let mut content = serde_json::json!({
+            crate::antigravity::backup_format::LOGGED_OUT_KEY: true,
+        });
+        crate::antigravity::backup_format::stamp_format_version(&mut content);
+        let json = serde_json::to_string_pretty(&content)
+            .map_err(|e| format!("序列化登出状态备份失败: {}", e))?;
+        fs::write(&account_file, json).map_err(|e| format!("写入登出状态备份失败: {}", e))?;
+
+        tracing::info!(target: "account::logged_out", file = %account_file.display(), "已创建登出状态备份");
+        Ok(format!("已创建登出状态备份: {}", name))
+    })
+}
+
+/// 判断备份文件是否为测试账户
+fn is_test_account_file(account_file: &std::path::Path) -> bool {
+    crate::antigravity::backup_format::read_backup_file(account_file)
+        .ok()
+        .and_then(|v| v.get("test").and_then(|t| t.as_bool()))
+        .unwrap_or(false)
+}
+
+/// 恢复前是否需要关闭 Antigravity 的检查结果
+#[derive(Debug, serde::Serialize)]
+pub struct RestoreCloseCheck {
+    /// Antigravity 当前是否在运行
+    pub running: bool,
+    /// 恢复前是否需要先关闭 Antigravity
+    pub requires_close: bool,
+    pub message: String,
+}
+
+/// 检查恢复前是否需要关闭 Antigravity
+///
+/// Antigravity 运行时会在内存中缓存状态，退出时可能覆盖刚写入 state.vscdb 的数据
+fn check_restore_requires_close() -> RestoreCloseCheck {
+    let running = crate::platform::is_antigravity_running();
+    RestoreCloseCheck {
+        running,
+        requires_close: running,
+        message: if running {
+            "Antigravity 正在运行，恢复的数据可能在其退出时被覆盖，请先关闭 Antigravity".to_string()
+        } else {
+            "Antigravity 未运行，可以直接恢复".to_string()
+        },
+    }
+}
+
+/// 恢复入口检查：Antigravity 运行中且未传入 `allow_while_running = true` 时返回错误
+fn ensure_restore_allowed(allow_while_running: Option<bool>) -> Result<(), String> {
+    if allow_while_running.unwrap_or(false) {
+        return Ok(());
+    }
+    let check = check_restore_requires_close();
+    if check.requires_close {
+        return Err(format!(
+            "{}（如确需在运行中恢复，请使用 allow_while_running 参数）",
+            check.message
+        ));
+    }
+    Ok(())
+}
+
+/// 查询恢复账户前是否需要关闭 Antigravity
+#[tauri::command]
+pub async fn restore_requires_close() -> Result<RestoreCloseCheck, String> {
+    Ok(check_restore_requires_close())
+}
+
 /// 恢复 Antigravity 账户
+///
+/// 测试账户默认拒绝恢复，需传入 `force = true`；
+/// Antigravity 运行中默认拒绝恢复，需传入 `allow_while_running = true`
 #[tauri::command]
-pub async fn restore_antigravity_account(account_name: String) -> Result<String, String> {
+pub async fn restore_antigravity_account(
+    account_name: String,
+    force: Option<bool>,
+    allow_while_running: Option<bool>,
+) -> Result<String, String> {
     tracing::debug!(target: "account::restore", account_name = %account_name, "调用 restore_antigravity_account");
+    crate::app_settings::ensure_not_read_only()?;
 
-    // 1. 构建备份文件路径
-    let accounts_dir = crate::directories::get_accounts_directory();
-    let account_file = accounts_dir.join(format!("{account_name}.json"));
+    // 同一时间只允许一个账户操作，避免与切换等操作并发写数据库
+    let _operation = crate::antigravity::account::try_begin_account_operation()?;
+    restore_account_unlocked(account_name, force, allow_while_running).await
+}
+
+/// 恢复账户的实际流程，调用方需已持有账户操作锁（切换流程在持锁期间调用）
+async fn restore_account_unlocked(
+    account_name: String,
+    force: Option<bool>,
+    allow_while_running: Option<bool>,
+) -> Result<String, String> {
+    ensure_restore_allowed(allow_while_running)?;
+
+    // 1. 构建备份文件路径（校验账户名，防止路径穿越）
+    let account_file = crate::directories::get_account_file(&account_name)?;
+
+    if !force.unwrap_or(false) && is_test_account_file(&account_file) {
+        return Err(format!(
+            "{} 是测试账户，如需恢复请使用 force 参数",
+            account_name
+        ));
+    }
 
     // 2. 调用统一的恢复函数
     crate::antigravity::restore::save_antigravity_account_to_file(account_file).await
 }
 
-/// 切换到 Antigravity 账户（调用 restore_antigravity_account）
+/// 只恢复备份中指定的键，其余数据库内容保持不变
+///
+/// 与完整恢复相同，Antigravity 运行中默认拒绝，需传入 `allow_while_running = true`
 #[tauri::command]
-pub async fn switch_to_antigravity_account(account_name: String) -> Result<String, String> {
-    crate::log_async_command!("switch_to_antigravity_account", async {
+pub async fn restore_account_partial(
+    account_name: String,
+    keys: Vec<String>,
+    allow_while_running: Option<bool>,
+) -> Result<crate::antigravity::restore::PartialRestoreResult, String> {
+    crate::log_async_command!("restore_account_partial", async {
+        tokio::task::spawn_blocking(
+            move || -> Result<crate::antigravity::restore::PartialRestoreResult, String> {
+                crate::app_settings::ensure_not_read_only()?;
+                ensure_restore_allowed(allow_while_running)?;
+                let _operation = crate::antigravity::account::try_begin_account_operation()?;
+                let account_file = crate::directories::get_account_file(&account_name)?;
+                crate::antigravity::restore::restore_keys_from_file(account_file, &keys)
+            },
+        )
+        .await
+        .map_err(|e| format!("恢复任务执行失败: {}", e))?
+    })
+}
+
+/// 预览恢复指定账户将修改的数据库键（不写入任何数据）
+#[tauri::command]
+pub async fn preview_restore(
+    account_name: String,
+) -> Result<Vec<crate::antigravity::restore::RestorePreviewEntry>, String> {
+    crate::log_async_command!("preview_restore", async {
+        tokio::task::spawn_blocking(
+            move || -> Result<Vec<crate::antigravity::restore::RestorePreviewEntry>, String> {
+                let account_file = crate::directories::get_account_file(&account_name)?;
+                crate::antigravity::restore::preview_restore_from_file(account_file)
+            },
+        )
+        .await
+        .map_err(|e| format!("数据库任务执行失败: {}", e))?
+    })
+}
+
+/// 目标备份与当前登录账户相同时，原地写回备份中的键并返回结果消息；不是同一账户时返回 None
+fn refresh_current_account_in_place(account_name: &str) -> Result<Option<String>, String> {
+    let account_file = crate::directories::get_account_file(account_name)?;
+    let backup = crate::antigravity::backup_format::read_backup_file(&account_file)?;
+    let Some(target_email) = backup
+        .get(crate::constants::database::AGENT_STATE)
+        .and_then(|v| v.as_str())
+        .and_then(|state| crate::antigravity::account::decode_jetski_state_email(state).ok())
+    else {
+        return Ok(None);
+    };
+    if crate::antigravity::account::read_current_account_email().as_deref()
+        != Some(target_email.as_str())
+    {
+        return Ok(None);
+    }
+
+    let result = crate::antigravity::restore::restore_keys_from_file(
+        account_file,
+        &[crate::constants::database::AGENT_STATE.to_string()],
+    )?;
+    tracing::info!(target: "account::switch", email = %target_email, "目标为当前登录账户，跳过关闭/重启，原地刷新");
+
+    // Antigravity 未运行时仍按切换流程启动它
+    let start_message = if crate::platform::is_antigravity_running() {
+        "Antigravity 保持运行".to_string()
+    } else {
+        crate::antigravity::starter::start_antigravity(&[])
+            .unwrap_or_else(|e| format!("启动失败: {}", e))
+    };
+
+    Ok(Some(format!(
+        "{} 已是当前登录账户，原地刷新 {} 项 -> {}",
+        target_email,
+        result.applied.len(),
+        start_message
+    )))
+}
+
+/// 切换到 Antigravity 账户（在持有账户操作锁期间执行恢复流程）
+///
+/// 目标账户就是当前登录账户时走快速路径：不关闭/重启 Antigravity，只原地写回备份中的键；
+/// 传入 `force_full_switch = true` 可强制走完整流程。
+/// Antigravity 运行中时原地写回可能被其覆盖，除非传入 `allow_while_running = true`，否则改走完整流程
+#[tauri::command]
+pub async fn switch_to_antigravity_account(
+    account_name: String,
+    force: Option<bool>,
+    force_full_switch: Option<bool>,
+    allow_while_running: Option<bool>,
+) -> Result<String, String> {
+    let started = std::time::Instant::now();
+    let result = crate::log_async_command!("switch_to_antigravity_account", async {
+        crate::app_settings::ensure_not_read_only()?;
+
+        // 同一时间只允许一个账户操作，重复点击直接返回错误
+        let _operation = crate::antigravity::account::try_begin_account_operation()?;
+
+        // 测试账户在关闭进程前就拒绝，避免无谓地中断 Antigravity
+        if !force.unwrap_or(false) {
+            let account_file = crate::directories::get_account_file(&account_name)?;
+            if is_test_account_file(&account_file) {
+                return Err(format!(
+                    "{} 是测试账户，如需切换请使用 force 参数",
+                    account_name
+                ));
+            }
+        }
+
+        // 同一账户刷新：跳过关闭进程和完整恢复
+        let in_place_allowed =
+            allow_while_running.unwrap_or(false) || !check_restore_requires_close().requires_close;
+        if !in_place_allowed {
+            tracing::info!(target: "account::switch", "Antigravity 正在运行，不原地刷新，改走完整切换流程");
+        }
+        if !force_full_switch.unwrap_or(false) && in_place_allowed {
+            let refreshed = tokio::task::spawn_blocking({
+                let account_name = account_name.clone();
+                move || refresh_current_account_in_place(&account_name)
+            })
+            .await
+            .map_err(|e| format!("原地刷新任务执行失败: {}", e))??;
+            if let Some(message) = refreshed {
+                return Ok(message);
+            }
+        }
+
         // 1. 关闭 Antigravity 进程 (如果存在)
-        let kill_result = match crate::platform::kill_antigravity_processes() {
-            Ok(result) => {
-                if result.contains("not found") || result.contains("未找到") {
-                    tracing::debug!(target: "account::switch::step1", "Antigravity 进程未运行，跳过关闭步骤");
-                    "Antigravity 进程未运行".to_string()
-                } else {
-                    tracing::debug!(target: "account::switch::step1", result = %result, "进程关闭完成");
-                    result
+        let kill_result = timed_step("kill", async {
+            match crate::platform::kill_antigravity_processes() {
+                Ok(result) => {
+                    if result.contains("not found") || result.contains("未找到") {
+                        tracing::debug!(target: "account::switch::step1", "Antigravity 进程未运行，跳过关闭步骤");
+                        Ok("Antigravity 进程未运行".to_string())
+                    } else {
+                        tracing::debug!(target: "account::switch::step1", result = %result, "进程关闭完成");
+                        Ok(result)
+                    }
                 }
-            }
-            Err(e) => {
-                if e.contains("not found") || e.contains("未找到") {
-                    tracing::debug!(target: "account::switch::step1", "Antigravity 进程未运行，跳过关闭步骤");
-                    "Antigravity 进程未运行".to_string()
-                } else {
-                    tracing::error!(target: "account::switch::step1", error = %e, "关闭进程时发生错误");
-                    return Err(format!("关闭进程时发生错误: {}", e));
+                Err(e) => {
+                    if e.contains("not found") || e.contains("未找到") {
+                        tracing::debug!(target: "account::switch::step1", "Antigravity 进程未运行，跳过关闭步骤");
+                        Ok("Antigravity 进程未运行".to_string())
+                    } else {
+                        tracing::error!(target: "account::switch::step1", error = %e, "关闭进程时发生错误");
+                        Err(format!("关闭进程时发生错误: {}", e))
+                    }
                 }
             }
-        };
+        })
+        .await?;
 
         // 等待一秒确保进程完全关闭
         tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
 
         // 2. 恢复指定账户到 Antigravity 数据库
-        let restore_result = restore_antigravity_account(account_name.clone()).await?;
+        let restore_result = timed_step(
+            "restore",
+            // 进程已在上一步关闭，这里不再重复检查运行状态
+            restore_account_unlocked(account_name.clone(), force, Some(true)),
+        )
+        .await?;
         tracing::debug!(target: "account::switch::step2", result = %restore_result, "账户数据恢复完成");
 
         // 等待一秒确保数据库操作完成
         tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
 
         // 3. 重新启动 Antigravity 进程
-        let start_result = crate::antigravity::starter::start_antigravity();
+        let start_result = timed_step("start", async {
+            crate::antigravity::starter::start_antigravity(&[])
+        })
+        .await;
         let start_message = match start_result {
             Ok(result) => {
                 tracing::debug!(target: "account::switch::step3", result = %result, "Antigravity 启动成功");
@@ -363,5 +1387,223 @@ pub async fn switch_to_antigravity_account(account_name: String) -> Result<Strin
         let final_message = format!("{} -> {} -> {}", kill_result, restore_result, start_message);
 
         Ok(final_message)
+    });
+
+    crate::metrics::record_switch(result.is_ok(), started.elapsed());
+    // 备份名可以是自定义标签，历史中记录备份里实际的账户邮箱；成功和失败都记录
+    let email = backup_account_email(&account_name).or(Some(account_name));
+    crate::antigravity::history::append_switch_history("switch", email, result.is_ok());
+    result
+}
+
+/// 从账户备份中解码出账户邮箱，备份不存在或无法解码时返回 None
+fn backup_account_email(account_name: &str) -> Option<String> {
+    let account_file = crate::directories::get_account_file(account_name).ok()?;
+    let backup = crate::antigravity::backup_format::read_backup_file(&account_file).ok()?;
+    let state = backup
+        .get(crate::constants::database::AGENT_STATE)?
+        .as_str()?;
+    crate::antigravity::account::decode_jetski_state_email(state).ok()
+}
+
+/// 获取最近的账户切换历史（最新的在前），默认 50 条
+#[tauri::command]
+pub async fn get_switch_history(
+    limit: Option<usize>,
+) -> Result<Vec<crate::antigravity::history::SwitchHistoryEntry>, String> {
+    crate::antigravity::history::read_switch_history(limit.unwrap_or(50))
+}
+
+/// 切换并验证的结果
+#[derive(Debug, serde::Serialize)]
+pub struct SwitchVerifyResult {
+    /// 切换流程（关闭、恢复、启动）是否成功
+    pub switched: bool,
+    /// Antigravity 启动并重新写入登录状态后，当前登录邮箱是否与目标账户一致
+    pub verified: bool,
+    /// 目标账户邮箱
+    pub target_email: String,
+    /// 验证结束时读取到的登录邮箱
+    pub final_email: Option<String>,
+    /// 切换结果或失败原因
+    pub message: String,
+}
+
+/// 切换验证的默认超时时间
+const DEFAULT_VERIFY_TIMEOUT_SECS: u64 = 30;
+
+/// 切换到指定账户，并等待 Antigravity 启动后确认登录邮箱与目标一致
+///
+/// 恢复后的状态在 Antigravity 启动前就已可读，单看邮箱无法区分"已生效"和"尚未被覆盖"；
+/// 恢复时会删除 `antigravityAuthStatus`，因此要等 Antigravity 重新写入该键后再比对邮箱。
+/// 超时（默认 30 秒）内未能确认时 `verified` 为 false，不视为错误
+#[tauri::command]
+pub async fn switch_and_verify(
+    account_name: String,
+    force: Option<bool>,
+    timeout_secs: Option<u64>,
+) -> Result<SwitchVerifyResult, String> {
+    crate::log_async_command!("switch_and_verify", async {
+        let account_file = crate::directories::get_account_file(&account_name)?;
+        let backup = crate::antigravity::backup_format::read_backup_file(&account_file)?;
+        let target_email = backup
+            .get(crate::constants::database::AGENT_STATE)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| format!("备份 {} 缺少账户状态数据", account_name))
+            .and_then(crate::antigravity::account::decode_jetski_state_email)?;
+
+        let message =
+            match switch_to_antigravity_account(account_name.clone(), force, None, None).await {
+                Ok(message) => message,
+                Err(e) => {
+                    let final_email = tokio::task::spawn_blocking(
+                        crate::antigravity::account::read_current_account_email,
+                    )
+                    .await
+                    .unwrap_or(None);
+                    return Ok(SwitchVerifyResult {
+                        switched: false,
+                        verified: false,
+                        target_email,
+                        final_email,
+                        message: e,
+                    });
+                }
+            };
+
+        let deadline = tokio::time::Instant::now()
+            + tokio::time::Duration::from_secs(timeout_secs.unwrap_or(DEFAULT_VERIFY_TIMEOUT_SECS));
+        let mut final_email = None;
+        let mut verified = false;
+
+        while tokio::time::Instant::now() < deadline {
+            // 先等 Antigravity 进程起来，再读取它实际使用的登录状态
+            let running = tokio::task::spawn_blocking(crate::platform::is_antigravity_running)
+                .await
+                .unwrap_or(false);
+            // 登录状态被重新写入，说明 Antigravity 已读取并接管了恢复的账户
+            if running
+                && tokio::task::spawn_blocking(crate::antigravity::account::has_auth_status)
+                    .await
+                    .unwrap_or(false)
+            {
+                final_email = tokio::task::spawn_blocking(
+                    crate::antigravity::account::read_current_account_email,
+                )
+                .await
+                .unwrap_or(None);
+                if final_email.as_deref() == Some(target_email.as_str()) {
+                    verified = true;
+                    break;
+                }
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+        }
+
+        if verified {
+            tracing::info!(target: "account::switch::verify", email = %target_email, "✅ 切换后登录账户验证通过");
+        } else {
+            tracing::warn!(
+                target: "account::switch::verify",
+                target_email = %target_email,
+                final_email = ?final_email,
+                "⚠️ 超时内未能确认切换后的登录账户"
+            );
+        }
+
+        Ok(SwitchVerifyResult {
+            switched: true,
+            verified,
+            target_email,
+            final_email,
+            message,
+        })
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{load_account_entries, write_account_backup};
+
+    #[tokio::test]
+    async fn load_account_entries_skips_invalid_backups_with_warnings() {
+        let dir = std::env::temp_dir().join(format!(
+            "antigravity-agent-account-list-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let valid = serde_json::json!({
+            crate::constants::database::AGENT_STATE:
+                crate::antigravity::account::encode_test_jetski_state("valid@example.com"),
+            "format_version": 1,
+        });
+        let files = [
+            ("valid@example.com", valid.to_string()),
+            ("broken-json", "{ not json".to_string()),
+            ("missing-state", r#"{"format_version": 1}"#.to_string()),
+            (
+                "bad-state",
+                r#"{"jetskiStateSync.agentManagerInitState": "!!!"}"#.to_string(),
+            ),
+        ];
+        let mut backup_files = Vec::new();
+        for (name, content) in &files {
+            let path = dir.join(format!("{name}.json"));
+            std::fs::write(&path, content).unwrap();
+            backup_files.push((path, name.to_string()));
+        }
+
+        let (accounts, warnings) =
+            load_account_entries(backup_files, Some("valid@example.com".to_string()), 0).await;
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(accounts.len(), 1);
+        let account = &accounts[0].1;
+        assert_eq!(account["context"]["email"], "valid@example.com");
+        assert_eq!(account["is_current"], true);
+        assert_eq!(account["backup_name"], "valid@example.com");
+
+        assert_eq!(warnings.len(), 3);
+        for (warning, name) in warnings
+            .iter()
+            .zip(["bad-state", "broken-json", "missing-state"])
+        {
+            assert!(warning.starts_with(&format!("{name}: ")), "{warning}");
+        }
+    }
+
+    /// 备份写入与恢复读取必须解析到同一个账户目录（配置目录下，而不是系统 config_dir）
+    #[cfg(unix)]
+    #[test]
+    fn backup_and_restore_resolve_to_same_accounts_directory() {
+        // 配置目录以用户主目录为根，指向临时目录避免写入真实的用户配置
+        let home = std::env::temp_dir().join(format!(
+            "antigravity-agent-accounts-dir-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&home).unwrap();
+        std::env::set_var("HOME", &home);
+
+        let email = "roundtrip@example.com";
+        let state = crate::antigravity::account::encode_test_jetski_state(email);
+        write_account_backup(&state, email).unwrap();
+
+        let config_dir = crate::directories::get_config_directory();
+        let accounts_dir = crate::directories::get_accounts_directory();
+        let (plain, compressed) = crate::directories::get_account_file_variants(email).unwrap();
+        let restore_file = crate::directories::get_account_file(email).unwrap();
+        let backup = crate::antigravity::backup_format::read_backup_file(&restore_file);
+        std::fs::remove_dir_all(&home).ok();
+
+        assert!(config_dir.starts_with(&home));
+        assert!(accounts_dir.starts_with(&config_dir));
+        assert_eq!(plain.parent(), Some(accounts_dir.as_path()));
+        assert_eq!(compressed.parent(), Some(accounts_dir.as_path()));
+        assert_eq!(restore_file, plain);
+        assert_eq!(
+            backup.unwrap()[crate::constants::database::AGENT_STATE],
+            state.as_str()
+        );
+    }
+}
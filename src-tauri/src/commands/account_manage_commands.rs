@@ -1,11 +1,12 @@
 //! 账户备份/导入导出与加解密命令
 
 use crate::log_async_command;
+use crate::utils::tracing_config::timed_step;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fs;
 use std::time::SystemTime;
-use tauri::State;
+use tauri::Manager;
 
 /// 备份数据收集结构
 #[derive(Serialize, Deserialize, Debug)]
@@ -31,15 +32,20 @@ pub struct FailedAccountExportedData {
     error: String,
 }
 
+/// 账户名解析出的备份文件路径
+#[derive(Serialize, Debug)]
+pub struct ResolvedAccountPath {
+    path: String,
+    exists: bool,
+}
+
 /// 收集所有账户文件的完整内容, 用于导出
 #[tauri::command]
-pub async fn collect_account_contents(
-    state: State<'_, crate::AppState>,
-) -> Result<Vec<AccountExportedData>, String> {
+pub async fn collect_account_contents() -> Result<Vec<AccountExportedData>, String> {
     let mut backups_with_content = Vec::new();
 
     // 读取Antigravity账户目录中的JSON文件
-    let antigravity_dir = state.config_dir.join("antigravity-accounts");
+    let antigravity_dir = crate::directories::get_accounts_directory();
 
     if !antigravity_dir.exists() {
         return Ok(backups_with_content);
@@ -50,36 +56,27 @@ pub async fn collect_account_contents(
         let entry = entry.map_err(|e| format!("读取目录项失败: {}", e))?;
         let path = entry.path();
 
-        if path.extension().is_some_and(|ext| ext == "json") {
-            let filename = path
-                .file_name()
-                .and_then(|name| name.to_str())
-                .map(|s| s.to_string())
-                .unwrap_or_default();
-
-            if filename.is_empty() {
-                continue;
-            }
-
-            match fs::read_to_string(&path).map_err(|e| format!("读取文件失败 {}: {}", filename, e))
-            {
-                Ok(content) => match serde_json::from_str::<serde_json::Value>(&content) {
-                    Ok(json_value) => {
-                        backups_with_content.push(AccountExportedData {
-                            filename,
-                            content: json_value,
-                            timestamp: SystemTime::now()
-                                .duration_since(std::time::UNIX_EPOCH)
-                                .unwrap_or_default()
-                                .as_secs(),
-                        });
-                    }
-                    Err(e) => {
-                        tracing::warn!(target: "backup::scan", filename = %filename, error = %e, "跳过损坏的备份文件");
-                    }
-                },
-                Err(_) => {
-                    tracing::warn!(target: "backup::scan", filename = %filename, "跳过无法读取的文件");
+        if path.is_file() {
+            // 压缩备份导出时解压，统一以 {账户名}.json 命名，保证可以重新导入
+            let filename = match crate::directories::account_name_from_path(&path) {
+                Some(name) => format!("{name}.json"),
+                None => continue,
+            };
+
+            // 增量备份在导出时还原为完整内容，保证导出文件可独立使用
+            match crate::antigravity::backup_format::read_backup_file(&path) {
+                Ok(json_value) => {
+                    backups_with_content.push(AccountExportedData {
+                        filename,
+                        content: json_value,
+                        timestamp: SystemTime::now()
+                            .duration_since(std::time::UNIX_EPOCH)
+                            .unwrap_or_default()
+                            .as_secs(),
+                    });
+                }
+                Err(e) => {
+                    tracing::warn!(target: "backup::scan", filename = %filename, error = %e, "跳过损坏的备份文件");
                 }
             }
         }
@@ -92,15 +89,16 @@ pub async fn collect_account_contents(
 #[tauri::command]
 pub async fn restore_backup_files(
     account_file_data: Vec<AccountExportedData>,
-    state: State<'_, crate::AppState>,
 ) -> Result<RestoreResult, String> {
+    crate::app_settings::ensure_not_read_only()?;
+
     let mut results = RestoreResult {
         restored_count: 0,
         failed: Vec::new(),
     };
 
     // 获取目标目录
-    let antigravity_dir = state.config_dir.join("antigravity-accounts");
+    let antigravity_dir = crate::directories::get_accounts_directory();
 
     // 确保目录存在
     if let Err(e) = fs::create_dir_all(&antigravity_dir) {
@@ -109,7 +107,22 @@ pub async fn restore_backup_files(
 
     // 遍历每个备份
     for account_file in account_file_data {
-        let file_path = antigravity_dir.join(&account_file.filename);
+        // 文件名必须是 {账户名}.json，防止写出账户目录
+        let paths = account_file
+            .filename
+            .strip_suffix(".json")
+            .ok_or_else(|| "文件名必须以 .json 结尾".to_string())
+            .and_then(crate::directories::get_account_file_variants);
+        let (file_path, stale_file) = match paths {
+            Ok(paths) => paths,
+            Err(e) => {
+                results.failed.push(FailedAccountExportedData {
+                    filename: account_file.filename,
+                    error: e,
+                });
+                continue;
+            }
+        };
 
         match fs::write(
             &file_path,
@@ -118,6 +131,12 @@ pub async fn restore_backup_files(
         .map_err(|e| format!("写入文件失败: {}", e))
         {
             Ok(_) => {
+                // 与 write_account_backup 一致：删除同一账户的压缩格式旧备份，避免列表中重复出现
+                if stale_file.exists() {
+                    if let Err(e) = fs::remove_file(&stale_file) {
+                        tracing::warn!(file = %stale_file.display(), error = %e, "删除旧格式备份失败");
+                    }
+                }
                 results.restored_count += 1;
             }
             Err(e) => {
@@ -134,26 +153,101 @@ pub async fn restore_backup_files(
 
 /// 删除指定备份
 #[tauri::command]
-pub async fn delete_backup(
-    name: String,
-    state: State<'_, crate::AppState>,
-) -> Result<String, String> {
-    // 只删除Antigravity账户JSON文件
-    let antigravity_dir = state.config_dir.join("antigravity-accounts");
-    let antigravity_file = antigravity_dir.join(format!("{}.json", name));
+pub async fn delete_backup(name: String) -> Result<String, String> {
+    crate::app_settings::ensure_not_read_only()?;
+
+    // 只删除Antigravity账户备份文件（.json / .json.gz，校验账户名，防止路径穿越）
+    let name = crate::directories::sanitize_account_name(&name)?;
+    let (plain_file, compressed_file) = crate::directories::get_account_file_variants(&name)?;
+    let candidates = [plain_file, compressed_file];
+
+    let mut deleted = false;
+    for antigravity_file in candidates.iter().filter(|path| path.exists()) {
+        fs::remove_file(antigravity_file).map_err(|e| format!("删除用户文件失败: {}", e))?;
+        deleted = true;
+    }
 
-    if antigravity_file.exists() {
-        fs::remove_file(&antigravity_file).map_err(|e| format!("删除用户文件失败: {}", e))?;
+    if deleted {
+        crate::antigravity::backup_format::remove_unreferenced_blobs();
         Ok(format!("删除用户成功: {}", name))
     } else {
         Err("用户文件不存在".to_string())
     }
 }
 
+/// 以新名称复制一份账户备份（保持原备份格式），返回新备份解码后的账户信息
+#[tauri::command]
+pub async fn duplicate_account(source_name: String, new_name: String) -> Result<Value, String> {
+    log_async_command!("duplicate_account", async {
+        crate::app_settings::ensure_not_read_only()?;
+
+        let source_file = crate::directories::get_account_file(&source_name)?;
+        if !source_file.exists() {
+            return Err(format!("源账户备份不存在: {}", source_name));
+        }
+
+        // 新名称与 .json / .json.gz 两种格式的已有备份都不能冲突
+        let new_name = crate::directories::sanitize_account_name(&new_name)?;
+        let (plain_file, compressed_file) =
+            crate::directories::get_account_file_variants(&new_name)?;
+        if plain_file.exists() || compressed_file.exists() {
+            return Err(format!("已存在同名备份: {}", new_name));
+        }
+
+        // 原样复制文件；增量备份引用的共享数据保持不变，由两个备份共同引用
+        let is_compressed = source_file
+            .to_string_lossy()
+            .ends_with(crate::directories::COMPRESSED_BACKUP_SUFFIX);
+        let target_file = if is_compressed {
+            compressed_file
+        } else {
+            plain_file
+        };
+        fs::copy(&source_file, &target_file).map_err(|e| format!("复制备份失败: {}", e))?;
+
+        let backup_data = crate::antigravity::backup_format::read_backup_file(&target_file)?;
+        let jetski_state = backup_data
+            .get(crate::constants::database::AGENT_STATE)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                format!(
+                    "备份 {} 缺少 jetskiStateSync.agentManagerInitState",
+                    new_name
+                )
+            })?;
+        let mut decoded = crate::antigravity::account::decode_jetski_state_proto(jetski_state)?;
+        if let Some(obj) = decoded.as_object_mut() {
+            obj.insert("backup_name".to_string(), Value::String(new_name.clone()));
+        }
+
+        // 副本的文件名与账户邮箱不一致，记录来源邮箱便于追溯
+        let email = crate::antigravity::account::decode_jetski_state_email(jetski_state)?;
+        if let Err(e) = crate::antigravity::backup_format::mark_duplicate_of(&target_file, &email) {
+            let _ = fs::remove_file(&target_file);
+            return Err(e);
+        }
+
+        tracing::info!(source = %source_name, target = %new_name, "✅ 已复制账户备份");
+        Ok(decoded)
+    })
+}
+
+/// 返回恢复/切换时账户名实际对应的备份文件绝对路径及其是否存在
+#[tauri::command]
+pub async fn resolve_account_path(account_name: String) -> Result<ResolvedAccountPath, String> {
+    let path = crate::directories::get_account_file(&account_name)?;
+    let path = std::path::absolute(&path).unwrap_or(path);
+    Ok(ResolvedAccountPath {
+        exists: path.exists(),
+        path: path.to_string_lossy().to_string(),
+    })
+}
+
 /// 清空所有备份
 #[tauri::command]
-pub async fn clear_all_backups(state: State<'_, crate::AppState>) -> Result<String, String> {
-    let antigravity_dir = state.config_dir.join("antigravity-accounts");
+pub async fn clear_all_backups() -> Result<String, String> {
+    crate::app_settings::ensure_not_read_only()?;
+    let antigravity_dir = crate::directories::get_accounts_directory();
 
     if antigravity_dir.exists() {
         // 读取目录中的所有文件
@@ -164,13 +258,14 @@ pub async fn clear_all_backups(state: State<'_, crate::AppState>) -> Result<Stri
             let entry = entry.map_err(|e| format!("读取目录项失败: {}", e))?;
             let path = entry.path();
 
-            // 只删除 JSON 文件
-            if path.extension().is_some_and(|ext| ext == "json") {
+            // 只删除备份文件（.json / .json.gz）
+            if path.is_file() && crate::directories::is_account_backup_file(&path) {
                 fs::remove_file(&path)
                     .map_err(|e| format!("删除文件 {} 失败: {}", path.display(), e))?;
                 deleted_count += 1;
             }
         }
+        crate::antigravity::backup_format::remove_unreferenced_blobs();
 
         Ok(format!(
             "已清空所有用户备份，共删除 {} 个文件",
@@ -181,6 +276,223 @@ pub async fn clear_all_backups(state: State<'_, crate::AppState>) -> Result<Stri
     }
 }
 
+/// 清理旧备份：保留最新的 keep_latest 个，其余移动到 pruned/ 子目录
+#[tauri::command]
+pub async fn prune_account_backups(
+    keep_latest: usize,
+) -> Result<crate::antigravity::prune::PruneResult, String> {
+    log_async_command!("prune_account_backups", async {
+        tokio::task::spawn_blocking(
+            move || -> Result<crate::antigravity::prune::PruneResult, String> {
+                crate::app_settings::ensure_not_read_only()?;
+                crate::antigravity::prune::prune_account_backups(keep_latest)
+            },
+        )
+        .await
+        .map_err(|e| format!("清理任务执行失败: {}", e))?
+    })
+}
+
+/// 同时校验的最大文件数
+const VERIFY_CONCURRENCY: usize = 8;
+
+/// 批量校验所有账户备份文件，返回每个备份的健康状况
+#[tauri::command]
+pub async fn verify_all_backups(
+) -> Result<Vec<crate::antigravity::backup_format::BackupVerification>, String> {
+    log_async_command!("verify_all_backups", async {
+        let accounts_dir = crate::directories::get_accounts_directory();
+        let entries =
+            fs::read_dir(&accounts_dir).map_err(|e| format!("读取备份目录失败: {}", e))?;
+        let paths: Vec<_> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && crate::directories::is_account_backup_file(path))
+            .collect();
+
+        let mut results = Vec::new();
+        for verification in
+            crate::utils::bounded_parallel::map_blocking(paths, VERIFY_CONCURRENCY, |path| {
+                crate::antigravity::backup_format::verify_backup_file(&path)
+            })
+            .await
+        {
+            match verification {
+                Ok(verification) => results.push(verification),
+                Err(e) => {
+                    tracing::error!(target: "backup::verify", error = %e, "校验任务失败");
+                }
+            }
+        }
+        results.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(results)
+    })
+}
+
+/// 检测并修复残缺或旧格式的账户备份，返回执行的修复项
+#[tauri::command]
+pub async fn repair_backup(
+    account_name: String,
+) -> Result<crate::antigravity::backup_format::BackupRepair, String> {
+    log_async_command!("repair_backup", async {
+        crate::app_settings::ensure_not_read_only()?;
+
+        let account_file = crate::directories::get_account_file(&account_name)?;
+        if !account_file.exists() {
+            return Err(format!("账户备份不存在: {}", account_name));
+        }
+        crate::antigravity::backup_format::repair_backup_file(&account_file)
+    })
+}
+
+/// 保存 Antigravity 数据库的完整快照（物理副本）到指定路径
+#[tauri::command]
+pub async fn snapshot_antigravity_db(
+    dest_path: String,
+) -> Result<crate::antigravity::snapshot::DbSnapshot, String> {
+    log_async_command!("snapshot_antigravity_db", async {
+        tokio::task::spawn_blocking(
+            move || -> Result<crate::antigravity::snapshot::DbSnapshot, String> {
+                crate::antigravity::snapshot::snapshot_db(std::path::Path::new(&dest_path))
+            },
+        )
+        .await
+        .map_err(|e| format!("快照任务执行失败: {}", e))?
+    })
+}
+
+/// 用快照文件恢复 Antigravity 数据库（需先关闭 Antigravity）
+#[tauri::command]
+pub async fn restore_antigravity_db_snapshot(src_path: String) -> Result<String, String> {
+    log_async_command!("restore_antigravity_db_snapshot", async {
+        tokio::task::spawn_blocking(move || -> Result<String, String> {
+            crate::app_settings::ensure_not_read_only()?;
+            let _guard = crate::antigravity::account::try_begin_account_operation()?;
+            crate::antigravity::snapshot::restore_db_snapshot(std::path::Path::new(&src_path))
+        })
+        .await
+        .map_err(|e| format!("恢复任务执行失败: {}", e))?
+    })
+}
+
+/// 获取最近一次旧账户目录迁移的报告
+#[tauri::command]
+pub async fn get_migration_report() -> Result<Option<crate::directories::MigrationReport>, String> {
+    Ok(crate::directories::get_last_migration_report())
+}
+
+/// 列出旧账户迁移后遗留的 .bak 文件及内容摘要
+#[tauri::command]
+pub async fn list_legacy_backups() -> Result<Vec<crate::directories::LegacyBackup>, String> {
+    Ok(crate::directories::list_legacy_backups())
+}
+
+/// 将一个迁移遗留的 .bak 文件重新导入为当前配置档下的普通备份
+#[tauri::command]
+pub async fn restore_legacy_backup(name: String) -> Result<String, String> {
+    log_async_command!("restore_legacy_backup", async {
+        crate::app_settings::ensure_not_read_only()?;
+        crate::directories::restore_legacy_backup(&name)
+    })
+}
+
+/// 删除所有迁移遗留的 .bak 文件，必须传入 `confirm: true`
+#[tauri::command]
+pub async fn cleanup_legacy_backups(confirm: bool) -> Result<usize, String> {
+    log_async_command!("cleanup_legacy_backups", async {
+        if !confirm {
+            return Err("删除后无法恢复，请确认后再执行（confirm = true）".to_string());
+        }
+        crate::app_settings::ensure_not_read_only()?;
+        crate::directories::cleanup_legacy_backups()
+    })
+}
+
+/// 获取当前激活的配置档名称
+#[tauri::command]
+pub async fn get_active_profile() -> Result<String, String> {
+    Ok(crate::directories::get_active_profile())
+}
+
+/// 切换激活的配置档，之后所有账户命令都读写该配置档的备份目录
+#[tauri::command]
+pub async fn set_active_profile(app: tauri::AppHandle, name: String) -> Result<String, String> {
+    log_async_command!("set_active_profile", async {
+        // 账户操作进行中切换配置档会导致备份写入错误的目录
+        let _guard = crate::antigravity::account::try_begin_account_operation()?;
+        let name = crate::directories::set_active_profile(&name)?;
+
+        // 改为监听新配置档的账户目录
+        if let Err(e) = app
+            .state::<crate::accounts_watcher::AccountsWatcher>()
+            .start(app.clone())
+        {
+            tracing::warn!(error = %e, "切换配置档后重新监听账户目录失败");
+        }
+
+        Ok(name)
+    })
+}
+
+/// 修改 default 配置档的账户备份目录（如放到同步盘），返回生效后的目录
+///
+/// `path` 为空时恢复默认位置；`move_existing` 为 true 时把原目录中的备份移动到新目录
+#[tauri::command]
+pub async fn set_accounts_directory(
+    app: tauri::AppHandle,
+    path: String,
+    move_existing: Option<bool>,
+) -> Result<String, String> {
+    log_async_command!("set_accounts_directory", async {
+        let move_existing = move_existing.unwrap_or(false);
+        if move_existing {
+            crate::app_settings::ensure_not_read_only()?;
+        }
+        // 账户操作进行中修改目录会导致备份写入错误的位置
+        let _guard = crate::antigravity::account::try_begin_account_operation()?;
+
+        let old_dir = crate::directories::get_default_accounts_directory();
+        let new_override = if path.trim().is_empty() {
+            None
+        } else {
+            Some(crate::directories::prepare_accounts_directory(&path)?)
+        };
+        let new_dir = new_override
+            .clone()
+            .unwrap_or_else(crate::directories::get_builtin_accounts_directory);
+
+        let same_dir = fs::canonicalize(&old_dir).ok() == fs::canonicalize(&new_dir).ok();
+        if move_existing && !same_dir {
+            fs::create_dir_all(&new_dir).map_err(|e| format!("创建账户目录失败: {}", e))?;
+            crate::directories::move_accounts_directory_contents(&old_dir, &new_dir)?;
+        }
+
+        let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+        settings_manager.update_settings(|settings| {
+            settings.accounts_directory = new_override.map(|dir| dir.to_string_lossy().to_string());
+        })?;
+
+        // 改为监听新的账户目录
+        if let Err(e) = app
+            .state::<crate::accounts_watcher::AccountsWatcher>()
+            .start(app.clone())
+        {
+            tracing::warn!(error = %e, "修改账户目录后重新监听失败");
+        }
+
+        Ok(crate::directories::get_default_accounts_directory()
+            .to_string_lossy()
+            .to_string())
+    })
+}
+
+/// 列出所有配置档（default 始终在首位）
+#[tauri::command]
+pub async fn list_profiles() -> Result<Vec<String>, String> {
+    Ok(crate::directories::list_profiles())
+}
+
 /// 加密配置数据（用于账户导出）
 #[tauri::command]
 pub async fn encrypt_config_data(json_data: String, password: String) -> Result<String, String> {
@@ -243,35 +555,45 @@ pub async fn decrypt_config_data(
 #[tauri::command]
 pub async fn sign_in_new_antigravity_account() -> Result<String, String> {
     println!("🔄 开始执行 sign_in_new_antigravity_account 命令");
+    crate::app_settings::ensure_not_read_only()?;
+    let _operation = crate::antigravity::account::try_begin_account_operation()?;
 
     // 1. 关闭进程 (如果存在)
     println!("🛑 步骤1: 检查并关闭 Antigravity 进程");
-    let kill_result = match crate::platform::kill_antigravity_processes() {
-        Ok(result) => {
-            if result.contains("not found") || result.contains("未找到") {
-                println!("ℹ️ Antigravity 进程未运行，跳过关闭步骤");
-                "Antigravity 进程未运行".to_string()
-            } else {
-                println!("✅ 进程关闭结果: {}", result);
-                result
+    let kill_result = timed_step("kill", async {
+        match crate::platform::kill_antigravity_processes() {
+            Ok(result) => {
+                if result.contains("not found") || result.contains("未找到") {
+                    println!("ℹ️ Antigravity 进程未运行，跳过关闭步骤");
+                    Ok("Antigravity 进程未运行".to_string())
+                } else {
+                    println!("✅ 进程关闭结果: {}", result);
+                    Ok(result)
+                }
             }
-        }
-        Err(e) => {
-            if e.contains("not found") || e.contains("未找到") {
-                println!("ℹ️ Antigravity 进程未运行，跳过关闭步骤");
-                "Antigravity 进程未运行".to_string()
-            } else {
-                return Err(format!("关闭进程时发生错误: {}", e));
+            Err(e) => {
+                if e.contains("not found") || e.contains("未找到") {
+                    println!("ℹ️ Antigravity 进程未运行，跳过关闭步骤");
+                    Ok("Antigravity 进程未运行".to_string())
+                } else {
+                    Err(format!("关闭进程时发生错误: {}", e))
+                }
             }
         }
-    };
+    })
+    .await?;
 
     // 等待500ms确保进程完全关闭（缩短等待时间避免前端超时）
     tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
 
     // 2. 备份当前账户信息（直接调用 save_antigravity_current_account）
     println!("💾 步骤2: 调用 save_antigravity_current_account 备份当前账户信息");
-    let backup_info = match crate::commands::save_antigravity_current_account().await {
+    let backup_info = match timed_step(
+        "backup",
+        crate::commands::save_antigravity_current_account(),
+    )
+    .await
+    {
         Ok(msg) => {
             println!("✅ 备份完成: {}", msg);
             Some(msg)
@@ -284,9 +606,14 @@ pub async fn sign_in_new_antigravity_account() -> Result<String, String> {
 
     // 3. 清除 Antigravity 所有数据 (彻底注销)
     println!("🗑️ 步骤3: 清除所有 Antigravity 数据 (彻底注销)");
-    match crate::antigravity::cleanup::clear_all_antigravity_data().await {
+    match timed_step(
+        "clear",
+        crate::antigravity::cleanup::clear_all_antigravity_data(),
+    )
+    .await
+    {
         Ok(result) => {
-            println!("✅ 清除完成: {}", result);
+            println!("✅ 清除完成: {}", result.message);
         }
         Err(e) => {
             // 清除失败可能是因为数据库本来就是空的，这是正常情况
@@ -299,7 +626,10 @@ pub async fn sign_in_new_antigravity_account() -> Result<String, String> {
 
     // 4. 重新启动进程
     println!("🚀 步骤4: 重新启动 Antigravity");
-    let start_result = crate::antigravity::starter::start_antigravity();
+    let start_result = timed_step("start", async {
+        crate::antigravity::starter::start_antigravity(&[])
+    })
+    .await;
     let start_message = match start_result {
         Ok(result) => {
             println!("✅ 启动结果: {}", result);
@@ -323,6 +653,7 @@ pub async fn sign_in_new_antigravity_account() -> Result<String, String> {
         )
     };
     println!("🎉 所有操作完成: {}", final_message);
+    crate::antigravity::history::append_switch_history("sign_in", None, true);
 
     Ok(final_message)
 }
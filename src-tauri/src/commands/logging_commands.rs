@@ -22,6 +22,22 @@ pub async fn write_text_file(path: String, content: String) -> Result<String, St
     })
 }
 
+/// 获取当前日志过滤指令（EnvFilter 格式）
+#[tauri::command]
+pub async fn get_log_level() -> Result<String, String> {
+    crate::utils::tracing_config::current_log_directive()
+}
+
+/// 运行时修改日志过滤指令，如 `info,account::switch=debug`
+#[tauri::command]
+pub async fn set_log_level(directive: String) -> Result<String, String> {
+    crate::log_async_command!("set_log_level", async {
+        crate::utils::tracing_config::set_log_directive(&directive)?;
+        tracing::info!(target: "app::logging", directive = %directive, "日志级别已更新");
+        Ok(format!("日志级别已更新为: {}", directive))
+    })
+}
+
 /// 写入前端日志
 /// 将前端日志条目写入到后端日志系统
 #[tauri::command]
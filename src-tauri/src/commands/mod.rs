@@ -35,3 +35,4 @@ pub use platform_commands::*;
 pub use process_commands::*;
 pub use settings_commands::*;
 pub use tray_commands::*;
+pub use window_commands::*;
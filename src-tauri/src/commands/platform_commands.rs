@@ -2,6 +2,7 @@
 //! 负责获取平台信息、安装位置验证等跨平台操作
 
 use serde_json::Value;
+use tauri::Manager;
 
 /// 获取平台信息
 #[tauri::command]
@@ -25,6 +26,52 @@ pub async fn get_platform_info() -> Result<Value, String> {
     }))
 }
 
+/// 获取诊断信息（平台、路径、运行状态、版本、备份数量等），可直接附在问题反馈中
+///
+/// 结果经过敏感字段脱敏
+#[tauri::command]
+pub async fn get_diagnostics(app: tauri::AppHandle) -> Result<Value, String> {
+    use tauri::Manager;
+
+    crate::log_async_command!("get_diagnostics", async {
+        let platform = get_platform_info().await?;
+        let launch_check = crate::antigravity::starter::can_start_antigravity();
+        let db_path = crate::platform::TargetApp::Antigravity.db_path();
+
+        let accounts_dir = crate::directories::get_accounts_directory();
+        let backup_count = std::fs::read_dir(&accounts_dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter(|e| crate::directories::is_account_backup_file(&e.path()))
+                    .count()
+            })
+            .unwrap_or(0);
+
+        let settings = app
+            .state::<crate::app_settings::AppSettingsManager>()
+            .get_settings();
+
+        let mut diagnostics = serde_json::json!({
+            "app_version": env!("CARGO_PKG_VERSION"),
+            "platform": platform,
+            "antigravity_running": crate::platform::is_antigravity_running(),
+            "db_path": db_path.as_ref().map(|p| p.to_string_lossy().to_string()),
+            "db_exists": db_path.is_some_and(|p| p.exists()),
+            "executable": launch_check,
+            "app_config_dir": crate::directories::get_config_directory().to_string_lossy(),
+            "log_dir": crate::directories::get_log_directory().to_string_lossy(),
+            "accounts_dir": accounts_dir.to_string_lossy(),
+            "backup_count": backup_count,
+            "log_level": crate::utils::tracing_config::current_log_directive().ok(),
+            "settings": settings,
+        });
+        crate::utils::log_sanitizer::redact_json_secrets(&mut diagnostics);
+
+        Ok(diagnostics)
+    })
+}
+
 /// 查找 Antigravity 安装位置
 #[tauri::command]
 pub async fn find_antigravity_installations() -> Result<Vec<String>, String> {
@@ -74,6 +121,78 @@ pub async fn detect_antigravity_installation() -> Result<serde_json::Value, Stri
     }))
 }
 
+/// 检测 Antigravity 状态数据库：未安装 / 已安装但未创建数据库 / 就绪
+///
+/// 账户命令因数据库不可用失败时，前端可据此提示安装 Antigravity 或先启动一次
+#[tauri::command]
+pub async fn get_antigravity_db_status() -> Result<crate::platform::AntigravityDbStatus, String> {
+    Ok(crate::platform::detect_antigravity_db())
+}
+
+/// 当前操作的 Antigravity 安装
+#[derive(Debug, serde::Serialize)]
+pub struct ActiveInstall {
+    /// 所有操作使用的 state.vscdb 路径，未检测到安装时为 None
+    pub db_path: Option<String>,
+    /// 是否为设置中固定的安装（否则为自动检测结果）
+    pub pinned: bool,
+}
+
+/// 获取当前操作的 Antigravity 安装：固定的安装，或自动检测的默认安装
+#[tauri::command]
+pub async fn get_active_install() -> Result<ActiveInstall, String> {
+    let pinned = crate::platform::pinned_antigravity_db_path();
+    let db_path = pinned
+        .clone()
+        .or_else(crate::platform::get_antigravity_db_path);
+    Ok(ActiveInstall {
+        db_path: db_path.map(|p| p.to_string_lossy().to_string()),
+        pinned: pinned.is_some(),
+    })
+}
+
+/// 固定所有操作使用的 Antigravity 安装（state.vscdb 路径），传空字符串恢复自动检测
+///
+/// 路径必须是检测到的安装之一且数据库文件存在
+#[tauri::command]
+pub async fn set_active_install(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<ActiveInstall, String> {
+    crate::log_async_command!("set_active_install", async {
+        // 账户操作进行中切换目标安装会导致读写不同的数据库
+        let _guard = crate::antigravity::account::try_begin_account_operation()?;
+
+        let pinned = if path.trim().is_empty() {
+            None
+        } else {
+            let requested = std::path::PathBuf::from(path.trim());
+            let canonical = std::fs::canonicalize(&requested)
+                .map_err(|e| format!("数据库文件不存在: {} ({})", requested.display(), e))?;
+            let detected = crate::platform::get_all_antigravity_db_paths()
+                .into_iter()
+                .any(|candidate| {
+                    std::fs::canonicalize(candidate).ok().as_ref() == Some(&canonical)
+                });
+            if !detected || !canonical.is_file() {
+                return Err(format!(
+                    "{} 不是检测到的 Antigravity 安装数据库",
+                    requested.display()
+                ));
+            }
+            Some(requested.to_string_lossy().to_string())
+        };
+
+        app.state::<crate::app_settings::AppSettingsManager>()
+            .update_settings(|settings| {
+                settings.active_install_db = pinned.clone();
+            })?;
+        tracing::info!(pinned = ?pinned, "已设置当前操作的 Antigravity 安装");
+
+        get_active_install().await
+    })
+}
+
 /// 检测 Antigravity 可执行文件
 #[tauri::command]
 pub async fn detect_antigravity_executable() -> Result<serde_json::Value, String> {
@@ -112,6 +231,53 @@ pub async fn detect_antigravity_executable() -> Result<serde_json::Value, String
     }))
 }
 
+/// 检查 Antigravity 是否可以启动（只做路径与文件校验，不实际启动）
+#[tauri::command]
+pub async fn can_start_antigravity() -> Result<crate::antigravity::starter::LaunchCheck, String> {
+    Ok(crate::antigravity::starter::can_start_antigravity())
+}
+
+/// 获取 Prometheus 文本格式的运行指标（备份数量、运行状态、切换次数等）
+#[tauri::command]
+pub async fn get_metrics_text() -> Result<String, String> {
+    tokio::task::spawn_blocking(crate::metrics::render_metrics_text)
+        .await
+        .map_err(|e| format!("生成指标失败: {}", e))
+}
+
+/// 检测已安装的 Antigravity 版本号（无法确定时 version 为 null）
+#[tauri::command]
+pub async fn detect_antigravity_version(
+) -> Result<crate::antigravity::version::AntigravityVersion, String> {
+    Ok(crate::antigravity::version::detect_antigravity_version())
+}
+
+/// 检测目标应用（Antigravity / Windsurf）的安装情况
+#[tauri::command]
+pub async fn detect_target_apps() -> Result<Vec<serde_json::Value>, String> {
+    use crate::platform::TargetApp;
+
+    let apps = [TargetApp::Antigravity, TargetApp::Windsurf]
+        .into_iter()
+        .map(|target| {
+            let db_path = target.db_path();
+            let executable = target
+                .executable_paths()
+                .into_iter()
+                .find(|path| path.exists());
+            serde_json::json!({
+                "target": target,
+                "data_dir": target.data_dir().map(|p| p.to_string_lossy().to_string()),
+                "db_path": db_path.as_ref().map(|p| p.to_string_lossy().to_string()),
+                "db_exists": db_path.is_some_and(|p| p.exists()),
+                "executable": executable.map(|p| p.to_string_lossy().to_string()),
+            })
+        })
+        .collect();
+
+    Ok(apps)
+}
+
 /// 保存用户自定义的 Antigravity 可执行文件路径
 #[tauri::command]
 pub async fn save_antigravity_executable(path: String) -> Result<String, String> {
@@ -126,12 +292,27 @@ pub async fn save_antigravity_executable(path: String) -> Result<String, String>
     Ok(format!("已保存 Antigravity 可执行文件路径: {}", path))
 }
 
+/// 设置额外的 Antigravity 可执行文件搜索路径（整体替换），返回保存后的列表
+#[tauri::command]
+pub async fn save_extra_executable_paths(paths: Vec<String>) -> Result<Vec<String>, String> {
+    crate::antigravity::path_config::save_extra_executable_paths(paths)
+}
+
+/// 重新检测 Antigravity 路径：清除失效的自定义可执行文件路径后重新自动检测
+#[tauri::command]
+pub async fn revalidate_paths() -> Result<crate::antigravity::path_config::RevalidatedPaths, String>
+{
+    crate::antigravity::path_config::revalidate_paths()
+}
+
 /// 获取当前配置的路径
 #[tauri::command]
 pub async fn get_current_paths() -> Result<serde_json::Value, String> {
     let exec_path = crate::antigravity::path_config::get_custom_executable_path().unwrap_or(None);
+    let extra_paths = crate::antigravity::path_config::get_extra_executable_paths();
 
     Ok(serde_json::json!({
-        "executablePath": exec_path
+        "executablePath": exec_path,
+        "extraExecutablePaths": extra_paths
     }))
 }
@@ -3,3 +3,86 @@
 pub async fn is_antigravity_running() -> bool {
     crate::platform::is_antigravity_running()
 }
+
+/// 列出被识别为 Antigravity 的进程及匹配依据（用于排查误判/漏判）
+#[tauri::command]
+pub async fn list_antigravity_processes(
+) -> Result<Vec<crate::platform::AntigravityProcessInfo>, String> {
+    tokio::task::spawn_blocking(crate::platform::list_antigravity_processes)
+        .await
+        .map_err(|e| format!("枚举进程失败: {}", e))
+}
+
+/// 终止残留的 Antigravity 进程（如崩溃后遗留、占用数据库锁的 Helper 进程）
+///
+/// `keep_newest` 为 true 时保留最新启动的实例及其子进程，为 false 时终止全部
+#[tauri::command]
+pub async fn kill_stale_antigravity(
+    keep_newest: bool,
+) -> Result<crate::platform::KillStaleResult, String> {
+    crate::log_async_command!("kill_stale_antigravity", async {
+        tokio::task::spawn_blocking(move || {
+            crate::platform::kill_stale_antigravity_processes(keep_newest)
+        })
+        .await
+        .map_err(|e| format!("清理残留进程失败: {}", e))
+    })
+}
+
+/// 启动 Antigravity，可附带启动参数
+#[tauri::command]
+pub async fn start_antigravity(args: Option<Vec<String>>) -> Result<String, String> {
+    crate::log_async_command!("start_antigravity", async {
+        crate::antigravity::starter::start_antigravity(&args.unwrap_or_default())
+    })
+}
+
+/// Antigravity 启动就绪检查结果
+#[derive(Debug, serde::Serialize)]
+pub struct AntigravityReadiness {
+    /// 进程、数据库、日志均已就绪
+    pub ready: bool,
+    /// 进程是否在运行
+    pub process: bool,
+    /// 状态数据库是否存在
+    pub db: bool,
+    /// 是否已写入日志文件
+    pub log: bool,
+}
+
+/// 就绪检查的轮询间隔
+const READY_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// 启动 Antigravity 后等待其就绪：进程运行、状态数据库存在且已写入日志
+///
+/// 就绪后立即返回；超时时返回最后一次检查的各项状态（`ready` 为 false），不视为错误
+#[tauri::command]
+pub async fn wait_for_antigravity_ready(timeout_ms: u64) -> Result<AntigravityReadiness, String> {
+    crate::log_async_command!("wait_for_antigravity_ready", async {
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_millis(timeout_ms);
+
+        loop {
+            let readiness = tokio::task::spawn_blocking(|| {
+                let process = crate::platform::is_antigravity_running();
+                let db = matches!(
+                    crate::platform::detect_antigravity_db(),
+                    crate::platform::AntigravityDbStatus::Ready(_)
+                );
+                let log = crate::platform::has_antigravity_log_file();
+                AntigravityReadiness {
+                    ready: process && db && log,
+                    process,
+                    db,
+                    log,
+                }
+            })
+            .await
+            .map_err(|e| format!("检查 Antigravity 状态失败: {}", e))?;
+
+            if readiness.ready || tokio::time::Instant::now() >= deadline {
+                return Ok(readiness);
+            }
+            tokio::time::sleep(READY_POLL_INTERVAL).await;
+        }
+    })
+}
@@ -2,6 +2,7 @@
 //! 负责应用程序配置的管理和存储，使用 State 模式
 
 use tauri::{AppHandle, Manager};
+use tauri_plugin_autostart::ManagerExt;
 
 /// 获取静默启动状态
 #[tauri::command]
@@ -27,6 +28,309 @@ pub async fn save_silent_start_state(app: AppHandle, enabled: bool) -> Result<bo
     })
 }
 
+/// 保存账户变化自动备份状态
+#[tauri::command]
+pub async fn save_auto_backup_on_change_state(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<bool, String> {
+    crate::log_async_command!("save_auto_backup_on_change_state", async {
+        let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+
+        settings_manager.update_settings(|settings| {
+            settings.auto_backup_on_change = enabled;
+        })?;
+
+        Ok(enabled)
+    })
+}
+
+/// 保存 Antigravity 进程监控设置，并立即启动或停止监控
+#[tauri::command]
+pub async fn save_process_watch_state(app: AppHandle, enabled: bool) -> Result<bool, String> {
+    crate::log_async_command!("save_process_watch_state", async {
+        let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+        settings_manager.update_settings(|settings| {
+            settings.process_watch_enabled = enabled;
+        })?;
+
+        let process_monitor = app.state::<crate::process_monitor::ProcessMonitor>();
+        if enabled {
+            process_monitor.start(app.clone());
+        } else {
+            process_monitor.stop();
+        }
+
+        Ok(enabled)
+    })
+}
+
+/// 保存启动时自动清理备份的设置
+#[tauri::command]
+pub async fn save_auto_prune_settings(
+    app: AppHandle,
+    enabled: bool,
+    keep_latest: usize,
+) -> Result<bool, String> {
+    crate::log_async_command!("save_auto_prune_settings", async {
+        let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+
+        settings_manager.update_settings(|settings| {
+            settings.auto_prune_enabled = enabled;
+            settings.auto_prune_keep_latest = keep_latest;
+        })?;
+
+        Ok(enabled)
+    })
+}
+
+/// 获取 HTTP 请求超时时间（毫秒）
+#[tauri::command]
+pub async fn get_http_timeout(app: AppHandle) -> Result<u64, String> {
+    let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+    Ok(settings_manager.get_settings().http_timeout_ms)
+}
+
+/// 设置 HTTP 请求超时时间（毫秒），允许范围 500–60000
+#[tauri::command]
+pub async fn set_http_timeout(app: AppHandle, ms: u64) -> Result<u64, String> {
+    crate::log_async_command!("set_http_timeout", async {
+        if !crate::app_settings::HTTP_TIMEOUT_RANGE_MS.contains(&ms) {
+            return Err(format!(
+                "HTTP 超时必须在 {}–{} 毫秒之间",
+                crate::app_settings::HTTP_TIMEOUT_RANGE_MS.start(),
+                crate::app_settings::HTTP_TIMEOUT_RANGE_MS.end()
+            ));
+        }
+
+        let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+        settings_manager.update_settings(|settings| {
+            settings.http_timeout_ms = ms;
+        })?;
+
+        Ok(ms)
+    })
+}
+
+/// 设置数据库变化事件的防抖间隔（毫秒），允许范围 0–60000，0 表示不防抖
+#[tauri::command]
+pub async fn set_db_monitor_debounce(app: AppHandle, ms: u64) -> Result<u64, String> {
+    crate::log_async_command!("set_db_monitor_debounce", async {
+        if !crate::app_settings::DB_MONITOR_DEBOUNCE_RANGE_MS.contains(&ms) {
+            return Err(format!(
+                "防抖间隔必须在 {}–{} 毫秒之间",
+                crate::app_settings::DB_MONITOR_DEBOUNCE_RANGE_MS.start(),
+                crate::app_settings::DB_MONITOR_DEBOUNCE_RANGE_MS.end()
+            ));
+        }
+
+        let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+        settings_manager.update_settings(|settings| {
+            settings.db_monitor_debounce_ms = ms;
+        })?;
+
+        Ok(ms)
+    })
+}
+
+/// 保存增量备份设置（只影响之后的备份，已有备份保持原格式）
+#[tauri::command]
+pub async fn save_delta_backups_state(app: AppHandle, enabled: bool) -> Result<bool, String> {
+    crate::log_async_command!("save_delta_backups_state", async {
+        let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+        settings_manager.update_settings(|settings| {
+            settings.delta_backups_enabled = enabled;
+        })?;
+
+        Ok(enabled)
+    })
+}
+
+/// 保存压缩备份设置（只影响之后的备份，读取时两种格式都支持）
+#[tauri::command]
+pub async fn save_compress_backups_state(app: AppHandle, enabled: bool) -> Result<bool, String> {
+    crate::log_async_command!("save_compress_backups_state", async {
+        let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+        settings_manager.update_settings(|settings| {
+            settings.compress_backups = enabled;
+        })?;
+
+        Ok(enabled)
+    })
+}
+
+/// 保存启动时重新检测 Antigravity 路径的设置
+#[tauri::command]
+pub async fn save_revalidate_paths_on_startup_state(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<bool, String> {
+    crate::log_async_command!("save_revalidate_paths_on_startup_state", async {
+        let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+        settings_manager.update_settings(|settings| {
+            settings.revalidate_paths_on_startup = enabled;
+        })?;
+
+        Ok(enabled)
+    })
+}
+
+/// 设置备份过期提示阈值（天），0 表示关闭过期提示
+#[tauri::command]
+pub async fn set_stale_backup_days(app: AppHandle, days: u64) -> Result<u64, String> {
+    crate::log_async_command!("set_stale_backup_days", async {
+        let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+        settings_manager.update_settings(|settings| {
+            settings.stale_backup_days = days;
+        })?;
+
+        Ok(days)
+    })
+}
+
+/// 导出应用自身的配置（设置、路径配置、窗口状态等，不含账户备份）到指定文件
+#[tauri::command]
+pub async fn export_agent_config(dest_path: String) -> Result<Vec<String>, String> {
+    crate::log_async_command!("export_agent_config", async {
+        crate::agent_config::export_agent_config(std::path::Path::new(&dest_path))
+    })
+}
+
+/// 从配置归档导入应用配置，覆盖前会把现有配置备份为 .bak
+///
+/// 账户目录和当前使用的安装保留原值；导入后同步自启动、进程监控和账户目录监听
+#[tauri::command]
+pub async fn import_agent_config(
+    app: AppHandle,
+    src_path: String,
+) -> Result<crate::agent_config::AgentConfigImportResult, String> {
+    crate::log_async_command!("import_agent_config", async {
+        crate::app_settings::ensure_not_read_only()?;
+
+        let archive =
+            crate::agent_config::read_agent_config_archive(std::path::Path::new(&src_path))?;
+        let mut result = crate::agent_config::AgentConfigImportResult {
+            imported: Vec::new(),
+            backed_up: Vec::new(),
+        };
+
+        crate::agent_config::write_config_files(&archive, &mut result)?;
+
+        // 应用设置通过设置管理器写入，使运行中的设置立即生效
+        if let Some(value) = archive.files.get("app_settings.json") {
+            let settings_file = crate::directories::get_app_settings_file();
+            if crate::agent_config::backup_existing_config(&settings_file)? {
+                result.backed_up.push("app_settings.json".to_string());
+            }
+            let imported: crate::app_settings::AppSettings = serde_json::from_value(value.clone())
+                .map_err(|e| format!("配置文件 app_settings.json 格式无效: {}", e))?;
+            let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+            settings_manager.update_settings(|settings| {
+                // 账户目录和当前安装需要校验和迁移，只能走专用命令，导入时保留当前值
+                let accounts_directory = settings.accounts_directory.take();
+                let active_install_db = settings.active_install_db.take();
+                *settings = imported;
+                settings.accounts_directory = accounts_directory;
+                settings.active_install_db = active_install_db;
+            })?;
+            result.imported.push("app_settings.json".to_string());
+        }
+
+        let settings = app
+            .state::<crate::app_settings::AppSettingsManager>()
+            .get_settings();
+        apply_runtime_settings(&app, &settings);
+
+        Ok(result)
+    })
+}
+
+/// 让导入的设置立即生效：同步系统自启动、进程监控，并重新监听账户目录
+fn apply_runtime_settings(app: &AppHandle, settings: &crate::app_settings::AppSettings) {
+    let autolaunch = app.autolaunch();
+    let autostart = if settings.autostart_enabled {
+        autolaunch.enable()
+    } else {
+        autolaunch.disable()
+    };
+    if let Err(e) = autostart {
+        tracing::warn!(error = %e, "导入配置后同步自启动失败");
+    }
+
+    let process_monitor = app.state::<crate::process_monitor::ProcessMonitor>();
+    if settings.process_watch_enabled {
+        process_monitor.start(app.clone());
+    } else {
+        process_monitor.stop();
+    }
+
+    // 导入的配置档等文件可能改变了当前账户目录
+    if let Err(e) = app
+        .state::<crate::accounts_watcher::AccountsWatcher>()
+        .start(app.clone())
+    {
+        tracing::warn!(error = %e, "导入配置后重新监听账户目录失败");
+    }
+}
+
+/// 获取系统自启动的实际状态
+#[tauri::command]
+pub async fn get_autostart(app: AppHandle) -> Result<bool, String> {
+    crate::log_async_command!("get_autostart", async {
+        app.autolaunch()
+            .is_enabled()
+            .map_err(|e| format!("读取自启动状态失败: {}", e))
+    })
+}
+
+/// 设置系统自启动（Windows 注册表 Run 键 / macOS LaunchAgent / Linux XDG autostart），返回实际状态
+#[tauri::command]
+pub async fn set_autostart(app: AppHandle, enabled: bool) -> Result<bool, String> {
+    crate::log_async_command!("set_autostart", async {
+        let autolaunch = app.autolaunch();
+        if enabled {
+            autolaunch.enable()
+        } else {
+            autolaunch.disable()
+        }
+        .map_err(|e| format!("设置自启动失败: {}", e))?;
+
+        let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+        settings_manager.update_settings(|settings| {
+            settings.autostart_enabled = enabled;
+        })?;
+
+        autolaunch
+            .is_enabled()
+            .map_err(|e| format!("读取自启动状态失败: {}", e))
+    })
+}
+
+/// 获取单个应用设置项
+#[tauri::command]
+pub async fn get_app_setting(app: AppHandle, key: String) -> Result<serde_json::Value, String> {
+    crate::log_async_command!("get_app_setting", async {
+        let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+        settings_manager.get_settings().get_field(&key)
+    })
+}
+
+/// 设置单个应用设置项，返回验证后的实际值
+#[tauri::command]
+pub async fn set_app_setting(
+    app: AppHandle,
+    key: String,
+    value: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    crate::log_async_command!("set_app_setting", async {
+        let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+
+        settings_manager.try_update_settings(|settings| settings.set_field(&key, value))?;
+
+        settings_manager.get_settings().get_field(&key)
+    })
+}
+
 /// 获取所有应用设置
 #[tauri::command]
 pub async fn get_all_settings(app: AppHandle) -> Result<serde_json::Value, String> {
@@ -36,7 +340,22 @@ pub async fn get_all_settings(app: AppHandle) -> Result<serde_json::Value, Strin
 
         Ok(serde_json::json!({
             "system_tray_enabled": settings.system_tray_enabled,
-            "silent_start_enabled": settings.silent_start_enabled
+            "silent_start_enabled": settings.silent_start_enabled,
+            "auto_backup_on_change": settings.auto_backup_on_change,
+            "auto_prune_enabled": settings.auto_prune_enabled,
+            "auto_prune_keep_latest": settings.auto_prune_keep_latest,
+            "autostart_enabled": settings.autostart_enabled,
+            "json_log_level": settings.json_log_level,
+            "log_retention_days": settings.log_retention_days,
+            "http_timeout_ms": settings.http_timeout_ms,
+            "stale_backup_days": settings.stale_backup_days,
+            "delta_backups_enabled": settings.delta_backups_enabled,
+            "compress_backups": settings.compress_backups,
+            "accounts_directory": settings.accounts_directory,
+            "db_monitor_debounce_ms": settings.db_monitor_debounce_ms,
+            "process_watch_enabled": settings.process_watch_enabled,
+            "revalidate_paths_on_startup": settings.revalidate_paths_on_startup,
+            "active_install_db": settings.active_install_db
         }))
     })
 }
@@ -3,3 +3,10 @@
 //!
 //! 注意：窗口状态管理已迁移到 window_event_handler.rs 中的自动处理
 //! 此模块保留以备将来需要手动窗口状态管理时使用
+
+/// 取消尚未执行的静默启动隐藏（前端检测到用户交互时调用）
+#[tauri::command]
+pub async fn cancel_silent_start_hide() -> Result<(), String> {
+    crate::window::cancel_silent_start_hide();
+    Ok(())
+}
@@ -3,7 +3,7 @@
 use serde::Serialize;
 use serde_json::Value;
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 use tokio::sync::Mutex;
 use tokio::time::{interval, Duration};
 use tracing::{error, info, warn};
@@ -16,10 +16,20 @@ pub struct DataDiff {
     pub summary: String,
 }
 
+/// 触发 database-changed 事件的数据库键，其余键的变化只更新基线、不推送
+const WATCHED_KEYS: &[&str] = &[
+    crate::constants::database::AGENT_STATE,
+    crate::constants::database::AUTH_STATUS,
+];
+
+/// 等待连续写入稳定的最长总时间，避免数据持续变化或防抖间隔较大时长时间阻塞轮询
+const MAX_SETTLE_DURATION: Duration = Duration::from_secs(3);
+
 // 数据库监控器
 pub struct DatabaseMonitor {
     app_handle: AppHandle,
     last_data: Arc<Mutex<Option<Value>>>,
+    last_email: Arc<Mutex<Option<String>>>,
     is_running: Arc<Mutex<bool>>,
 }
 
@@ -29,6 +39,7 @@ impl DatabaseMonitor {
         Self {
             app_handle,
             last_data: Arc::new(Mutex::new(None)),
+            last_email: Arc::new(Mutex::new(None)),
             is_running: Arc::new(Mutex::new(false)),
         }
     }
@@ -38,6 +49,7 @@ impl DatabaseMonitor {
         info!("🔧 启动数据库自动监控（简化版）");
 
         let last_data = self.last_data.clone();
+        let last_email = self.last_email.clone();
         let is_running = self.is_running.clone();
         let app_handle = self.app_handle.clone();
 
@@ -46,6 +58,7 @@ impl DatabaseMonitor {
 
         tokio::spawn(async move {
             let mut interval = interval(Duration::from_secs(3)); // 3秒间隔，更敏感
+            let mut last_emit: Option<tokio::time::Instant> = None;
 
             loop {
                 interval.tick().await;
@@ -60,13 +73,36 @@ impl DatabaseMonitor {
 
                 // 获取当前完整数据
                 match Self::get_complete_data().await {
-                    Ok(new_data) => {
-                        let mut last = last_data.lock().await;
+                    Ok(mut new_data) => {
+                        // 检查登录账户是否变化，必要时自动备份
+                        Self::check_account_change(&app_handle, &last_email, &new_data).await;
+
+                        // 取出基线后立即释放锁，等待数据稳定期间不持有锁
+                        let previous = last_data.lock().await.clone();
 
                         // 检查是否有数据变化
-                        if let Some(ref old_data) = *last {
-                            // 分析差异
-                            let diff = Self::analyze_diff(old_data, &new_data);
+                        if let Some(ref old_data) = previous {
+                            let debounce = Duration::from_millis(
+                                app_handle
+                                    .state::<crate::app_settings::AppSettingsManager>()
+                                    .get_settings()
+                                    .db_monitor_debounce_ms,
+                            );
+
+                            // 只关注账户相关的键
+                            let mut diff = Self::watched_diff(old_data, &new_data);
+                            if diff.has_changes {
+                                // 合并连续写入：等数据稳定后再推送
+                                new_data = Self::wait_until_settled(new_data, debounce).await;
+                                diff = Self::watched_diff(old_data, &new_data);
+                            }
+
+                            // 距上次推送不足防抖间隔时保留旧基线，下次轮询合并推送
+                            if diff.has_changes
+                                && last_emit.is_some_and(|emitted| emitted.elapsed() < debounce)
+                            {
+                                continue;
+                            }
 
                             if diff.has_changes {
                                 info!("📢 检测到数据库变化: {}", diff.summary);
@@ -84,10 +120,11 @@ impl DatabaseMonitor {
                                 } else {
                                     info!("✅ 数据库变化事件推送成功");
                                 }
+                                last_emit = Some(tokio::time::Instant::now());
                             }
                         }
 
-                        *last = Some(new_data);
+                        *last_data.lock().await = Some(new_data);
                     }
                     Err(e) => {
                         warn!("⚠️ 获取完整数据失败: {}", e);
@@ -105,31 +142,101 @@ impl DatabaseMonitor {
         *self.is_running.lock().await = false;
     }
 
+    /// 检测登录邮箱变化，并在启用自动备份时备份新账户
+    ///
+    /// 以 antigravityAuthStatus 存在作为已登录的依据（恢复账户时会删除该键，
+    /// 由 Antigravity 登录后重新写入）；邮箱则从 jetskiStateSync 解码，
+    /// 因为 antigravityAuthStatus 中没有可靠的邮箱字段，且备份文件也以该邮箱命名。
+    /// 切换、恢复等账户操作进行中时跳过，避免备份写到一半的状态
+    async fn check_account_change(
+        app_handle: &AppHandle,
+        last_email: &Mutex<Option<String>>,
+        new_data: &Value,
+    ) {
+        // 等账户操作完成后再比较，不记录中间状态
+        if crate::antigravity::account::is_account_operation_in_progress() {
+            return;
+        }
+
+        let logged_in = new_data
+            .get(crate::constants::database::AUTH_STATUS)
+            .is_some();
+        let current_email = new_data
+            .get(crate::constants::database::AGENT_STATE)
+            .and_then(|v| v.as_str())
+            .filter(|_| logged_in)
+            .and_then(|state| crate::antigravity::account::decode_jetski_state_email(state).ok());
+
+        let mut last = last_email.lock().await;
+        let previous = last.replace(current_email.clone().unwrap_or_default());
+
+        // 首次读取只记录当前邮箱，不触发备份
+        let Some(previous) = previous else {
+            return;
+        };
+        let Some(email) = current_email else {
+            return;
+        };
+        if email == previous {
+            return;
+        }
+        drop(last);
+
+        info!("👤 检测到登录账户变化");
+
+        let settings = app_handle
+            .state::<crate::app_settings::AppSettingsManager>()
+            .get_settings();
+        if !settings.auto_backup_on_change {
+            return;
+        }
+
+        // 备份期间持有账户操作锁，避免与刚开始的切换、恢复同时读写数据库
+        let Ok(_guard) = crate::antigravity::account::try_begin_account_operation() else {
+            // 恢复上次的邮箱，操作完成后的下一次轮询重新判断
+            *last_email.lock().await = Some(previous);
+            return;
+        };
+
+        let (success, message) = match crate::commands::save_antigravity_current_account().await {
+            Ok(msg) => {
+                info!("💾 账户变化后自动备份完成");
+                (true, msg)
+            }
+            Err(e) => {
+                warn!("⚠️ 账户变化后自动备份失败: {}", e);
+                (false, e)
+            }
+        };
+
+        let event_data = serde_json::json!({
+            "email": email,
+            "success": success,
+            "message": message
+        });
+        if let Err(e) = app_handle.emit("account-auto-backup", &event_data) {
+            error!("❌ 推送自动备份事件失败: {}", e);
+        }
+    }
+
     /// 获取完整数据库数据
+    ///
+    /// 数据库路径与账户操作共用 `detect_antigravity_db` 的解析结果（含自定义安装位置），
+    /// 数据库尚不存在时返回空对象
     async fn get_complete_data() -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
-        // 检测数据库路径
-        let db_path = if cfg!(windows) {
-            dirs::home_dir()
-                .unwrap_or_default()
-                .join("AppData")
-                .join("Roaming")
-                .join("Antigravity")
-                .join("User")
-                .join("globalStorage")
-                .join("state.vscdb")
-        } else {
-            dirs::config_dir()
-                .unwrap_or_default()
-                .join("Antigravity")
-                .join("User")
-                .join("globalStorage")
-                .join("state.vscdb")
-        };
+        tokio::task::spawn_blocking(Self::read_complete_data).await?
+    }
 
+    fn read_complete_data() -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
         let mut complete_data = serde_json::Map::new();
 
-        if db_path.exists() {
-            let conn = rusqlite::Connection::open(&db_path)?;
+        if let crate::platform::AntigravityDbStatus::Ready(db_path) =
+            crate::platform::detect_antigravity_db()
+        {
+            let conn = rusqlite::Connection::open_with_flags(
+                &db_path,
+                rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
+            )?;
 
             // 查询所有数据（完整的ItemTable）
             let mut stmt = conn.prepare("SELECT key, value FROM ItemTable ORDER BY key")?;
@@ -153,6 +260,54 @@ impl DatabaseMonitor {
         Ok(Value::Object(complete_data))
     }
 
+    /// 只保留 WATCHED_KEYS 变化的差异
+    fn watched_diff(old: &Value, new: &Value) -> DataDiff {
+        let mut changed_fields = Self::analyze_diff(old, new).changed_fields;
+        changed_fields.retain(|field| {
+            WATCHED_KEYS.iter().any(|key| {
+                field
+                    .strip_prefix(key)
+                    .is_some_and(|rest| rest.starts_with(':'))
+            })
+        });
+
+        let has_changes = !changed_fields.is_empty();
+        let summary = if has_changes {
+            format!("{} fields changed", changed_fields.len())
+        } else {
+            "No changes".to_string()
+        };
+
+        DataDiff {
+            has_changes,
+            changed_fields,
+            summary,
+        }
+    }
+
+    /// 每隔防抖间隔重新读取一次，直到关注的键不再变化（总等待时间不超过 MAX_SETTLE_DURATION）
+    async fn wait_until_settled(mut data: Value, debounce: Duration) -> Value {
+        if debounce.is_zero() {
+            return data;
+        }
+        let deadline = tokio::time::Instant::now() + MAX_SETTLE_DURATION;
+        loop {
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            if remaining.is_zero() {
+                return data;
+            }
+            tokio::time::sleep(debounce.min(remaining)).await;
+            match Self::get_complete_data().await {
+                Ok(next) if Self::watched_diff(&data, &next).has_changes => data = next,
+                Ok(next) => return next,
+                Err(e) => {
+                    warn!("⚠️ 防抖期间读取数据失败: {}", e);
+                    return data;
+                }
+            }
+        }
+    }
+
     /// 分析两个数据之间的差异
     fn analyze_diff(old: &Value, new: &Value) -> DataDiff {
         let mut changed_fields = Vec::new();
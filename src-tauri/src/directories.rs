@@ -1,18 +1,45 @@
 /// 目录获取模块
 /// 统一管理所有配置和数据目录路径
+use serde::{Deserialize, Serialize};
+use serde_json::{self, Value};
 use std::fs;
 use std::io;
-use std::path::PathBuf;
-use serde_json::{self, Value};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use tracing::{info, warn};
 
+/// 配置目录的基础目录（通常为用户主目录），首次解析后缓存
+static BASE_DIRECTORY: OnceLock<PathBuf> = OnceLock::new();
+
+/// 获取配置目录的基础目录
+///
+/// 优先使用用户主目录；在沙箱等找不到主目录的环境下，
+/// 依次退回到可执行文件所在目录和系统临时目录（降级模式），而不是直接崩溃
+fn base_directory() -> &'static Path {
+    BASE_DIRECTORY.get_or_init(|| {
+        if let Some(home) = dirs::home_dir() {
+            return home;
+        }
+
+        let fallback = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf))
+            .unwrap_or_else(std::env::temp_dir);
+        // 此时日志系统可能尚未初始化，同时输出到 stderr
+        eprintln!(
+            "警告：未找到用户主目录，配置将保存到 {}（降级模式）",
+            fallback.display()
+        );
+        warn!(target: "app::startup", "未找到用户主目录，使用降级目录: {}", fallback.display());
+        fallback
+    })
+}
+
 /// 获取应用主配置目录
 /// 所有配置、日志、数据都统一存放在用户主目录的 .antigravity-agent 下
 #[cfg(windows)]
 pub fn get_config_directory() -> PathBuf {
-    let config_dir = dirs::home_dir()
-        .expect("Home directory not found")
-        .join(".antigravity-agent");
+    let config_dir = base_directory().join(".antigravity-agent");
 
     // 确保目录存在
     if let Err(e) = fs::create_dir_all(&config_dir) {
@@ -25,9 +52,7 @@ pub fn get_config_directory() -> PathBuf {
 /// 获取应用主配置目录
 #[cfg(not(windows))]
 pub fn get_config_directory() -> PathBuf {
-    let config_dir = dirs::home_dir()
-        .expect("Home directory not found")
-        .join(".antigravity-agent");
+    let config_dir = base_directory().join(".antigravity-agent");
 
     // 确保目录存在
     if let Err(e) = fs::create_dir_all(&config_dir) {
@@ -49,9 +74,103 @@ pub fn get_log_directory() -> PathBuf {
     get_config_directory().join("logs")
 }
 
-/// 获取账户备份目录
-pub fn get_accounts_directory() -> PathBuf {
-    let accounts_dir = get_config_directory().join("antigravity-accounts");
+/// 默认配置档名称，对应旧版（无配置档）的目录布局
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// 获取当前激活配置档的持久化文件路径
+pub fn get_active_profile_file() -> PathBuf {
+    get_config_directory().join("active_profile.json")
+}
+
+/// 激活配置档的持久化内容
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ActiveProfileState {
+    active_profile: Option<String>,
+}
+
+/// 校验配置档名称：只允许字母、数字、`-`、`_`，长度 1–64
+pub fn validate_profile_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() || name.len() > 64 {
+        return Err("配置档名称长度必须在 1–64 个字符之间".to_string());
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!("配置档名称只能包含字母、数字、- 和 _: {}", name));
+    }
+    Ok(name.to_string())
+}
+
+/// 获取当前激活的配置档名称，未设置或文件损坏时返回 default
+pub fn get_active_profile() -> String {
+    fs::read_to_string(get_active_profile_file())
+        .ok()
+        .and_then(|content| serde_json::from_str::<ActiveProfileState>(&content).ok())
+        .and_then(|state| state.active_profile)
+        .and_then(|name| validate_profile_name(&name).ok())
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+/// 切换激活的配置档（不存在时会在首次访问账户目录时自动创建）
+pub fn set_active_profile(name: &str) -> Result<String, String> {
+    let name = validate_profile_name(name)?;
+    let state = ActiveProfileState {
+        active_profile: Some(name.clone()),
+    };
+    let json =
+        serde_json::to_string_pretty(&state).map_err(|e| format!("序列化配置档失败: {}", e))?;
+    fs::write(get_active_profile_file(), json).map_err(|e| format!("写入配置档文件失败: {}", e))?;
+    info!("✅ 已切换到配置档: {}", name);
+    Ok(name)
+}
+
+/// 列出所有配置档（default 始终在首位，其余按名称排序）
+pub fn list_profiles() -> Vec<String> {
+    let mut profiles: Vec<String> = fs::read_dir(get_config_directory().join("profiles"))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .filter(|name| name != DEFAULT_PROFILE && validate_profile_name(name).is_ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    profiles.sort();
+    profiles.insert(0, DEFAULT_PROFILE.to_string());
+    profiles
+}
+
+/// 读取设置中的账户目录覆盖路径（未设置或为空时为 None）
+fn accounts_directory_override() -> Option<PathBuf> {
+    crate::app_settings::AppSettings::load_from_file(&get_app_settings_file())
+        .accounts_directory
+        .filter(|path| !path.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+/// 配置目录下的默认账户备份目录 `.antigravity-agent/antigravity-accounts`（不考虑设置覆盖）
+pub fn get_builtin_accounts_directory() -> PathBuf {
+    get_config_directory().join("antigravity-accounts")
+}
+
+/// 获取指定配置档的账户备份目录
+///
+/// default 配置档优先使用设置中的 `accounts_directory`，否则沿用旧布局
+/// `.antigravity-agent/antigravity-accounts`；
+/// 其他配置档位于 `.antigravity-agent/profiles/<name>/antigravity-accounts`
+fn profile_accounts_directory(profile: &str) -> PathBuf {
+    let accounts_dir = if profile == DEFAULT_PROFILE {
+        accounts_directory_override().unwrap_or_else(get_builtin_accounts_directory)
+    } else {
+        get_config_directory()
+            .join("profiles")
+            .join(profile)
+            .join("antigravity-accounts")
+    };
 
     // 确保目录存在
     if let Err(e) = fs::create_dir_all(&accounts_dir) {
@@ -61,6 +180,195 @@ pub fn get_accounts_directory() -> PathBuf {
     accounts_dir
 }
 
+/// 获取当前激活配置档的账户备份目录
+pub fn get_accounts_directory() -> PathBuf {
+    profile_accounts_directory(&get_active_profile())
+}
+
+/// 获取 default 配置档的账户备份目录（受 `accounts_directory` 设置影响）
+pub fn get_default_accounts_directory() -> PathBuf {
+    profile_accounts_directory(DEFAULT_PROFILE)
+}
+
+/// 校验并创建新的账户备份目录，确认可写后返回规范化路径
+pub fn prepare_accounts_directory(path: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(path.trim());
+    if !path.is_absolute() {
+        return Err(format!("账户目录必须是绝对路径: {}", path.display()));
+    }
+    fs::create_dir_all(&path).map_err(|e| format!("创建账户目录失败: {}", e))?;
+
+    probe_dir_writable(&path).map_err(|e| format!("账户目录不可写 {}: {}", path.display(), e))?;
+
+    fs::canonicalize(&path).map_err(|e| format!("解析账户目录失败: {}", e))
+}
+
+/// 写入并删除一个临时文件，确认目录实际可写
+///
+/// 只读权限位无法反映只读挂载、同步盘或 ACL 限制，需要真正写一次
+pub fn probe_dir_writable(dir: &Path) -> io::Result<()> {
+    let probe = dir.join(format!(".write-test-{}", std::process::id()));
+    fs::write(&probe, b"ok")?;
+    fs::remove_file(&probe)
+}
+
+/// 移动文件或目录：优先重命名，跨磁盘时复制后删除源
+fn move_path(src: &Path, dst: &Path) -> io::Result<()> {
+    if fs::rename(src, dst).is_ok() {
+        return Ok(());
+    }
+    if src.is_dir() {
+        fs::create_dir_all(dst)?;
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            let target = dst.join(entry.file_name());
+            if target.exists() {
+                // 目录合并时保留目标中已有的文件（如内容寻址的增量数据）
+                warn!("目标已存在，跳过: {}", target.display());
+                continue;
+            }
+            move_path(&entry.path(), &target)?;
+        }
+        // 有跳过的文件时源目录非空，保留在原处
+        let _ = fs::remove_dir(src);
+        Ok(())
+    } else {
+        fs::copy(src, dst)?;
+        fs::remove_file(src)
+    }
+}
+
+/// 将账户目录下的全部内容（备份文件、.blobs、pruned 等）移动到新目录，返回移动的条目数
+///
+/// 新目录中已有同名备份文件时不移动任何内容，直接返回错误；同名子目录会合并
+pub fn move_accounts_directory_contents(from: &Path, to: &Path) -> Result<usize, String> {
+    let canonical_from = fs::canonicalize(from).unwrap_or_else(|_| from.to_path_buf());
+    let canonical_to = fs::canonicalize(to).unwrap_or_else(|_| to.to_path_buf());
+    if canonical_to.starts_with(&canonical_from) {
+        return Err("新目录不能位于原账户目录之内".to_string());
+    }
+
+    let entries: Vec<PathBuf> = fs::read_dir(from)
+        .map_err(|e| format!("读取原账户目录失败: {}", e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+
+    let conflicts: Vec<String> = entries
+        .iter()
+        .filter(|path| path.is_file())
+        .filter_map(|path| path.file_name())
+        .filter(|name| to.join(name).exists())
+        .map(|name| name.to_string_lossy().to_string())
+        .collect();
+    if !conflicts.is_empty() {
+        return Err(format!(
+            "新目录中已存在同名文件，未移动任何备份: {}",
+            conflicts.join(", ")
+        ));
+    }
+
+    let mut moved = 0;
+    for path in entries {
+        let Some(name) = path.file_name() else {
+            continue;
+        };
+        move_path(&path, &to.join(name))
+            .map_err(|e| format!("移动 {} 失败: {}", path.display(), e))?;
+        moved += 1;
+    }
+    info!(
+        "✅ 已移动 {} 个条目: {} -> {}",
+        moved,
+        from.display(),
+        to.display()
+    );
+    Ok(moved)
+}
+
+/// Windows 保留的设备名，不能作为文件名
+const WINDOWS_RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// 校验账户名（备份文件名，不含 .json），防止路径穿越
+///
+/// 拒绝空名、路径分隔符、`..`、控制字符以及 Windows 保留设备名
+pub fn sanitize_account_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("账户名不能为空".to_string());
+    }
+    if name.contains(['/', '\\', ':']) || name.contains("..") {
+        return Err(format!("账户名包含非法路径字符: {}", name));
+    }
+    if name.chars().any(|c| c.is_control()) {
+        return Err("账户名包含控制字符".to_string());
+    }
+    let base = name.split('.').next().unwrap_or(name);
+    if WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(base))
+    {
+        return Err(format!("账户名为系统保留名称: {}", name));
+    }
+    Ok(name.to_string())
+}
+
+/// 压缩备份文件的后缀
+pub const COMPRESSED_BACKUP_SUFFIX: &str = ".json.gz";
+
+/// 账户名对应的两种备份文件路径：(`{name}.json`, `{name}.json.gz`)，账户名会先经过校验
+///
+/// 所有账户备份路径都应通过此函数构造，保证备份、恢复、删除使用同一目录
+pub fn get_account_file_variants(name: &str) -> Result<(PathBuf, PathBuf), String> {
+    let name = sanitize_account_name(name)?;
+    let accounts_dir = get_accounts_directory();
+    Ok((
+        accounts_dir.join(format!("{name}.json")),
+        accounts_dir.join(format!("{name}{COMPRESSED_BACKUP_SUFFIX}")),
+    ))
+}
+
+/// 获取指定账户的备份文件路径（账户名经过校验）
+///
+/// 已存在压缩备份（.json.gz）且不存在 .json 时返回压缩备份的路径，否则返回 .json 路径
+pub fn get_account_file(name: &str) -> Result<PathBuf, String> {
+    let (plain, compressed) = get_account_file_variants(name)?;
+    if !plain.exists() && compressed.exists() {
+        return Ok(compressed);
+    }
+    Ok(plain)
+}
+
+/// 从备份文件路径取出账户名（去掉 .json 或 .json.gz 后缀），不是备份文件时返回 None
+pub fn account_name_from_path(path: &Path) -> Option<String> {
+    let file_name = path.file_name()?.to_str()?;
+    file_name
+        .strip_suffix(COMPRESSED_BACKUP_SUFFIX)
+        .or_else(|| file_name.strip_suffix(".json"))
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+}
+
+/// 按文件名判断是否为账户备份文件（.json 或 .json.gz）
+pub fn is_account_backup_file(path: &Path) -> bool {
+    account_name_from_path(path).is_some()
+}
+
+/// 获取账户头像缓存目录
+pub fn get_avatar_cache_directory() -> PathBuf {
+    let avatar_dir = get_config_directory().join("avatars");
+
+    // 确保目录存在
+    if let Err(e) = fs::create_dir_all(&avatar_dir) {
+        eprintln!("警告：无法创建头像缓存目录 {}: {}", avatar_dir.display(), e);
+    }
+
+    avatar_dir
+}
+
 /// 获取应用设置文件路径
 pub fn get_app_settings_file() -> PathBuf {
     get_config_directory().join("app_settings.json")
@@ -76,176 +384,519 @@ pub fn get_antigravity_path_file() -> PathBuf {
     get_config_directory().join("antigravity_path.json")
 }
 
+/// 获取旧账户迁移状态文件路径
+pub fn get_migration_state_file() -> PathBuf {
+    get_config_directory().join("migration_state.json")
+}
+
+/// 旧账户目录迁移报告
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MigrationReport {
+    /// 本次迁移的执行时间（RFC 3339）
+    pub ran_at: String,
+    /// 实际扫描过的旧账户目录
+    pub legacy_dirs: Vec<String>,
+    /// 成功迁移的文件数
+    pub migrated: usize,
+    /// 因同名冲突而追加后缀写入的文件数
+    pub renamed_on_collision: usize,
+    /// 新目录已存在相同内容而跳过的文件数
+    pub skipped_existing: usize,
+    /// 之前已迁移过而跳过的文件数
+    pub skipped_already_migrated: usize,
+    /// 已重命名为 .bak 的旧文件数
+    pub renamed: usize,
+    /// 处理失败的文件及原因
+    pub errors: Vec<String>,
+}
+
+/// 持久化的迁移状态，用于保证重复运行时的幂等性
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct MigrationState {
+    /// 已成功迁移过的旧文件路径
+    migrated_files: Vec<String>,
+    /// 最近一次运行的报告
+    last_report: Option<MigrationReport>,
+}
+
+fn load_migration_state() -> MigrationState {
+    fs::read_to_string(get_migration_state_file())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_migration_state(state: &MigrationState) {
+    let result = serde_json::to_string_pretty(state)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(get_migration_state_file(), json).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        warn!(target: "app::startup", "保存迁移状态失败: {}", e);
+    }
+}
+
+/// 获取最近一次旧账户迁移的报告
+pub fn get_last_migration_report() -> Option<MigrationReport> {
+    load_migration_state().last_report
+}
+
+/// 列出可能存在旧账户目录的位置（Roaming 配置目录与数据目录），已去重
+fn legacy_accounts_dirs() -> Vec<PathBuf> {
+    let mut dirs_found: Vec<PathBuf> = Vec::new();
+    for base in [dirs::config_dir(), dirs::data_dir()].into_iter().flatten() {
+        let dir = base.join(".antigravity-agent").join("antigravity-accounts");
+        if !dirs_found.contains(&dir) {
+            dirs_found.push(dir);
+        }
+    }
+    dirs_found
+}
+
+/// 为同名冲突的文件生成不冲突的目标路径：{stem}-legacy-{n}.json
+fn collision_free_path(accounts_dir: &Path, stem: &str) -> PathBuf {
+    let mut index = 1;
+    loop {
+        let candidate = accounts_dir.join(format!("{stem}-legacy-{index}.json"));
+        if !candidate.exists() {
+            return candidate;
+        }
+        index += 1;
+    }
+}
+
 /// 在应用启动时检查并迁移旧账户目录到新路径。
-/// 当前为空实现，后续补充实际迁移逻辑。
-pub fn migrate_legacy_accounts_if_needed() -> io::Result<()> {
+///
+/// 会扫描 Roaming 配置目录与数据目录下的 `.antigravity-agent/antigravity-accounts`，
+/// 只保留 jetskiStateSync.agentManagerInitState 字段写入新目录；
+/// 新目录已有同名但内容不同的文件时追加 `-legacy-N` 后缀，内容相同则跳过。
+pub fn migrate_legacy_accounts_if_needed() -> io::Result<MigrationReport> {
     let new_config_dir = get_config_directory();
-    let new_accounts_dir = get_accounts_directory();
+    // 旧账户始终迁移到 default 配置档（即旧版目录布局）
+    let new_accounts_dir = profile_accounts_directory(DEFAULT_PROFILE);
     info!(
         target: "app::startup",
         "当前配置目录: {}",
         new_config_dir.display()
     );
 
-    // 旧账户目录（Roaming 配置目录下）
-    let Some(config_dir) = dirs::config_dir() else {
-        info!(target: "app::startup", "未找到系统配置目录 (dirs::config_dir)，跳过旧账户目录检测");
-        return Ok(());
-    };
-    let legacy_accounts_dir = config_dir
-        .join(".antigravity-agent")
-        .join("antigravity-accounts");
-    info!(
-        target: "app::startup",
-        "检测旧账户目录: {}",
-        legacy_accounts_dir.display()
-    );
+    let mut state = load_migration_state();
+    let report = migrate_legacy_dirs(&legacy_accounts_dirs(), &new_accounts_dir, &mut state)?;
+
+    state.last_report = Some(report.clone());
+    save_migration_state(&state);
 
-    // 收集旧账户目录下的 JSON 文件列表（忽略子目录）
-    let mut legacy_files = Vec::new();
-    let read_dir = match fs::read_dir(&legacy_accounts_dir) {
-        Ok(rd) => rd,
-        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
-        Err(err) => return Err(err),
+    Ok(report)
+}
+
+/// 将 `legacy_dirs` 中的旧账户文件迁移到 `new_accounts_dir`，已迁移过的文件记录在 `state` 中
+fn migrate_legacy_dirs(
+    legacy_dirs: &[PathBuf],
+    new_accounts_dir: &Path,
+    state: &mut MigrationState,
+) -> io::Result<MigrationReport> {
+    let mut report = MigrationReport {
+        ran_at: chrono::Local::now().to_rfc3339(),
+        ..Default::default()
     };
-    for entry in read_dir {
-        let entry = entry?;
-        let path = entry.path();
-        if path.is_file() {
-            if path
+
+    for legacy_accounts_dir in legacy_dirs {
+        if legacy_accounts_dir == new_accounts_dir {
+            continue;
+        }
+        info!(
+            target: "app::startup",
+            "检测旧账户目录: {}",
+            legacy_accounts_dir.display()
+        );
+
+        // 收集旧账户目录下的 JSON 文件列表（忽略子目录）
+        let read_dir = match fs::read_dir(legacy_accounts_dir) {
+            Ok(rd) => rd,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        report
+            .legacy_dirs
+            .push(legacy_accounts_dir.display().to_string());
+
+        let mut legacy_files = Vec::new();
+        for entry in read_dir {
+            let path = entry?.path();
+            let is_json = path
                 .extension()
                 .and_then(|s| s.to_str())
-                .map(|s| s.eq_ignore_ascii_case("json"))
-                .unwrap_or(false)
-            {
+                .is_some_and(|s| s.eq_ignore_ascii_case("json"));
+            if path.is_file() && is_json {
                 legacy_files.push(path);
             }
         }
-    }
-    info!(
-        target: "app::startup",
-        "旧账户目录文件数: {}",
-        legacy_files.len()
-    );
+        info!(
+            target: "app::startup",
+            "旧账户目录文件数: {}",
+            legacy_files.len()
+        );
 
-    // 读取 JSON，并只保留 jetskiStateSync.agentManagerInitState 字段（作为单一键存储）
-    let mut legacy_account_states: Vec<Value> = Vec::new();
-    let mut migrated_count = 0usize;
-    let mut skipped_existing = 0usize;
-    let mut renamed_count = 0usize;
-    for json_path in legacy_files {
-        let file_name = json_path.file_name().map(|f| f.to_owned());
-
-        if let Some(file_name) = file_name {
-            match fs::read_to_string(&json_path) {
-                Ok(content) => {
-                    match serde_json::from_str::<Value>(&content) {
-                        Ok(v) => {
-                            // 仅使用顶层键 jetskiStateSync.agentManagerInitState
-                            if let Some(state) = v.get("jetskiStateSync.agentManagerInitState") {
-                                let mut filtered = serde_json::Map::new();
-                                filtered.insert(
-                                    "jetskiStateSync.agentManagerInitState".to_string(),
-                                    state.clone(),
-                                );
-                                let filtered_value = Value::Object(filtered);
-                                legacy_account_states.push(filtered_value.clone());
-
-                                // 目标写入路径
-                                let new_path = new_accounts_dir.join(&file_name);
-
-                                if new_path.exists() {
-                                    warn!(
-                                        target: "app::startup",
-                                        "新目录已存在同名文件，跳过写入: {}",
-                                        new_path.display()
-                                    );
-                                    skipped_existing += 1;
-                                } else {
-                                    match serde_json::to_string_pretty(&filtered_value) {
-                                        Ok(serialized) => {
-                                            if let Err(e) = fs::write(&new_path, serialized) {
-                                                warn!(
-                                                    target: "app::startup",
-                                                    "写入新文件失败: {}，错误: {}",
-                                                    new_path.display(),
-                                                    e
-                                                );
-                                            } else {
-                                                migrated_count += 1;
-                                            }
-                                        }
-                                        Err(e) => {
-                                            warn!(
-                                                target: "app::startup",
-                                                "序列化 JSON 失败: {}，错误: {}",
-                                                json_path.display(),
-                                                e
-                                            );
-                                        }
-                                    }
-                                }
-                            } else {
-                                warn!(
-                                    target: "app::startup",
-                                    "未找到 jetskiStateSync.agentManagerInitState，文件已忽略: {}",
-                                    json_path.display()
-                                );
-                            }
-                        }
-                        Err(e) => {
-                            warn!(
-                                target: "app::startup",
-                                "解析 JSON 失败（忽略此文件）: {}，错误: {}",
-                                json_path.display(),
-                                e
-                            );
-                        }
-                    }
-                }
+        for json_path in legacy_files {
+            let source_key = json_path.display().to_string();
+            if state.migrated_files.contains(&source_key) {
+                info!(
+                    target: "app::startup",
+                    "文件已迁移过，跳过: {}",
+                    json_path.display()
+                );
+                report.skipped_already_migrated += 1;
+            } else if let Err(e) = migrate_legacy_file(&json_path, new_accounts_dir, &mut report) {
+                warn!(
+                    target: "app::startup",
+                    "迁移旧账户文件失败（忽略此文件）: {}，错误: {}",
+                    json_path.display(),
+                    e
+                );
+                report
+                    .errors
+                    .push(format!("{}: {}", json_path.display(), e));
+            } else {
+                state.migrated_files.push(source_key);
+            }
+
+            // 无论写入/解析是否成功，都尝试将旧文件改为 .bak
+            let bak_path = json_path.with_extension("bak");
+            match fs::rename(&json_path, &bak_path) {
+                Ok(()) => report.renamed += 1,
                 Err(e) => {
                     warn!(
                         target: "app::startup",
-                        "读取文件失败（忽略此文件）: {}，错误: {}",
+                        "重命名旧文件为 .bak 失败: {} -> {}，错误: {}",
                         json_path.display(),
+                        bak_path.display(),
                         e
                     );
+                    report.errors.push(format!(
+                        "{}: 重命名为 .bak 失败: {}",
+                        json_path.display(),
+                        e
+                    ));
                 }
             }
-        } else {
-            warn!(
-                target: "app::startup",
-                "文件名无效，跳过: {}",
-                json_path.display()
-            );
         }
+    }
 
-        // 无论写入/解析是否成功，都尝试将旧文件改为 .bak
-        let bak_path = json_path.with_extension("bak");
-        match fs::rename(&json_path, &bak_path) {
-            Ok(()) => {
-                renamed_count += 1;
-            }
-            Err(e) => {
-                warn!(
-                    target: "app::startup",
-                    "重命名旧文件为 .bak 失败: {} -> {}，错误: {}",
-                    json_path.display(),
-                    bak_path.display(),
-                    e
-                );
+    info!(
+        target: "app::startup",
+        "旧账户迁移完成，扫描目录: {}，成功迁移: {}，冲突改名: {}，同名跳过: {}，已重命名为 .bak: {}，失败: {}",
+        report.legacy_dirs.len(),
+        report.migrated,
+        report.renamed_on_collision,
+        report.skipped_existing,
+        report.renamed,
+        report.errors.len()
+    );
+
+    Ok(report)
+}
+
+/// 迁移后遗留在旧账户目录中的 .bak 文件
+#[derive(Debug, Clone, Serialize)]
+pub struct LegacyBackup {
+    /// 文件名（如 user@example.com.bak），用于恢复或识别
+    pub name: String,
+    /// 完整路径
+    pub path: String,
+    /// 文件大小（字节）
+    pub size: u64,
+    /// 最后修改时间（RFC 3339）
+    pub modified: Option<String>,
+    /// 是否包含可恢复的 jetskiStateSync.agentManagerInitState
+    pub has_agent_state: bool,
+    /// 顶层键数量（无法解析时为 None）
+    pub key_count: Option<usize>,
+    /// 无法解析时的原因
+    pub error: Option<String>,
+}
+
+/// 列出旧账户目录下的 .bak 文件（迁移时重命名的旧备份）
+fn legacy_backup_files() -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for dir in legacy_accounts_dirs() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            let is_bak = path
+                .extension()
+                .and_then(|s| s.to_str())
+                .is_some_and(|s| s.eq_ignore_ascii_case("bak"));
+            if path.is_file() && is_bak {
+                files.push(path);
             }
         }
     }
-    info!(
-        target: "app::startup",
-        "提取到旧账户状态数: {}，成功迁移: {}，同名跳过: {}，已重命名为 .bak: {}",
-        legacy_account_states.len(),
-        migrated_count,
-        skipped_existing,
-        renamed_count
+    files.sort();
+    files
+}
+
+/// 列出迁移遗留的 .bak 文件及其内容摘要
+pub fn list_legacy_backups() -> Vec<LegacyBackup> {
+    legacy_backup_files()
+        .into_iter()
+        .map(|path| {
+            let metadata = fs::metadata(&path).ok();
+            let modified = metadata
+                .as_ref()
+                .and_then(|m| m.modified().ok())
+                .map(|t| chrono::DateTime::<chrono::Local>::from(t).to_rfc3339());
+            let parsed = fs::read_to_string(&path)
+                .map_err(|e| format!("读取文件失败: {}", e))
+                .and_then(|content| {
+                    serde_json::from_str::<Value>(&content)
+                        .map_err(|e| format!("解析 JSON 失败: {}", e))
+                });
+            let (has_agent_state, key_count, error) = match parsed {
+                Ok(value) => (
+                    value.get(crate::constants::database::AGENT_STATE).is_some(),
+                    value.as_object().map(|obj| obj.len()),
+                    None,
+                ),
+                Err(e) => (false, None, Some(e)),
+            };
+
+            LegacyBackup {
+                name: path
+                    .file_name()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                path: path.display().to_string(),
+                size: metadata.map(|m| m.len()).unwrap_or(0),
+                modified,
+                has_agent_state,
+                key_count,
+                error,
+            }
+        })
+        .collect()
+}
+
+/// 将一个迁移遗留的 .bak 文件重新导入为当前配置档下的普通备份
+///
+/// 与迁移规则一致：只保留 jetskiStateSync 字段，同名且内容不同时追加 `-legacy-N` 后缀
+pub fn restore_legacy_backup(name: &str) -> Result<String, String> {
+    let bak_path = legacy_backup_files()
+        .into_iter()
+        .find(|path| path.file_name().is_some_and(|n| n == name))
+        .ok_or_else(|| format!("未找到迁移遗留文件: {}", name))?;
+
+    let accounts_dir = get_accounts_directory();
+    fs::create_dir_all(&accounts_dir).map_err(|e| format!("创建账户目录失败: {}", e))?;
+
+    let mut report = MigrationReport::default();
+    migrate_legacy_file(&bak_path, &accounts_dir, &mut report)?;
+    if report.skipped_existing > 0 {
+        Ok(format!("备份目录已存在相同内容，无需恢复: {}", name))
+    } else if report.renamed_on_collision > 0 {
+        Ok(format!(
+            "已恢复 {}（与现有备份同名，已追加 -legacy 后缀）",
+            name
+        ))
+    } else {
+        Ok(format!("已恢复 {}", name))
+    }
+}
+
+/// 删除所有迁移遗留的 .bak 文件，返回删除数量
+pub fn cleanup_legacy_backups() -> Result<usize, String> {
+    let mut deleted = 0;
+    for path in legacy_backup_files() {
+        fs::remove_file(&path).map_err(|e| format!("删除 {} 失败: {}", path.display(), e))?;
+        deleted += 1;
+    }
+    info!(target: "app::startup", "已删除迁移遗留的 .bak 文件: {}", deleted);
+    Ok(deleted)
+}
+
+/// 迁移单个旧账户文件：仅保留 jetskiStateSync.agentManagerInitState 字段
+fn migrate_legacy_file(
+    json_path: &Path,
+    new_accounts_dir: &Path,
+    report: &mut MigrationReport,
+) -> Result<(), String> {
+    let stem = json_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .ok_or_else(|| "文件名无效".to_string())?;
+
+    let content = fs::read_to_string(json_path).map_err(|e| format!("读取文件失败: {}", e))?;
+    let value: Value =
+        serde_json::from_str(&content).map_err(|e| format!("解析 JSON 失败: {}", e))?;
+
+    // 仅使用顶层键 jetskiStateSync.agentManagerInitState
+    let state = value
+        .get(crate::constants::database::AGENT_STATE)
+        .ok_or_else(|| "未找到 jetskiStateSync.agentManagerInitState".to_string())?;
+    let mut filtered = serde_json::Map::new();
+    filtered.insert(
+        crate::constants::database::AGENT_STATE.to_string(),
+        state.clone(),
     );
+    let mut filtered_value = Value::Object(filtered);
+    crate::antigravity::backup_format::stamp_format_version(&mut filtered_value);
+
+    let mut new_path = new_accounts_dir.join(format!("{stem}.json"));
+    if new_path.exists() {
+        let existing = crate::antigravity::backup_format::read_backup_file(&new_path).ok();
+        if existing.as_ref() == Some(&filtered_value) {
+            info!(
+                target: "app::startup",
+                "新目录已存在相同内容的文件，跳过写入: {}",
+                new_path.display()
+            );
+            report.skipped_existing += 1;
+            return Ok(());
+        }
+
+        new_path = collision_free_path(new_accounts_dir, &stem);
+        warn!(
+            target: "app::startup",
+            "新目录已存在同名文件，改名写入: {}",
+            new_path.display()
+        );
+        report.renamed_on_collision += 1;
+    }
+
+    let serialized = serde_json::to_string_pretty(&filtered_value)
+        .map_err(|e| format!("序列化 JSON 失败: {}", e))?;
+    fs::write(&new_path, serialized).map_err(|e| format!("写入新文件失败: {}", e))?;
+    report.migrated += 1;
 
-    // TODO: 检测 Roaming 下的旧账户目录并迁移到新目录，处理冲突和错误。
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{migrate_legacy_dirs, sanitize_account_name, MigrationState};
+    use crate::constants::database::AGENT_STATE;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn sanitize_account_name_rejects_path_characters() {
+        for name in ["../x", "a/b", "a\\b", "C:x", "..", "a..b"] {
+            assert!(sanitize_account_name(name).is_err(), "{name:?} 应被拒绝");
+        }
+    }
+
+    #[test]
+    fn sanitize_account_name_rejects_reserved_names() {
+        for name in ["CON", "con", "con.json", "Nul.txt", "COM1", "lpt9.json"] {
+            assert!(sanitize_account_name(name).is_err(), "{name:?} 应被拒绝");
+        }
+    }
+
+    #[test]
+    fn sanitize_account_name_rejects_control_characters() {
+        for name in ["a\u{0}b", "a\nb", "a\tb", "a\u{7f}b"] {
+            assert!(sanitize_account_name(name).is_err(), "{name:?} 应被拒绝");
+        }
+    }
+
+    #[test]
+    fn sanitize_account_name_rejects_empty_names() {
+        for name in ["", " ", "\t", " \n "] {
+            assert!(sanitize_account_name(name).is_err(), "{name:?} 应被拒绝");
+        }
+    }
+
+    #[test]
+    fn sanitize_account_name_accepts_and_trims_valid_names() {
+        assert_eq!(
+            sanitize_account_name("  user@example.com ").as_deref(),
+            Ok("user@example.com")
+        );
+        assert_eq!(sanitize_account_name("工作账户").as_deref(), Ok("工作账户"));
+        assert_eq!(sanitize_account_name("console").as_deref(), Ok("console"));
+    }
+
+    fn write_json(path: &Path, value: serde_json::Value) {
+        fs::write(path, value.to_string()).unwrap();
+    }
+
+    #[test]
+    fn migrate_legacy_dirs_handles_fake_legacy_tree() {
+        let root = std::env::temp_dir().join(format!(
+            "antigravity-agent-migration-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        let roaming = root.join("roaming/.antigravity-agent/antigravity-accounts");
+        let data = root.join("data/.antigravity-agent/antigravity-accounts");
+        let missing = root.join("missing/.antigravity-agent/antigravity-accounts");
+        let new_dir = root.join("new/antigravity-accounts");
+        for dir in [&roaming, &data, &new_dir] {
+            fs::create_dir_all(dir).unwrap();
+        }
+
+        // 旧格式备份：多余的数据库键在迁移时丢弃
+        write_json(
+            &roaming.join("a@example.com.json"),
+            serde_json::json!({ AGENT_STATE: "state-a", "antigravityAuthStatus": "{}" }),
+        );
+        // 两个旧目录中的同名文件内容不同：第二个追加 -legacy-1 后缀
+        write_json(
+            &data.join("a@example.com.json"),
+            serde_json::json!({ AGENT_STATE: "state-a2" }),
+        );
+        // 新目录中已有相同内容：跳过
+        write_json(
+            &new_dir.join("b@example.com.json"),
+            serde_json::json!({ AGENT_STATE: "state-b", "format_version": 1 }),
+        );
+        write_json(
+            &data.join("b@example.com.json"),
+            serde_json::json!({ AGENT_STATE: "state-b" }),
+        );
+        // 损坏的文件记入 errors，但仍重命名为 .bak
+        fs::write(data.join("broken.json"), "{ not json").unwrap();
+        // 非 JSON 文件和子目录不处理
+        fs::write(data.join("notes.txt"), "ignored").unwrap();
+        fs::create_dir_all(data.join("nested.json")).unwrap();
+
+        let legacy_dirs = vec![roaming.clone(), data.clone(), missing];
+        let mut state = MigrationState::default();
+        let report = migrate_legacy_dirs(&legacy_dirs, &new_dir, &mut state).unwrap();
+
+        assert_eq!(report.legacy_dirs.len(), 2);
+        assert_eq!(report.migrated, 2);
+        assert_eq!(report.renamed_on_collision, 1);
+        assert_eq!(report.skipped_existing, 1);
+        assert_eq!(report.renamed, 4);
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.errors[0].contains("broken.json"));
+
+        let read = |name: &str| -> serde_json::Value {
+            serde_json::from_str(&fs::read_to_string(new_dir.join(name)).unwrap()).unwrap()
+        };
+        let migrated = read("a@example.com.json");
+        assert_eq!(migrated[AGENT_STATE], "state-a");
+        assert!(migrated.get("antigravityAuthStatus").is_none());
+        assert_eq!(read("a@example.com-legacy-1.json")[AGENT_STATE], "state-a2");
+
+        assert!(roaming.join("a@example.com.bak").is_file());
+        assert!(data.join("broken.bak").is_file());
+        assert!(!data.join("broken.json").exists());
+        assert!(data.join("notes.txt").is_file());
+        assert!(data.join("nested.json").is_dir());
+
+        // 再次运行：已迁移过的文件即使重新出现也不会重复写入
+        write_json(
+            &roaming.join("a@example.com.json"),
+            serde_json::json!({ AGENT_STATE: "state-a" }),
+        );
+        let second = migrate_legacy_dirs(&legacy_dirs, &new_dir, &mut state).unwrap();
+        assert_eq!(second.migrated, 0);
+        assert_eq!(second.skipped_already_migrated, 1);
+        assert!(!new_dir.join("a@example.com-legacy-2.json").exists());
+
+        fs::remove_dir_all(&root).ok();
+    }
+}
@@ -6,11 +6,14 @@ use tracing_appender::non_blocking::WorkerGuard;
 use tracing_subscriber::{prelude::*, EnvFilter};
 
 // Modules
+mod accounts_watcher;
+mod agent_config;
 mod antigravity;
 mod app_settings;
 mod config_manager;
 mod constants;
 mod directories;
+mod metrics;
 mod platform;
 mod proto;
 mod system_tray;
@@ -20,6 +23,7 @@ mod window;
 mod commands;
 mod db_monitor;
 mod path_utils;
+mod process_monitor;
 mod setup;
 mod state;
 
@@ -29,11 +33,17 @@ pub use state::{AntigravityAccount, AppState, ProfileInfo};
 // Use commands
 use crate::commands::*;
 
-/// 初始化双层日志系统（控制台 + 文件）
-fn init_tracing() -> WorkerGuard {
+/// 覆盖 JSON 日志文件级别的环境变量
+const JSON_LOG_ENV: &str = "ANTIGRAVITY_AGENT_JSON_LOG";
+
+/// 初始化日志系统（控制台 + 文本文件 + JSON 行文件）
+fn init_tracing() -> Vec<WorkerGuard> {
     // 日志过滤器：默认 info，降低 h2/hyper 噪音（可被 RUST_LOG 覆盖）
     let env_filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new("info,h2=warn,hyper=warn"));
+    // 包装为可重载过滤器，支持运行时通过 set_log_level 调整
+    let (env_filter, filter_handle) = tracing_subscriber::reload::Layer::new(env_filter);
+    crate::utils::tracing_config::register_log_filter_handle(filter_handle);
 
     // 创建日志目录
     let log_dir = crate::directories::get_log_directory();
@@ -46,30 +56,63 @@ fn init_tracing() -> WorkerGuard {
         crate::utils::sanitizing_layer::SanitizingFileWriter::new().expect("无法创建文件写入器");
     let (non_blocking, guard) = tracing_appender::non_blocking(file_writer);
 
-    // 设置控制台和文件双层输出
+    // JSON 行日志：级别优先取环境变量，其次取应用设置
+    let settings = crate::app_settings::AppSettings::load_from_file(
+        &crate::directories::get_app_settings_file(),
+    );
+    let json_directive = std::env::var(JSON_LOG_ENV).unwrap_or(settings.json_log_level);
+    let json_filter = EnvFilter::try_new(&json_directive).unwrap_or_else(|e| {
+        eprintln!(
+            "警告：无效的 JSON 日志级别 {}: {}，使用 info",
+            json_directive, e
+        );
+        EnvFilter::new("info")
+    });
+    // JSON 日志写入器创建失败时仅跳过 JSON 输出，不影响应用启动
+    let (json_layer, json_guard) = match crate::utils::sanitizing_layer::SanitizingFileWriter::json(
+        settings.log_retention_days,
+    ) {
+        Ok(json_writer) => {
+            let (json_non_blocking, json_guard) = tracing_appender::non_blocking(json_writer);
+            let layer = tracing_subscriber::fmt::layer()
+                .json()
+                .with_writer(json_non_blocking) // JSON 行文件，便于附在问题反馈中
+                .with_target(true)
+                .with_filter(json_filter);
+            (Some(layer), Some(json_guard))
+        }
+        Err(e) => {
+            eprintln!("警告：无法创建 JSON 日志写入器: {}", e);
+            (None, None)
+        }
+    };
+
+    // 控制台和文本文件共用 RUST_LOG 过滤器，JSON 文件使用独立过滤器
     tracing_subscriber::registry()
-        .with(env_filter)
         .with(
             tracing_subscriber::fmt::layer()
                 .with_writer(std::io::stdout) // 控制台输出，不脱敏
                 .with_target(false)
                 .compact()
-                .with_ansi(true), // 控制台启用颜色
-        )
-        .with(
-            tracing_subscriber::fmt::layer()
-                .with_writer(non_blocking) // 文件输出，自动脱敏
-                .with_target(true)
-                .with_ansi(false) // 文件不使用颜色代码
-                .compact(), // 使用紧凑格式而非 JSON，便于脱敏处理
+                .with_ansi(true) // 控制台启用颜色
+                .and_then(
+                    tracing_subscriber::fmt::layer()
+                        .with_writer(non_blocking) // 文件输出，自动脱敏
+                        .with_target(true)
+                        .with_ansi(false) // 文件不使用颜色代码
+                        .compact(), // 使用紧凑格式而非 JSON，便于脱敏处理
+                )
+                .with_filter(env_filter),
         )
+        .with(json_layer)
         .init();
 
-    guard // 返回 guard 以防止日志缓冲区被过早清理
+    // 返回 guard 以防止日志缓冲区被过早清理
+    std::iter::once(guard).chain(json_guard).collect()
 }
 
 fn main() {
-    // 初始化双层日志系统（控制台 + 文件）
+    // 初始化日志系统（控制台 + 文本文件 + JSON 行文件）
     let _guard = init_tracing();
 
     tracing::info!(target: "app::startup", "🚀 启动 Antigravity Agent");
@@ -81,7 +124,12 @@ fn main() {
 
     // 阻塞主线程执行一次账户目录迁移检查
     match crate::directories::migrate_legacy_accounts_if_needed() {
-        Ok(()) => tracing::info!(target: "app::startup", "📦 账户目录迁移检查完成"),
+        Ok(report) => tracing::info!(
+            target: "app::startup",
+            migrated = report.migrated,
+            errors = report.errors.len(),
+            "📦 账户目录迁移检查完成"
+        ),
         Err(e) => tracing::error!(target: "app::startup", "⚠️ 账户目录迁移检查失败: {}", e),
     }
 
@@ -93,32 +141,90 @@ fn main() {
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_http::init())
+        .plugin(tauri_plugin_autostart::init(
+            tauri_plugin_autostart::MacosLauncher::LaunchAgent,
+            None,
+        ))
         .manage(AppState::default())
         .setup(|app| setup::init(app))
         .invoke_handler(tauri::generate_handler![
             collect_account_contents,
             restore_backup_files,
             delete_backup,
+            duplicate_account,
+            resolve_account_path,
             clear_all_backups,
+            prune_account_backups,
+            verify_all_backups,
+            repair_backup,
+            snapshot_antigravity_db,
+            restore_antigravity_db_snapshot,
+            get_active_profile,
+            set_active_profile,
+            list_profiles,
+            set_accounts_directory,
+            get_migration_report,
+            list_legacy_backups,
+            restore_legacy_backup,
+            cleanup_legacy_backups,
             // 账户基础命令
             get_antigravity_accounts,
+            get_antigravity_accounts_with_warnings,
+            search_accounts,
+            get_account_avatar,
             get_current_antigravity_account_info,
+            get_current_antigravity_account_info_redacted,
+            get_current_account_email,
+            list_antigravity_db_keys,
+            get_antigravity_db_value,
             save_antigravity_current_account,
+            backup_all_installs,
+            backup_current_account_to,
+            import_account_from_db,
+            validate_antigravity_db,
+            check_db_compatibility,
             restore_antigravity_account,
+            restore_requires_close,
             switch_to_antigravity_account,
+            switch_and_verify,
+            get_switch_history,
+            is_account_operation_in_progress,
+            is_read_only,
+            preview_restore,
+            list_unrestored_keys,
+            get_accounts_volume_free_bytes,
+            set_account_meta,
+            restore_account_partial,
+            create_test_account,
+            create_logged_out_backup,
             clear_all_antigravity_data,
+            compact_antigravity_db,
             is_antigravity_running,
+            list_antigravity_processes,
+            kill_stale_antigravity,
+            wait_for_antigravity_ready,
+            start_antigravity,
             sign_in_new_antigravity_account,
             // 平台支持命令
             get_platform_info,
+            get_diagnostics,
+            get_metrics_text,
             find_antigravity_installations,
             get_current_paths,
+            revalidate_paths,
+            get_active_install,
+            set_active_install,
             // 数据库路径相关
             detect_antigravity_installation,
+            get_antigravity_db_status,
             // 可执行文件路径相关
             validate_antigravity_executable,
             detect_antigravity_executable,
+            can_start_antigravity,
+            detect_target_apps,
+            detect_antigravity_version,
             save_antigravity_executable,
+            save_extra_executable_paths,
             enable_system_tray,
             disable_system_tray,
             minimize_to_tray,
@@ -128,7 +234,24 @@ fn main() {
             update_tray_menu_command,
             is_silent_start_enabled,
             save_silent_start_state,
+            save_auto_backup_on_change_state,
+            save_auto_prune_settings,
+            save_process_watch_state,
+            cancel_silent_start_hide,
             get_all_settings,
+            get_app_setting,
+            set_app_setting,
+            get_http_timeout,
+            set_http_timeout,
+            set_db_monitor_debounce,
+            set_stale_backup_days,
+            save_delta_backups_state,
+            save_compress_backups_state,
+            save_revalidate_paths_on_startup_state,
+            export_agent_config,
+            import_agent_config,
+            get_autostart,
+            set_autostart,
             // 数据库监控命令
             is_database_monitoring_running,
             start_database_monitoring,
@@ -137,7 +260,16 @@ fn main() {
             encrypt_config_data,
             write_text_file,
             write_frontend_log,
+            get_log_level,
+            set_log_level,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            // 退出时停止后台进程监控任务和账户目录监听
+            if let tauri::RunEvent::Exit = event {
+                tauri::Manager::state::<process_monitor::ProcessMonitor>(app).stop();
+                tauri::Manager::state::<accounts_watcher::AccountsWatcher>(app).stop();
+            }
+        });
 }
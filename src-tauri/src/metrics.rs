@@ -0,0 +1,128 @@
+//! 运行指标模块
+//! 汇总账户备份数量、Antigravity 运行状态和账户切换次数，输出 Prometheus 文本格式
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 持久化的计数器（跨重启保留）
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct PersistedMetrics {
+    /// 成功完成的账户切换次数
+    switches_total: u64,
+    /// 失败的账户切换次数
+    switch_failures_total: u64,
+    /// 最近一次成功切换的耗时（秒）
+    last_switch_duration_seconds: Option<f64>,
+}
+
+/// 串行化计数器文件的读改写
+static METRICS_LOCK: Mutex<()> = Mutex::new(());
+
+fn metrics_file() -> PathBuf {
+    crate::directories::get_config_directory().join("metrics.json")
+}
+
+fn load_metrics() -> PersistedMetrics {
+    fs::read_to_string(metrics_file())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// 记录一次账户切换结果；写入失败只记日志，不影响切换本身
+pub fn record_switch(success: bool, duration: Duration) {
+    let _guard = METRICS_LOCK.lock().unwrap();
+    let mut metrics = load_metrics();
+    if success {
+        metrics.switches_total += 1;
+        metrics.last_switch_duration_seconds = Some(duration.as_secs_f64());
+    } else {
+        metrics.switch_failures_total += 1;
+    }
+
+    let result = serde_json::to_string_pretty(&metrics)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(metrics_file(), json).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        tracing::warn!(target: "metrics", error = %e, "保存切换计数失败");
+    }
+}
+
+/// 统计当前配置档下的账户备份数量
+fn count_account_backups() -> usize {
+    fs::read_dir(crate::directories::get_accounts_directory())
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| crate::directories::is_account_backup_file(&entry.path()))
+                .count()
+        })
+        .unwrap_or(0)
+}
+
+/// 写入一个指标（含 HELP / TYPE 注释）
+fn write_metric(
+    out: &mut String,
+    name: &str,
+    kind: &str,
+    help: &str,
+    value: impl std::fmt::Display,
+) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+    let _ = writeln!(out, "{name} {value}");
+}
+
+/// 生成 Prometheus 文本格式（exposition format）的指标
+pub fn render_metrics_text() -> String {
+    let metrics = {
+        let _guard = METRICS_LOCK.lock().unwrap();
+        load_metrics()
+    };
+    let running = crate::platform::is_antigravity_running();
+
+    let mut out = String::new();
+    write_metric(
+        &mut out,
+        "antigravity_agent_account_backups",
+        "gauge",
+        "Number of account backups in the active profile",
+        count_account_backups(),
+    );
+    write_metric(
+        &mut out,
+        "antigravity_agent_antigravity_running",
+        "gauge",
+        "Whether an Antigravity process is running (1) or not (0)",
+        u8::from(running),
+    );
+    write_metric(
+        &mut out,
+        "antigravity_agent_switches_total",
+        "counter",
+        "Account switches completed successfully, persisted across restarts",
+        metrics.switches_total,
+    );
+    write_metric(
+        &mut out,
+        "antigravity_agent_switch_failures_total",
+        "counter",
+        "Account switches that failed, persisted across restarts",
+        metrics.switch_failures_total,
+    );
+    if let Some(seconds) = metrics.last_switch_duration_seconds {
+        write_metric(
+            &mut out,
+            "antigravity_agent_last_switch_duration_seconds",
+            "gauge",
+            "Duration of the most recent successful account switch",
+            seconds,
+        );
+    }
+    out
+}
@@ -4,7 +4,15 @@ use dirs::*;
 /// 提供跨平台兼容的路径处理方法，避免硬编码路径
 use std::path::{Path, PathBuf};
 
+/// 覆盖 Antigravity 可执行文件路径的环境变量（用于测试/CI）
+pub const EXE_PATH_ENV: &str = "ANTIGRAVITY_AGENT_EXE_PATH";
+
+/// 覆盖 Antigravity 数据目录的环境变量（用于测试/CI，目录下应包含 state.vscdb）
+pub const DATA_DIR_ENV: &str = "ANTIGRAVITY_AGENT_DATA_DIR";
+
 /// 应用程序相关路径管理器
+///
+/// 路径解析优先级：环境变量 > 用户自定义配置 > 自动检测
 pub struct AppPaths;
 
 impl AppPaths {
@@ -14,8 +22,10 @@ impl AppPaths {
     /// - Windows: %APPDATA%\Antigravity\User\globalStorage\
     /// - macOS: ~/Library/Application Support/Antigravity/User/globalStorage/
     /// - Linux: ~/.config/Antigravity/User/globalStorage/
+    ///
+    /// 设置了 `ANTIGRAVITY_AGENT_DATA_DIR` 且目录存在时优先使用该目录
     pub fn antigravity_data_dir() -> Option<PathBuf> {
-        let result = antigravity_data_dir_impl();
+        let result = Self::env_data_dir().or_else(antigravity_data_dir_impl);
 
         match &result {
             Some(path) => {
@@ -34,8 +44,98 @@ impl AppPaths {
     /// - Windows: %LOCALAPPDATA%\Programs\Antigravity\Antigravity.exe
     /// - macOS: /Applications/Antigravity.app
     /// - Linux: /usr/bin/antigravity, ~/.local/bin/antigravity
+    ///
+    /// 用户配置的额外搜索路径排在内置路径之前；
+    /// 设置了 `ANTIGRAVITY_AGENT_EXE_PATH` 且路径存在时只返回该路径
     pub fn antigravity_executable_paths() -> Vec<PathBuf> {
-        antigravity_executable_paths_impl()
+        if let Some(path) = Self::env_executable_path() {
+            return vec![path];
+        }
+
+        let mut paths: Vec<PathBuf> = crate::antigravity::path_config::get_extra_executable_paths()
+            .into_iter()
+            .map(PathBuf::from)
+            .collect();
+        paths.extend(antigravity_executable_paths_impl());
+        paths
+    }
+
+    /// 获取 Windsurf 数据目录
+    ///
+    /// 跨平台路径:
+    /// - Windows: %APPDATA%\Windsurf\User\globalStorage\
+    /// - macOS: ~/Library/Application Support/Windsurf/User/globalStorage/
+    /// - Linux: ~/.config/Windsurf/User/globalStorage/
+    pub fn windsurf_data_dir() -> Option<PathBuf> {
+        let base = if cfg!(target_os = "macos") {
+            data_dir()
+        } else {
+            config_dir()
+        };
+        base.map(|path| path.join("Windsurf").join("User").join("globalStorage"))
+    }
+
+    /// 获取 Windsurf 可执行文件路径
+    ///
+    /// 跨平台搜索路径:
+    /// - Windows: %LOCALAPPDATA%\Programs\Windsurf\Windsurf.exe
+    /// - macOS: /Applications/Windsurf.app
+    /// - Linux: /usr/bin/windsurf, /usr/share/windsurf/windsurf, ~/.local/bin/windsurf
+    pub fn windsurf_executable_paths() -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+
+        if cfg!(target_os = "windows") {
+            if let Some(local_data) = data_local_dir() {
+                paths.push(
+                    local_data
+                        .join("Programs")
+                        .join("Windsurf")
+                        .join("Windsurf.exe"),
+                );
+            }
+            if let Ok(program_files) = std::env::var("ProgramFiles") {
+                paths.push(
+                    PathBuf::from(program_files)
+                        .join("Windsurf")
+                        .join("Windsurf.exe"),
+                );
+            }
+        } else if cfg!(target_os = "macos") {
+            paths.push(PathBuf::from("/Applications/Windsurf.app"));
+            if let Some(home) = home_dir() {
+                paths.push(home.join("Applications").join("Windsurf.app"));
+            }
+        } else {
+            paths.push(PathBuf::from("/usr/bin/windsurf"));
+            paths.push(PathBuf::from("/usr/share/windsurf/windsurf"));
+            if let Some(home) = home_dir() {
+                paths.push(home.join(".local").join("bin").join("windsurf"));
+            }
+        }
+
+        paths
+    }
+
+    /// 读取环境变量中覆盖的可执行文件路径（路径不存在时忽略）
+    pub fn env_executable_path() -> Option<PathBuf> {
+        let path = PathBuf::from(std::env::var_os(EXE_PATH_ENV)?);
+        if path.exists() {
+            Some(path)
+        } else {
+            tracing::warn!("⚠️ {} 指向的路径不存在，已忽略", EXE_PATH_ENV);
+            None
+        }
+    }
+
+    /// 读取环境变量中覆盖的数据目录（目录不存在时忽略）
+    pub fn env_data_dir() -> Option<PathBuf> {
+        let path = PathBuf::from(std::env::var_os(DATA_DIR_ENV)?);
+        if path.is_dir() {
+            Some(path)
+        } else {
+            tracing::warn!("⚠️ {} 指向的目录不存在，已忽略", DATA_DIR_ENV);
+            None
+        }
     }
 }
 
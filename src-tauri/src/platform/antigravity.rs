@@ -1,17 +1,105 @@
 use crate::path_utils::AppPaths;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+/// 可管理的目标应用，默认为 Antigravity
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetApp {
+    #[default]
+    Antigravity,
+    Windsurf,
+}
+
+impl TargetApp {
+    /// 应用显示名称
+    pub fn display_name(self) -> &'static str {
+        match self {
+            TargetApp::Antigravity => "Antigravity",
+            TargetApp::Windsurf => "Windsurf",
+        }
+    }
+
+    /// 应用数据目录
+    pub fn data_dir(self) -> Option<PathBuf> {
+        match self {
+            TargetApp::Antigravity => get_antigravity_data_dir(),
+            TargetApp::Windsurf => AppPaths::windsurf_data_dir(),
+        }
+    }
+
+    /// 状态数据库路径（Antigravity 找不到主路径时回退到其他候选位置）
+    pub fn db_path(self) -> Option<PathBuf> {
+        match self {
+            TargetApp::Antigravity => get_antigravity_db_path()
+                .or_else(|| get_all_antigravity_db_paths().into_iter().next()),
+            TargetApp::Windsurf => get_windsurf_db_path(),
+        }
+    }
+
+    /// 可执行文件候选路径
+    pub fn executable_paths(self) -> Vec<PathBuf> {
+        match self {
+            TargetApp::Antigravity => AppPaths::antigravity_executable_paths(),
+            TargetApp::Windsurf => AppPaths::windsurf_executable_paths(),
+        }
+    }
+}
+
+/// 获取 Windsurf 状态数据库文件路径
+pub fn get_windsurf_db_path() -> Option<PathBuf> {
+    AppPaths::windsurf_data_dir().map(|dir| dir.join("state.vscdb"))
+}
+
+/// 设置中固定的 Antigravity 状态数据库（`active_install_db`），文件已不存在时返回 None
+pub fn pinned_antigravity_db_path() -> Option<PathBuf> {
+    crate::app_settings::AppSettings::load_from_file(&crate::directories::get_app_settings_file())
+        .active_install_db
+        .filter(|path| !path.trim().is_empty())
+        .map(PathBuf::from)
+        .filter(|path| path.is_file())
+}
+
 /// 获取Antigravity应用数据目录（跨平台）
+///
+/// 设置中固定了某个安装且其数据库仍存在时，返回该安装的数据目录
 pub fn get_antigravity_data_dir() -> Option<PathBuf> {
-    AppPaths::antigravity_data_dir()
+    pinned_antigravity_db_path()
+        .and_then(|db| db.parent().map(PathBuf::from))
+        .or_else(AppPaths::antigravity_data_dir)
 }
 
 /// 获取Antigravity状态数据库文件路径
-/// 使用自动检测的路径
+/// 优先使用固定的安装，否则使用自动检测的路径
 pub fn get_antigravity_db_path() -> Option<PathBuf> {
     get_antigravity_data_dir().map(|dir| dir.join("state.vscdb"))
 }
 
+/// 获取 Antigravity 日志目录（数据根目录下的 logs）
+pub fn get_antigravity_logs_dir() -> Option<PathBuf> {
+    // 数据目录为 <根目录>/User/globalStorage
+    get_antigravity_data_dir().and_then(|dir| dir.ancestors().nth(2).map(|root| root.join("logs")))
+}
+
+/// 日志目录下（按会话分子目录，最多向下 3 层）是否已有 .log 文件
+pub fn has_antigravity_log_file() -> bool {
+    fn contains_log(dir: &std::path::Path, depth: usize) -> bool {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return false;
+        };
+        entries.flatten().any(|entry| {
+            let path = entry.path();
+            if path.is_dir() {
+                depth > 0 && contains_log(&path, depth - 1)
+            } else {
+                path.extension().is_some_and(|ext| ext == "log")
+            }
+        })
+    }
+
+    get_antigravity_logs_dir().is_some_and(|dir| contains_log(&dir, 3))
+}
+
 /// 检查Antigravity是否安装并运行
 pub fn is_antigravity_available() -> bool {
     get_antigravity_db_path()
@@ -19,6 +107,63 @@ pub fn is_antigravity_available() -> bool {
         .unwrap_or(false)
 }
 
+/// Antigravity 状态数据库检测结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", content = "path", rename_all = "snake_case")]
+pub enum AntigravityDbStatus {
+    /// 未找到 Antigravity 可执行文件或数据目录
+    NotInstalled,
+    /// 已安装但尚未创建状态数据库（通常是从未启动过），附带预期的数据库路径
+    InstalledButNoDb(PathBuf),
+    /// 状态数据库已存在
+    Ready(PathBuf),
+}
+
+impl AntigravityDbStatus {
+    /// 转换为数据库路径，未就绪时返回带处理建议的错误信息
+    pub fn into_result(self) -> Result<PathBuf, String> {
+        match self {
+            AntigravityDbStatus::Ready(path) => Ok(path),
+            AntigravityDbStatus::NotInstalled => {
+                Err("未找到 Antigravity 安装位置，请先安装 Antigravity".to_string())
+            }
+            AntigravityDbStatus::InstalledButNoDb(path) => Err(format!(
+                "Antigravity 状态数据库文件不存在: {}，请先启动一次 Antigravity 以创建数据库",
+                path.display()
+            )),
+        }
+    }
+}
+
+/// 检测 Antigravity 是否已安装以及状态数据库是否存在
+///
+/// 任一候选数据库存在即为就绪；否则找到可执行文件或数据目录时视为已安装但未创建数据库
+pub fn detect_antigravity_db() -> AntigravityDbStatus {
+    let candidates = get_all_antigravity_db_paths();
+    if let Some(path) = candidates.iter().find(|path| path.is_file()) {
+        return AntigravityDbStatus::Ready(path.clone());
+    }
+
+    let has_executable = crate::antigravity::path_config::get_custom_executable_path()
+        .ok()
+        .flatten()
+        .map(PathBuf::from)
+        .into_iter()
+        .chain(AppPaths::antigravity_executable_paths())
+        .any(|path| path.exists());
+    // 数据目录为 <根目录>/User/globalStorage，根目录存在说明 Antigravity 运行过或已安装
+    let has_data_root = get_antigravity_data_dir()
+        .and_then(|dir| dir.ancestors().nth(2).map(PathBuf::from))
+        .is_some_and(|root| root.exists());
+
+    match candidates.into_iter().next() {
+        Some(expected) if has_executable || has_data_root => {
+            AntigravityDbStatus::InstalledButNoDb(expected)
+        }
+        _ => AntigravityDbStatus::NotInstalled,
+    }
+}
+
 /// 搜索可能的Antigravity安装位置
 pub fn find_antigravity_installations() -> Vec<PathBuf> {
     let mut possible_paths = Vec::new();
@@ -33,6 +178,11 @@ pub fn find_antigravity_installations() -> Vec<PathBuf> {
         possible_paths.push(config_dir.join("Antigravity"));
     }
 
+    // Linux 沙盒安装：Flatpak（~/.var/app/<应用ID>/）和 Snap（~/snap/<包名>/）
+    if std::env::consts::OS == "linux" {
+        possible_paths.extend(super::flatpak::sandboxed_installation_dirs());
+    }
+
     possible_paths
 }
 
@@ -58,6 +208,15 @@ pub fn get_all_antigravity_db_paths() -> Vec<PathBuf> {
                     }
                 }
             }
+
+            // 标准布局：<安装目录>/User/globalStorage/state.vscdb
+            let global_storage_db = install_dir
+                .join("User")
+                .join("globalStorage")
+                .join("state.vscdb");
+            if global_storage_db.is_file() && !db_paths.contains(&global_storage_db) {
+                db_paths.push(global_storage_db);
+            }
         }
     }
 
@@ -0,0 +1,92 @@
+//! Linux 沙盒安装（Flatpak / Snap）支持
+//!
+//! Flatpak 应用的数据位于 `~/.var/app/<应用ID>/`，Snap 应用位于 `~/snap/<包名>/`，
+//! 且 Flatpak 应用需要通过 `flatpak run` / `flatpak kill` 启动和关闭
+
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// 可能的 Antigravity Flatpak 应用 ID
+pub const ANTIGRAVITY_FLATPAK_APP_IDS: &[&str] =
+    &["com.google.Antigravity", "com.google.antigravity"];
+
+/// 可能的 Antigravity Snap 包名
+pub const ANTIGRAVITY_SNAP_NAMES: &[&str] = &["antigravity"];
+
+/// Flatpak / Snap 沙盒中 Antigravity 的配置和数据目录候选
+pub fn sandboxed_installation_dirs() -> Vec<PathBuf> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+
+    let mut paths = Vec::new();
+    for app_id in ANTIGRAVITY_FLATPAK_APP_IDS {
+        let app_dir = home.join(".var").join("app").join(app_id);
+        paths.push(app_dir.join("config").join("Antigravity"));
+        paths.push(app_dir.join("data").join("Antigravity"));
+    }
+    for snap_name in ANTIGRAVITY_SNAP_NAMES {
+        let snap_dir = home.join("snap").join(snap_name).join("current");
+        paths.push(snap_dir.join(".config").join("Antigravity"));
+        paths.push(snap_dir.join(".local").join("share").join("Antigravity"));
+    }
+    paths
+}
+
+/// 返回已安装的 Antigravity Flatpak 应用 ID（未安装 flatpak 或应用时为 None）
+pub fn installed_flatpak_app_id() -> Option<&'static str> {
+    ANTIGRAVITY_FLATPAK_APP_IDS.iter().copied().find(|app_id| {
+        Command::new("flatpak")
+            .args(["info", app_id])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    })
+}
+
+/// 通过 `flatpak run` 启动 Antigravity
+pub fn start_flatpak(app_id: &str, args: &[String]) -> Result<String, String> {
+    Command::new("flatpak")
+        .arg("run")
+        .arg(app_id)
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("flatpak run {} 失败: {}", app_id, e))?;
+    Ok(format!("Antigravity 已启动 (Flatpak: {})", app_id))
+}
+
+/// 通过 `flatpak kill` 关闭正在运行的 Antigravity Flatpak 实例，返回已关闭的应用 ID
+pub fn kill_flatpak_instances() -> Vec<String> {
+    let Ok(output) = Command::new("flatpak")
+        .args(["ps", "--columns=application"])
+        .stderr(Stdio::null())
+        .output()
+    else {
+        return Vec::new();
+    };
+    let running = String::from_utf8_lossy(&output.stdout);
+
+    let mut killed = Vec::new();
+    for app_id in ANTIGRAVITY_FLATPAK_APP_IDS {
+        if !running.lines().any(|line| line.trim() == *app_id) {
+            continue;
+        }
+        match Command::new("flatpak")
+            .args(["kill", app_id])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+        {
+            Ok(status) if status.success() => {
+                tracing::info!("✅ 已关闭 Flatpak 实例: {}", app_id);
+                killed.push(app_id.to_string());
+            }
+            Ok(status) => tracing::warn!("⚠️ flatpak kill {} 失败: {}", app_id, status),
+            Err(e) => tracing::warn!("⚠️ flatpak kill {} 失败: {}", app_id, e),
+        }
+    }
+    killed
+}
@@ -2,6 +2,7 @@
 //! Provides cross-platform functionality for interacting with Antigravity
 
 pub mod antigravity;
+pub mod flatpak;
 pub mod process;
 
 // Re-export commonly used types and functions
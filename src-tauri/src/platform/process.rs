@@ -1,3 +1,6 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
 /// 关闭Antigravity进程 - 使用sysinfo库实现跨平台统一处理
 pub fn kill_antigravity_processes() -> Result<String, String> {
     tracing::info!("🔍 开始搜索并关闭 Antigravity 进程");
@@ -11,13 +14,22 @@ pub fn kill_antigravity_processes() -> Result<String, String> {
     // 定义需要关闭的进程模式（按优先级排序）
     let process_patterns = get_antigravity_process_patterns();
 
+    let install_dirs = known_install_dirs();
+
     for (pid, process) in system.processes() {
         let process_name = process.name();
         let process_cmd = process.cmd().join(" ");
 
-        // 检查进程名或命令行是否匹配任何模式
-        if matches_antigravity_process(process_name, &process_cmd, &process_patterns) {
-            tracing::info!("🎯 找到目标进程: {} (PID: {})", process_name, pid);
+        // 检查可执行文件路径、进程名或命令行是否匹配
+        if let Some(matched_by) =
+            match_antigravity_process(process, &process_patterns, &install_dirs)
+        {
+            tracing::info!(
+                "🎯 找到目标进程: {} (PID: {}, 匹配依据: {})",
+                process_name,
+                pid,
+                matched_by
+            );
             tracing::info!("📝 命令行: {}", process_cmd);
 
             // 尝试终止进程
@@ -38,6 +50,13 @@ pub fn kill_antigravity_processes() -> Result<String, String> {
         }
     }
 
+    // Flatpak 沙盒中的进程需要通过 flatpak kill 关闭
+    if std::env::consts::OS == "linux" {
+        for app_id in super::flatpak::kill_flatpak_instances() {
+            killed_processes.push(format!("{} (Flatpak)", app_id));
+        }
+    }
+
     if killed_processes.is_empty() {
         tracing::info!("ℹ️ 未找到匹配的 Antigravity 进程");
         tracing::info!("🔍 搜索的进程模式: {:?}", process_patterns);
@@ -57,16 +76,17 @@ pub fn is_antigravity_running() -> bool {
     system.refresh_all();
 
     let process_patterns = get_antigravity_process_patterns();
+    let install_dirs = known_install_dirs();
 
     for (pid, process) in system.processes() {
-        let process_name = process.name();
-        let process_cmd = process.cmd().join(" ");
-
-        if matches_antigravity_process(process_name, &process_cmd, &process_patterns) {
+        if let Some(matched_by) =
+            match_antigravity_process(process, &process_patterns, &install_dirs)
+        {
             tracing::debug!(
-                "✅ 发现运行中的 Antigravity 进程: {} (PID: {})",
-                process_name,
-                pid
+                "✅ 发现运行中的 Antigravity 进程: {} (PID: {}, 匹配依据: {})",
+                process.name(),
+                pid,
+                matched_by
             );
             return true;
         }
@@ -110,32 +130,208 @@ fn get_antigravity_process_patterns() -> Vec<ProcessPattern> {
     }
 }
 
-/// 检查进程是否匹配 Antigravity 模式
-fn matches_antigravity_process(
-    process_name: &str,
-    process_cmd: &str,
+/// 匹配到的 Antigravity 进程信息
+#[derive(Debug, Serialize)]
+pub struct AntigravityProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    /// 可执行文件路径（无权限读取时为 None）
+    pub exe: Option<String>,
+    pub cmd: String,
+    /// 匹配依据：exe_path / name:<进程名> / cmd:<命令行片段>
+    pub matched_by: String,
+}
+
+/// 列出所有被识别为 Antigravity 的进程及其匹配依据
+pub fn list_antigravity_processes() -> Vec<AntigravityProcessInfo> {
+    let mut system = sysinfo::System::new_all();
+    system.refresh_all();
+
+    let process_patterns = get_antigravity_process_patterns();
+    let install_dirs = known_install_dirs();
+
+    let mut processes: Vec<AntigravityProcessInfo> = system
+        .processes()
+        .iter()
+        .filter_map(|(pid, process)| {
+            let matched_by = match_antigravity_process(process, &process_patterns, &install_dirs)?;
+            Some(AntigravityProcessInfo {
+                pid: pid.as_u32(),
+                name: process.name().to_string(),
+                exe: process.exe().map(|p| p.to_string_lossy().to_string()),
+                cmd: process.cmd().join(" "),
+                matched_by,
+            })
+        })
+        .collect();
+    processes.sort_by_key(|p| p.pid);
+    processes
+}
+
+/// 清理残留进程的结果
+#[derive(Debug, Default, Serialize)]
+pub struct KillStaleResult {
+    /// 已终止的进程
+    pub killed: Vec<AntigravityProcessInfo>,
+    /// 保留的进程（最新实例及其子进程）
+    pub kept: Vec<AntigravityProcessInfo>,
+    /// 终止失败的进程
+    pub failed: Vec<AntigravityProcessInfo>,
+}
+
+/// 终止残留的 Antigravity 进程
+///
+/// `keep_newest` 为 true 时保留最新启动的实例（父进程不是 Antigravity 的进程视为实例主进程）
+/// 及其全部子进程，终止其余实例和僵尸进程；为 false 时终止所有匹配的进程
+pub fn kill_stale_antigravity_processes(keep_newest: bool) -> KillStaleResult {
+    let mut system = sysinfo::System::new_all();
+    system.refresh_all();
+
+    let process_patterns = get_antigravity_process_patterns();
+    let install_dirs = known_install_dirs();
+
+    let matched: Vec<(sysinfo::Pid, String)> = system
+        .processes()
+        .iter()
+        .filter_map(|(pid, process)| {
+            match_antigravity_process(process, &process_patterns, &install_dirs)
+                .map(|matched_by| (*pid, matched_by))
+        })
+        .collect();
+    let is_matched = |pid: sysinfo::Pid| matched.iter().any(|(p, _)| *p == pid);
+
+    // 向上追溯到父进程不再是 Antigravity 的进程，即所属实例的主进程
+    let instance_root = |mut pid: sysinfo::Pid| {
+        while let Some(parent) = system.process(pid).and_then(|p| p.parent()) {
+            if parent == pid || !is_matched(parent) {
+                break;
+            }
+            pid = parent;
+        }
+        pid
+    };
+
+    let newest_root = if keep_newest {
+        matched
+            .iter()
+            .map(|(pid, _)| instance_root(*pid))
+            .filter(|root| {
+                system
+                    .process(*root)
+                    .is_some_and(|p| p.status() != sysinfo::ProcessStatus::Zombie)
+            })
+            .max_by_key(|root| system.process(*root).map(|p| p.start_time()))
+    } else {
+        None
+    };
+
+    let mut result = KillStaleResult::default();
+    for (pid, matched_by) in &matched {
+        let Some(process) = system.process(*pid) else {
+            continue;
+        };
+        let info = AntigravityProcessInfo {
+            pid: pid.as_u32(),
+            name: process.name().to_string(),
+            exe: process.exe().map(|p| p.to_string_lossy().to_string()),
+            cmd: process.cmd().join(" "),
+            matched_by: matched_by.clone(),
+        };
+
+        let is_zombie = process.status() == sysinfo::ProcessStatus::Zombie;
+        if !is_zombie && newest_root.is_some_and(|root| instance_root(*pid) == root) {
+            result.kept.push(info);
+        } else if process.kill() {
+            tracing::info!("✅ 已终止残留进程: {} (PID: {})", info.name, info.pid);
+            result.killed.push(info);
+        } else {
+            tracing::warn!("⚠️ 终止残留进程失败: {} (PID: {})", info.name, info.pid);
+            result.failed.push(info);
+        }
+    }
+
+    for list in [&mut result.killed, &mut result.kept, &mut result.failed] {
+        list.sort_by_key(|p| p.pid);
+    }
+    result
+}
+
+/// 已知的 Antigravity 安装目录（可执行文件所在目录，macOS 为 .app bundle），已解析符号链接
+///
+/// 来源与启动时一致：环境变量、自定义路径、额外搜索路径及内置路径中实际存在的文件
+fn known_install_dirs() -> Vec<PathBuf> {
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    if let Some(path) = crate::path_utils::AppPaths::env_executable_path() {
+        candidates.push(path);
+    }
+    if let Ok(Some(custom)) = crate::antigravity::path_config::get_custom_executable_path() {
+        candidates.push(PathBuf::from(custom));
+    }
+    candidates.extend(crate::path_utils::AppPaths::antigravity_executable_paths());
+
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for path in candidates {
+        let Ok(path) = std::fs::canonicalize(&path) else {
+            continue;
+        };
+        let install_dir = path
+            .ancestors()
+            .find(|p| p.extension().is_some_and(|ext| ext == "app"))
+            .map(Path::to_path_buf)
+            .or_else(|| path.parent().map(Path::to_path_buf));
+        if let Some(dir) = install_dir {
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+    }
+    dirs
+}
+
+/// 检查进程是否为 Antigravity，返回匹配依据
+///
+/// - 可执行文件位于已知安装目录下：直接匹配（可识别改名后的程序）
+/// - 进程名精确匹配：若已知安装目录且能读取可执行文件路径，要求路径位于安装目录下，避免同名进程误判
+/// - 命令行包含指定路径片段：直接匹配
+fn match_antigravity_process(
+    process: &sysinfo::Process,
     patterns: &[ProcessPattern],
-) -> bool {
-    let mut matched = false;
+    install_dirs: &[PathBuf],
+) -> Option<String> {
+    let exe = process.exe();
+    if let Some(exe) = exe {
+        if install_dirs.iter().any(|dir| exe.starts_with(dir)) {
+            return Some("exe_path".to_string());
+        }
+    }
+
+    let process_name = process.name();
+    let process_cmd = process.cmd().join(" ");
     for pattern in patterns {
         match pattern {
             ProcessPattern::ExactName(name) => {
-                if process_name == *name {
-                    tracing::debug!("✅ 精确匹配进程名: {}", name);
-                    tracing::info!("🎯 匹配模式: ProcessPattern::ExactName(\"{}\")", name);
-                    matched = true;
+                if process_name != *name {
+                    continue;
+                }
+                if let (Some(exe), false) = (exe, install_dirs.is_empty()) {
+                    tracing::debug!(
+                        "⏭️ 进程名匹配但可执行文件不在已知安装目录下，跳过: {}",
+                        exe.display()
+                    );
+                    continue;
                 }
+                tracing::debug!("✅ 精确匹配进程名: {}", name);
+                return Some(format!("name:{}", name));
             }
             ProcessPattern::CmdContains(text) => {
                 if process_cmd.contains(text) {
                     tracing::debug!("✅ 命令行包含匹配: {}", text);
-                    tracing::info!("🎯 匹配模式: ProcessPattern::CmdContains(\"{}\")", text);
-                    matched = true;
+                    return Some(format!("cmd:{}", text));
                 }
             }
         }
     }
-    matched
+    None
 }
 
 /// 进程匹配模式
@@ -0,0 +1,88 @@
+//! Antigravity 进程状态监控模块
+//! 定时检查 Antigravity 是否在运行，仅在状态变化时推送 antigravity-process-state 事件
+
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter};
+use tokio::time::{interval, Duration};
+use tracing::{error, info};
+
+/// 轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// 进程状态事件数据
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessStateEvent {
+    pub running: bool,
+}
+
+/// 进程状态监控器
+pub struct ProcessMonitor {
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl ProcessMonitor {
+    /// 创建新的进程监控器（不会自动启动）
+    pub fn new() -> Self {
+        Self {
+            task: Mutex::new(None),
+        }
+    }
+
+    /// 启动进程监控；已在运行时不重复启动
+    pub fn start(&self, app_handle: AppHandle) {
+        let mut task = self.task.lock().unwrap();
+        if task.is_some() {
+            return;
+        }
+        info!(target: "process_monitor", "🔧 启动 Antigravity 进程状态监控");
+
+        *task = Some(tauri::async_runtime::spawn(async move {
+            let mut ticker = interval(POLL_INTERVAL);
+            let mut last_running: Option<bool> = None;
+
+            loop {
+                ticker.tick().await;
+
+                // 进程枚举较慢，放到阻塞线程中执行
+                let running = match tokio::task::spawn_blocking(
+                    crate::platform::is_antigravity_running,
+                )
+                .await
+                {
+                    Ok(running) => running,
+                    Err(e) => {
+                        error!(target: "process_monitor", error = %e, "检查进程状态失败");
+                        continue;
+                    }
+                };
+
+                // 首次只记录状态，之后仅在变化时推送
+                if last_running.is_some_and(|last| last != running) {
+                    info!(target: "process_monitor", running, "📢 Antigravity 进程状态变化");
+                    if let Err(e) =
+                        app_handle.emit("antigravity-process-state", ProcessStateEvent { running })
+                    {
+                        error!(target: "process_monitor", error = %e, "推送进程状态事件失败");
+                    }
+                }
+                last_running = Some(running);
+            }
+        }));
+    }
+
+    /// 停止进程监控
+    pub fn stop(&self) {
+        if let Some(task) = self.task.lock().unwrap().take() {
+            info!(target: "process_monitor", "⏹️ 停止 Antigravity 进程状态监控");
+            task.abort();
+        }
+    }
+}
+
+impl Default for ProcessMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,6 +1,7 @@
-use crate::{app_settings, db_monitor, system_tray, window};
+use crate::{accounts_watcher, app_settings, db_monitor, process_monitor, system_tray, window};
 use std::sync::Arc;
 use tauri::{App, Manager};
+use tauri_plugin_autostart::ManagerExt;
 
 pub fn init(app: &mut App) -> std::result::Result<(), Box<dyn std::error::Error>> {
     tracing::info!(target: "app::setup", "开始应用程序设置");
@@ -9,6 +10,56 @@ pub fn init(app: &mut App) -> std::result::Result<(), Box<dyn std::error::Error>
     let app_handle = app.handle();
     app.manage(app_settings::AppSettingsManager::new(app_handle));
 
+    // 启动时按设置自动清理旧备份
+    let settings = app
+        .state::<app_settings::AppSettingsManager>()
+        .get_settings();
+    if settings.auto_prune_enabled && !settings.read_only {
+        match crate::antigravity::prune::prune_account_backups(settings.auto_prune_keep_latest) {
+            Ok(result) => tracing::info!(
+                target: "app::setup::prune",
+                pruned = result.pruned.len(),
+                "启动时备份清理完成"
+            ),
+            Err(e) => {
+                tracing::error!(target: "app::setup::prune", error = %e, "启动时备份清理失败")
+            }
+        }
+    }
+
+    // 按设置重新检测 Antigravity 路径，清除失效的自定义路径
+    if settings.revalidate_paths_on_startup {
+        if let Err(e) = crate::antigravity::path_config::revalidate_paths() {
+            tracing::error!(target: "app::setup::paths", error = %e, "启动时重新检测路径失败");
+        }
+    }
+
+    // 按设置同步系统自启动注册状态
+    let autolaunch = app.autolaunch();
+    match autolaunch.is_enabled() {
+        Ok(registered) if registered != settings.autostart_enabled => {
+            let result = if settings.autostart_enabled {
+                autolaunch.enable()
+            } else {
+                autolaunch.disable()
+            };
+            match result {
+                Ok(()) => tracing::info!(
+                    target: "app::setup::autostart",
+                    enabled = settings.autostart_enabled,
+                    "已同步系统自启动状态"
+                ),
+                Err(e) => {
+                    tracing::error!(target: "app::setup::autostart", error = %e, "同步系统自启动状态失败")
+                }
+            }
+        }
+        Ok(_) => {}
+        Err(e) => {
+            tracing::warn!(target: "app::setup::autostart", error = %e, "读取系统自启动状态失败")
+        }
+    }
+
     // 初始化系统托盘管理器
     app.manage(system_tray::SystemTrayManager::new());
 
@@ -32,6 +83,24 @@ pub fn init(app: &mut App) -> std::result::Result<(), Box<dyn std::error::Error>
 
     tracing::info!(target: "app::setup::db_monitor", "数据库监控器初始化完成");
 
+    // 初始化进程状态监控器，按设置决定是否启动
+    let process_monitor = process_monitor::ProcessMonitor::new();
+    if app
+        .state::<app_settings::AppSettingsManager>()
+        .get_settings()
+        .process_watch_enabled
+    {
+        process_monitor.start(app.handle().clone());
+    }
+    app.manage(process_monitor);
+
+    // 监听账户备份目录，外部修改备份时通知前端刷新列表
+    let accounts_watcher = accounts_watcher::AccountsWatcher::new();
+    if let Err(e) = accounts_watcher.start(app.handle().clone()) {
+        tracing::warn!(target: "app::setup", error = %e, "账户目录监听启动失败，列表需要手动刷新");
+    }
+    app.manage(accounts_watcher);
+
     // 初始化窗口事件处理器
     if let Err(e) = window::init_window_event_handler(app) {
         tracing::error!(target: "app::setup::window", error = %e, "窗口事件处理器初始化失败");
@@ -85,6 +154,12 @@ pub fn init(app: &mut App) -> std::result::Result<(), Box<dyn std::error::Error>
             // 等待1.5秒，确保窗口状态恢复和其他初始化都完成
             tokio::time::sleep(tokio::time::Duration::from_millis(1500)).await;
 
+            // 等待期间用户已主动打开窗口时不再隐藏，避免窗口刚出现又消失
+            if window::is_silent_start_hide_cancelled() {
+                tracing::info!(target: "app::setup::silent_start", "用户已主动显示窗口，跳过静默启动隐藏");
+                return;
+            }
+
             tracing::debug!(target: "app::setup::silent_start", "执行静默启动窗口隐藏操作");
 
             if let Some(main_window) = app_handle_for_silent.get_webview_window("main") {
@@ -23,6 +23,9 @@ pub struct AntigravityAccount {
     pub user_settings: String, // 编码后的用户设置
     pub created_at: String,
     pub last_switched: String,
+    /// 是否为 Antigravity 当前登录的账户
+    #[serde(default)]
+    pub is_current: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
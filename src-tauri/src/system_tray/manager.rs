@@ -87,6 +87,7 @@ impl SystemTrayManager {
 
     /// 从托盘恢复窗口
     pub fn restore_from_tray(&self, app_handle: &AppHandle) -> Result<(), String> {
+        crate::window::cancel_silent_start_hide();
         if let Some(window) = app_handle.get_webview_window("main") {
             window.show().map_err(|e| e.to_string())?;
             window.set_focus().map_err(|e| e.to_string())?;
@@ -51,6 +51,7 @@ fn handle_tray_menu_event(app: &AppHandle, event: tauri::menu::MenuEvent) {
 
     match event.id.0.as_str() {
         "show_main" => {
+            crate::window::cancel_silent_start_hide();
             if let Some(window) = app.get_webview_window("main") {
                 let _ = window.unminimize();
                 let _ = window.show();
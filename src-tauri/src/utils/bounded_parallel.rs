@@ -0,0 +1,96 @@
+//! 有界并发执行阻塞任务
+//! 批量读取备份文件等场景使用，避免一次性打开全部文件，也不阻塞异步运行时
+
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// 在阻塞线程池中对每个元素执行 `f`，同时最多执行 `limit` 个，结果按输入顺序返回
+///
+/// 单个任务 panic 时对应位置返回 `Err`，不影响其他任务
+pub async fn map_blocking<T, R, F>(items: Vec<T>, limit: usize, f: F) -> Vec<Result<R, String>>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> R + Send + Sync + 'static,
+{
+    let count = items.len();
+    let semaphore = Arc::new(Semaphore::new(limit.max(1)));
+    let f = Arc::new(f);
+
+    let mut tasks = JoinSet::new();
+    for (index, item) in items.into_iter().enumerate() {
+        let semaphore = semaphore.clone();
+        let f = f.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let result = tokio::task::spawn_blocking(move || f(item))
+                .await
+                .map_err(|e| format!("任务执行失败: {}", e));
+            (index, result)
+        });
+    }
+
+    let mut results: Vec<Option<Result<R, String>>> = (0..count).map(|_| None).collect();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok((index, result)) => results[index] = Some(result),
+            Err(e) => tracing::error!(error = %e, "并发任务失败"),
+        }
+    }
+    results
+        .into_iter()
+        .map(|result| result.unwrap_or_else(|| Err("任务未完成".to_string())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::map_blocking;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn map_blocking_keeps_input_order() {
+        let results = map_blocking((0..20).collect(), 4, |n: u64| {
+            std::thread::sleep(std::time::Duration::from_millis(20 - n));
+            n * 2
+        })
+        .await;
+        let values: Vec<u64> = results.into_iter().map(Result::unwrap).collect();
+        assert_eq!(values, (0..20).map(|n| n * 2).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn map_blocking_limits_concurrency() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let results = map_blocking((0..16).collect(), 3, {
+            let running = running.clone();
+            let peak = peak.clone();
+            move |_: usize| {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                std::thread::sleep(std::time::Duration::from_millis(10));
+                running.fetch_sub(1, Ordering::SeqCst);
+            }
+        })
+        .await;
+        assert_eq!(results.len(), 16);
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+    }
+
+    #[tokio::test]
+    async fn map_blocking_isolates_panics() {
+        let results = map_blocking(vec![1, 2, 3], 2, |n: i32| {
+            if n == 2 {
+                panic!("boom");
+            }
+            n
+        })
+        .await;
+        assert_eq!(results[0], Ok(1));
+        assert!(results[1].is_err());
+        assert_eq!(results[2], Ok(3));
+    }
+}
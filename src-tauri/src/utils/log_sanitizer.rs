@@ -156,3 +156,34 @@ pub fn sanitize_log_message(message: &str) -> String {
     let sanitizer = LogSanitizer::new();
     sanitizer.sanitize(message)
 }
+
+/// 递归替换 JSON 中的敏感字段（apiKey、accessToken、idToken 等）为 `***`
+///
+/// 只按字段名匹配，邮箱、名称、头像地址等字段保持原样
+pub fn redact_json_secrets(value: &mut serde_json::Value) {
+    let secret_key_regex =
+        Regex::new(r"(?i)(api[-_]?key|token|secret|password|credential)").unwrap();
+    redact_json_secrets_with(value, &secret_key_regex);
+}
+
+fn redact_json_secrets_with(value: &mut serde_json::Value, secret_key_regex: &Regex) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if secret_key_regex.is_match(key) && !field.is_object() && !field.is_array() {
+                    if !field.is_null() {
+                        *field = serde_json::Value::String("***".to_string());
+                    }
+                } else {
+                    redact_json_secrets_with(field, secret_key_regex);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                redact_json_secrets_with(item, secret_key_regex);
+            }
+        }
+        _ => {}
+    }
+}
@@ -1,5 +1,6 @@
 //! 工具模块
 
+pub mod bounded_parallel;
 pub mod log_decorator;
 pub mod log_sanitizer;
 pub mod sanitizing_layer;
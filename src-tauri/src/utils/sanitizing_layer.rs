@@ -1,12 +1,14 @@
 /// 自定义日志写入器，仅对文件输出进行脱敏
 /// 控制台输出保持原始内容
 use std::io::{self, Write};
-use tracing_appender::rolling::RollingFileAppender;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
 use tracing_subscriber::fmt::writer::MakeWriter;
 
 /// 脱敏文件写入器
 pub struct SanitizingFileWriter {
     appender: RollingFileAppender,
+    /// JSON 日志保留的文件数（None 表示普通文本日志）
+    json_max_files: Option<usize>,
 }
 
 impl SanitizingFileWriter {
@@ -17,7 +19,29 @@ impl SanitizingFileWriter {
         // 文件名格式: antigravity-agent.2024-01-15.log
         let appender = tracing_appender::rolling::daily(&log_dir, "antigravity-agent");
 
-        Ok(Self { appender })
+        Ok(Self {
+            appender,
+            json_max_files: None,
+        })
+    }
+
+    /// 创建 JSON 行日志写入器，按天滚动并只保留最近 `max_files` 个文件
+    /// 文件名格式: antigravity-agent.2024-01-15.jsonl
+    pub fn json(max_files: usize) -> io::Result<Self> {
+        let log_dir = crate::directories::get_log_directory();
+
+        let appender = RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix("antigravity-agent")
+            .filename_suffix("jsonl")
+            .max_log_files(max_files.max(1))
+            .build(&log_dir)
+            .map_err(io::Error::other)?;
+
+        Ok(Self {
+            appender,
+            json_max_files: Some(max_files),
+        })
     }
 }
 
@@ -39,6 +63,10 @@ impl<'a> MakeWriter<'a> for SanitizingFileWriter {
     type Writer = SanitizingFileWriter;
 
     fn make_writer(&'a self) -> Self::Writer {
-        SanitizingFileWriter::new().expect("Failed to create sanitizing file writer")
+        match self.json_max_files {
+            Some(max_files) => SanitizingFileWriter::json(max_files),
+            None => SanitizingFileWriter::new(),
+        }
+        .expect("Failed to create sanitizing file writer")
     }
 }
@@ -1,6 +1,38 @@
 //! Tracing 配置模块
 //! 提供统一的结构化日志配置和初始化
 
+use std::future::Future;
+use std::sync::OnceLock;
+use tracing::Instrument;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+/// 控制台与文本日志过滤器的重载句柄，在 init_tracing 中注册
+static LOG_FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// 注册可重载的日志过滤器句柄
+pub fn register_log_filter_handle(handle: reload::Handle<EnvFilter, Registry>) {
+    let _ = LOG_FILTER_HANDLE.set(handle);
+}
+
+/// 获取当前日志过滤指令
+pub fn current_log_directive() -> Result<String, String> {
+    LOG_FILTER_HANDLE
+        .get()
+        .ok_or_else(|| "日志过滤器尚未初始化".to_string())?
+        .with_current(|filter| filter.to_string())
+        .map_err(|e| format!("读取日志过滤器失败: {}", e))
+}
+
+/// 校验并替换日志过滤指令，立即生效（重启后恢复默认）
+pub fn set_log_directive(directive: &str) -> Result<(), String> {
+    let filter = EnvFilter::try_new(directive).map_err(|e| format!("无效的日志过滤指令: {}", e))?;
+    LOG_FILTER_HANDLE
+        .get()
+        .ok_or_else(|| "日志过滤器尚未初始化".to_string())?
+        .reload(filter)
+        .map_err(|e| format!("更新日志过滤器失败: {}", e))
+}
+
 /// 记录系统启动信息
 pub fn log_system_info() {
     tracing::info!(
@@ -64,3 +96,17 @@ pub fn log_database_operation(operation: &str, table: Option<&str>, success: boo
         }
     }
 }
+
+/// 在 `step` span 中执行一个异步步骤，并在 span 关闭前记录 `duration_ms`
+///
+/// 多个步骤嵌套在命令 span 下，支持 span 的订阅者可据此生成耗时瀑布图
+pub async fn timed_step<F, T>(name: &'static str, fut: F) -> T
+where
+    F: Future<Output = T>,
+{
+    let span = tracing::info_span!("step", step = name, duration_ms = tracing::field::Empty);
+    let start_time = std::time::Instant::now();
+    let result = fut.instrument(span.clone()).await;
+    span.record("duration_ms", start_time.elapsed().as_millis() as u64);
+    result
+}
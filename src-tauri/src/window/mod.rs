@@ -2,7 +2,9 @@
 //! Handles window state persistence and event handling
 
 pub mod event_handler;
+pub mod silent_start;
 pub mod state_manager;
 
 // Re-export commonly used functions
 pub use event_handler::init_window_event_handler;
+pub use silent_start::{cancel_silent_start_hide, is_silent_start_hide_cancelled};
@@ -0,0 +1,21 @@
+// 静默启动隐藏控制
+// 静默启动会在启动后延迟隐藏主窗口；如果用户在此之前主动打开了窗口，则取消隐藏
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 用户是否已主动显示/操作过主窗口
+///
+/// 不使用窗口获得焦点事件判断：新建窗口启动时通常会自动获得焦点，无法区分是否为用户操作
+static SILENT_HIDE_CANCELLED: AtomicBool = AtomicBool::new(false);
+
+/// 取消尚未执行的静默启动隐藏（托盘显示窗口、前端检测到用户交互时调用）
+pub fn cancel_silent_start_hide() {
+    if !SILENT_HIDE_CANCELLED.swap(true, Ordering::SeqCst) {
+        tracing::debug!(target: "app::setup::silent_start", "用户已主动显示窗口，取消静默启动隐藏");
+    }
+}
+
+/// 静默启动隐藏是否已被取消
+pub fn is_silent_start_hide_cancelled() -> bool {
+    SILENT_HIDE_CANCELLED.load(Ordering::SeqCst)
+}
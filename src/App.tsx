@@ -8,6 +8,7 @@ import AppDock from './components/app/AppDock.tsx';
 import {AppContent} from "@/components/app/AppContent.tsx";
 import {AppLoader} from "@/components/app/AppLoader.tsx";
 import {PlatformCommands} from "@/commands/PlatformCommands.ts";
+import {SettingsCommands} from "@/commands/SettingsCommands.ts";
 
 function App() {
   // ========== 应用状态 ==========
@@ -46,6 +47,20 @@ function App() {
     return () => antigravityIsRunning.stop();
   }, []);
 
+  // 用户在静默启动隐藏前操作了窗口时，取消隐藏
+  useEffect(() => {
+    const cancelSilentHide = () => {
+      SettingsCommands.cancelSilentStartHide().catch(() => {});
+    };
+    window.addEventListener('pointerdown', cancelSilentHide, {once: true});
+    window.addEventListener('keydown', cancelSilentHide, {once: true});
+
+    return () => {
+      window.removeEventListener('pointerdown', cancelSilentHide);
+      window.removeEventListener('keydown', cancelSilentHide);
+    };
+  }, []);
+
   // ========== 初始化启动流程 ==========
   const initializeApp = async () => {
     try {
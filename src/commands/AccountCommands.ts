@@ -1,5 +1,6 @@
 import { invoke } from '@tauri-apps/api/core';
-import {AntigravityAccount} from "@/commands/types/account.types.ts";
+import type {TargetApp} from "@/commands/types/platform.types.ts";
+import {AccountListResult, AccountMeta, AntigravityAccount, ClearDataResult, DbCompatibility, DbValidation, DbValue, ExternalBackupResult, InstallBackupResult, PartialRestoreResult, RestoreCloseCheck, RestorePreviewEntry, SwitchVerifyResult, CompactResult, SwitchHistoryEntry} from "@/commands/types/account.types.ts";
 
 /**
  * Antigravity 账户管理命令
@@ -13,6 +14,42 @@ export class AccountCommands {
     return invoke('get_current_antigravity_account_info');
   }
 
+  /**
+   * 获取当前登录的账户信息（令牌等敏感字段已替换为 ***，附带 db_path）
+   * @returns 脱敏后的账户信息
+   */
+  static async getCurrentAntigravityAccountRedacted(): Promise<AntigravityAccount & { db_path?: string }> {
+    return invoke('get_current_antigravity_account_info_redacted');
+  }
+
+  /**
+   * 获取当前登录账户的邮箱（轻量查询，不会因 Antigravity 未运行而报错）
+   * @returns 邮箱，未登录或数据库不可用时为 null
+   */
+  static async getCurrentAccountEmail(): Promise<string | null> {
+    return invoke('get_current_account_email');
+  }
+
+  /**
+   * 列出 Antigravity 数据库中的所有键（不含值）
+   * @param targetApp 目标应用，默认 antigravity
+   * @returns 键名列表
+   */
+  static async listDbKeys(targetApp?: TargetApp): Promise<string[]> {
+    return invoke('list_antigravity_db_keys', { targetApp });
+  }
+
+  /**
+   * 读取 Antigravity 数据库中单个键的值
+   * @param key 键名
+   * @param maxBytes 返回的最大字节数（默认 64KB），超出部分截断
+   * @param targetApp 目标应用，默认 antigravity
+   * @returns 键值及大小、截断信息
+   */
+  static async getDbValue(key: string, maxBytes?: number, targetApp?: TargetApp): Promise<DbValue> {
+    return invoke('get_antigravity_db_value', { key, maxBytes, targetApp });
+  }
+
   /**
    * 获取所有已备份的账户列表
    * @returns 账户列表
@@ -21,6 +58,33 @@ export class AccountCommands {
     return invoke('get_antigravity_accounts');
   }
 
+  /**
+   * 获取所有已备份的账户列表，并附带无法解析的备份文件说明
+   * @returns 可用的账户列表及损坏备份的警告
+   */
+  static async getAntigravityAccountsWithWarnings(): Promise<AccountListResult> {
+    return invoke('get_antigravity_accounts_with_warnings');
+  }
+
+  /**
+   * 按邮箱、名称或标签搜索账户（不区分大小写）
+   * @param query 搜索词，为空时返回全部账户
+   * @returns 匹配的账户列表
+   */
+  static async searchAccounts(query: string): Promise<AntigravityAccount[]> {
+    return invoke('search_accounts', { query });
+  }
+
+  /**
+   * 获取账户头像的本地缓存路径（首次调用时下载并缓存）
+   * @param accountName 账户名（邮箱）
+   * @param profileUrl 头像地址（可选，未传时从备份中读取）
+   * @returns 本地文件路径，无头像或离线时为 null（可配合 convertFileSrc 显示）
+   */
+  static async getAccountAvatar(accountName: string, profileUrl?: string): Promise<string | null> {
+    return invoke('get_account_avatar', { accountName, profileUrl });
+  }
+
   /**
    * 备份当前登录的账户
    * @returns 备份结果消息
@@ -29,20 +93,188 @@ export class AccountCommands {
     return invoke('save_antigravity_current_account');
   }
 
+  /**
+   * 一次性备份所有检测到的 Antigravity 安装中已登录的账户（未登录的安装会被跳过）
+   * @returns 每个安装的备份结果
+   */
+  static async backupAllInstalls(): Promise<InstallBackupResult[]> {
+    return invoke('backup_all_installs');
+  }
+
+  /**
+   * 备份当前登录的账户到指定路径
+   * @param destPath 目标文件路径（父目录必须存在且可写）
+   * @param dedup 目标文件内容相同时是否跳过写入
+   * @returns 写入路径、邮箱及是否实际写入
+   */
+  static async backupCurrentAccountTo(destPath: string, dedup?: boolean): Promise<ExternalBackupResult> {
+    return invoke('backup_current_account_to', { destPath, dedup });
+  }
+
+  /**
+   * 从外部 state.vscdb 文件导入账户（如无法再启动的旧安装）
+   * @param dbPath state.vscdb 文件路径
+   * @param label 备份名称，留空时使用账户邮箱
+   * @returns 导入结果消息
+   */
+  static async importAccountFromDb(dbPath: string, label: string): Promise<string> {
+    return invoke('import_account_from_db', { dbPath, label });
+  }
+
+  /**
+   * 校验 state.vscdb 是否为有效的 Antigravity 数据库（只读，不做修改）
+   * @param path state.vscdb 文件路径
+   * @returns 校验结果
+   */
+  static async validateAntigravityDb(path: string): Promise<DbValidation> {
+    return invoke('validate_antigravity_db', { path });
+  }
+
+  /**
+   * 检查当前 Antigravity 数据库结构是否为预期的 ItemTable(key, value)
+   * @returns 是否兼容、数据库中的表及说明
+   */
+  static async checkDbCompatibility(): Promise<DbCompatibility> {
+    return invoke('check_db_compatibility');
+  }
+
   /**
    * 切换到指定账户（完整流程：关闭进程 → 恢复数据 → 重启）
+   * 目标账户就是当前登录账户时只原地刷新，不关闭/重启 Antigravity
    * @param accountName 账户名（邮箱）
+   * @param force 是否允许切换到测试账户
+   * @param forceFullSwitch 为 true 时即使是当前账户也走完整流程
+   * @param allowWhileRunning 为 true 时允许在 Antigravity 运行中原地刷新（否则改走完整流程）
    * @returns 切换结果消息
    */
-  static async switchToAntigravityAccount(accountName: string): Promise<string> {
-    return invoke('switch_to_antigravity_account', { accountName: accountName });
+  static async switchToAntigravityAccount(accountName: string, force?: boolean, forceFullSwitch?: boolean, allowWhileRunning?: boolean): Promise<string> {
+    return invoke('switch_to_antigravity_account', { accountName: accountName, force, forceFullSwitch, allowWhileRunning });
+  }
+
+  /**
+   * 查询恢复账户前是否需要关闭 Antigravity（运行中恢复的数据可能在其退出时被覆盖）
+   * @returns 运行状态及是否需要关闭
+   */
+  static async restoreRequiresClose(): Promise<RestoreCloseCheck> {
+    return invoke('restore_requires_close');
+  }
+
+  /**
+   * 切换到指定账户，并在 Antigravity 启动后确认登录邮箱与目标一致
+   * @param accountName 账户名（邮箱）
+   * @param force 是否允许切换到测试账户
+   * @param timeoutSecs 验证超时时间（秒），默认 30
+   * @returns 切换与验证结果
+   */
+  static async switchAndVerify(accountName: string, force?: boolean, timeoutSecs?: number): Promise<SwitchVerifyResult> {
+    return invoke('switch_and_verify', { accountName, force, timeoutSecs });
+  }
+
+  /**
+   * 获取最近的账户切换历史（最新的在前）
+   * @param limit 最多返回的条数，默认 50
+   * @returns 切换历史
+   */
+  static async getSwitchHistory(limit?: number): Promise<SwitchHistoryEntry[]> {
+    return invoke('get_switch_history', { limit });
+  }
+
+  /**
+   * 创建测试账户备份（无需真实登录）
+   * @param email 测试账户邮箱
+   * @returns 创建结果消息
+   */
+  static async createTestAccount(email: string): Promise<string> {
+    return invoke('create_test_account', { email });
+  }
+
+  /**
+   * 创建登出状态备份，恢复后 Antigravity 处于未登录状态（其他设置保持不变）
+   * @param name 备份名称
+   * @returns 创建结果消息
+   */
+  static async createLoggedOutBackup(name: string): Promise<string> {
+    return invoke('create_logged_out_backup', { name });
+  }
+
+  /**
+   * 只恢复备份中指定的键，其余数据库内容保持不变
+   * @param accountName 账户名（邮箱）
+   * @param keys 要恢复的数据库键
+   * @param allowWhileRunning 为 true 时允许在 Antigravity 运行中恢复
+   * @returns 已写入和被跳过的键
+   */
+  static async restoreAccountPartial(accountName: string, keys: string[], allowWhileRunning?: boolean): Promise<PartialRestoreResult> {
+    return invoke('restore_account_partial', { accountName, keys, allowWhileRunning });
+  }
+
+  /**
+   * 列出备份中存在、但恢复时不会写回数据库的键
+   * @param accountName 账户名（邮箱）
+   * @returns 不会被恢复的键名
+   */
+  static async listUnrestoredKeys(accountName: string): Promise<string[]> {
+    return invoke('list_unrestored_keys', { accountName });
+  }
+
+  /**
+   * 获取账户备份目录所在磁盘的可用空间
+   * @returns 可用字节数
+   */
+  static async getAccountsVolumeFreeBytes(): Promise<number> {
+    return invoke('get_accounts_volume_free_bytes');
+  }
+
+  /**
+   * 设置账户备注与标签（重新备份同一账户时保留）
+   * @param name 账户名（备份名称）
+   * @param notes 备注
+   * @param tags 标签
+   * @returns 规范化后保存的备注与标签
+   */
+  static async setAccountMeta(name: string, notes: string, tags: string[]): Promise<AccountMeta> {
+    return invoke('set_account_meta', { name, notes, tags });
+  }
+
+  /**
+   * 预览恢复指定账户将修改的数据库键（不写入任何数据）
+   * @param accountName 账户名（邮箱）
+   * @returns 每个数据库键的变更情况
+   */
+  static async previewRestore(accountName: string): Promise<RestorePreviewEntry[]> {
+    return invoke('preview_restore', { accountName });
+  }
+
+  /**
+   * 是否处于只读模式（只能通过编辑配置文件开启）
+   * @returns 只读模式下为 true，可用于隐藏切换、清除、删除等按钮
+   */
+  static async isReadOnly(): Promise<boolean> {
+    return invoke('is_read_only');
+  }
+
+  /**
+   * 是否有账户操作（切换、登录新账户、清除数据）正在进行
+   * @returns 进行中时为 true，可用于禁用切换按钮
+   */
+  static async isAccountOperationInProgress(): Promise<boolean> {
+    return invoke('is_account_operation_in_progress');
   }
 
   /**
    * 清除所有 Antigravity 数据（注销）
-   * @returns 清除结果消息
+   * @returns 清除明细（删除/写入的键、受影响行数、是否删除备份库）及汇总消息
    */
-  static async clearAllData(): Promise<string> {
+  static async clearAllData(): Promise<ClearDataResult> {
     return invoke('clear_all_antigravity_data');
   }
+
+  /**
+   * 压缩 Antigravity 状态数据库（VACUUM），需先关闭 Antigravity
+   * @param confirm 必须为 true，VACUUM 期间会锁定数据库文件
+   * @returns 压缩前后的文件大小
+   */
+  static async compactDb(confirm: boolean): Promise<CompactResult> {
+    return invoke('compact_antigravity_db', { confirm });
+  }
 }
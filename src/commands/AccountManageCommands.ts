@@ -1,5 +1,6 @@
 import {invoke} from '@tauri-apps/api/core';
-import type {BackupData, RestoreResult} from './types/account-manage.types.ts';
+import type {BackupData, BackupRepair, DbSnapshot, BackupVerification, LegacyBackup, MigrationReport, PruneResult, ResolvedAccountPath, RestoreResult} from './types/account-manage.types.ts';
+import type {AntigravityAccount} from './types/account.types.ts';
 
 /**
  * 账户与备份综合命令
@@ -17,10 +18,127 @@ export class AccountManageCommands {
     return invoke('delete_backup', { name });
   }
 
+  /**
+   * 以新名称复制一份账户备份
+   * @param sourceName 源备份名称
+   * @param newName 新备份名称（不能与已有备份重名）
+   * @returns 新备份解码后的账户信息
+   */
+  static duplicateAccount(sourceName: string, newName: string): Promise<AntigravityAccount> {
+    return invoke('duplicate_account', { sourceName, newName });
+  }
+
+  /**
+   * 查看账户名在恢复/切换时实际对应的备份文件
+   * @param accountName 账户名
+   * @returns 备份文件绝对路径及是否存在
+   */
+  static resolveAccountPath(accountName: string): Promise<ResolvedAccountPath> {
+    return invoke('resolve_account_path', { accountName });
+  }
+
   static clearAllBackups(): Promise<string> {
     return invoke('clear_all_backups');
   }
 
+  static pruneAccountBackups(keepLatest: number): Promise<PruneResult> {
+    return invoke('prune_account_backups', { keepLatest });
+  }
+
+  static verifyAllBackups(): Promise<BackupVerification[]> {
+    return invoke('verify_all_backups');
+  }
+
+  /**
+   * 检测并修复残缺或旧格式的账户备份（无法恢复登录状态时标记为需要重新登录）
+   * @param accountName 账户名
+   * @returns 执行的修复项
+   */
+  static repairBackup(accountName: string): Promise<BackupRepair> {
+    return invoke('repair_backup', { accountName });
+  }
+
+  /**
+   * 保存 Antigravity 数据库（state.vscdb）的完整快照
+   * @param destPath 快照文件路径（父目录必须存在，文件不能已存在）
+   * @returns 快照路径、大小及快照方式
+   */
+  static snapshotAntigravityDb(destPath: string): Promise<DbSnapshot> {
+    return invoke('snapshot_antigravity_db', { destPath });
+  }
+
+  /**
+   * 用快照文件恢复 Antigravity 数据库（需先关闭 Antigravity，原数据库另存为 state.vscdb.pre-snapshot）
+   * @param srcPath 快照文件路径
+   * @returns 恢复结果消息
+   */
+  static restoreAntigravityDbSnapshot(srcPath: string): Promise<string> {
+    return invoke('restore_antigravity_db_snapshot', { srcPath });
+  }
+
+  static getMigrationReport(): Promise<MigrationReport | null> {
+    return invoke('get_migration_report');
+  }
+
+  /**
+   * 列出旧账户迁移后遗留的 .bak 文件及内容摘要
+   */
+  static listLegacyBackups(): Promise<LegacyBackup[]> {
+    return invoke('list_legacy_backups');
+  }
+
+  /**
+   * 将迁移遗留的 .bak 文件重新导入为普通备份
+   * @param name .bak 文件名
+   * @returns 结果消息
+   */
+  static restoreLegacyBackup(name: string): Promise<string> {
+    return invoke('restore_legacy_backup', { name });
+  }
+
+  /**
+   * 删除所有迁移遗留的 .bak 文件
+   * @param confirm 必须为 true，否则拒绝执行
+   * @returns 删除的文件数
+   */
+  static cleanupLegacyBackups(confirm: boolean): Promise<number> {
+    return invoke('cleanup_legacy_backups', { confirm });
+  }
+
+  // ==== 配置档 ====
+  /**
+   * 获取当前激活的配置档名称（默认为 default）
+   */
+  static getActiveProfile(): Promise<string> {
+    return invoke('get_active_profile');
+  }
+
+  /**
+   * 切换激活的配置档，之后的账户备份/恢复都作用于该配置档
+   * @param name 配置档名称（字母、数字、- 和 _）
+   * @returns 切换后的配置档名称
+   */
+  static setActiveProfile(name: string): Promise<string> {
+    return invoke('set_active_profile', { name });
+  }
+
+  /**
+   * 列出所有配置档（default 始终在首位）
+   */
+  static listProfiles(): Promise<string[]> {
+    return invoke('list_profiles');
+  }
+
+  /**
+   * 修改 default 配置档的账户备份目录（如放到同步盘）
+   * @param path 新目录的绝对路径，为空时恢复默认位置
+   * @param moveExisting 是否把原目录中的备份移动到新目录
+   * @returns 生效后的账户目录
+   */
+  static setAccountsDirectory(path: string, moveExisting?: boolean): Promise<string> {
+    return invoke('set_accounts_directory', { path, moveExisting });
+  }
+
   // ==== 配置加解密 ====
   static encryptConfig(jsonData: string, password: string): Promise<string> {
     return invoke('encrypt_config_data', { jsonData: jsonData, password });
@@ -22,4 +22,21 @@ export class LoggingCommands {
   static async writeTextFile(path: string, content: string): Promise<string> {
     return invoke('write_text_file', { path, content });
   }
+
+  /**
+   * 获取当前日志过滤指令
+   * @returns EnvFilter 格式的指令字符串
+   */
+  static async getLogLevel(): Promise<string> {
+    return invoke('get_log_level');
+  }
+
+  /**
+   * 运行时修改日志过滤指令（重启后恢复默认）
+   * @param directive EnvFilter 格式的指令，如 "info,account::switch=debug"
+   * @returns 更新结果消息
+   */
+  static async setLogLevel(directive: string): Promise<string> {
+    return invoke('set_log_level', { directive });
+  }
 }
@@ -1,5 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { PlatformInfo, DetectionResult, PathConfig } from './types/platform.types';
+import type { PlatformInfo, DetectionResult, PathConfig, LaunchCheck, TargetAppInfo, AntigravityVersion, AntigravityDbStatus, RevalidatedPaths, ActiveInstall } from './types/platform.types';
 
 /**
  * 平台工具命令
@@ -13,6 +13,22 @@ export class PlatformCommands {
     return invoke('get_platform_info');
   }
 
+  /**
+   * 获取诊断信息（平台、路径、运行状态、版本、备份数量等，已脱敏）
+   * @returns 可直接附在问题反馈中的诊断 JSON
+   */
+  static async getDiagnostics(): Promise<Record<string, unknown>> {
+    return invoke('get_diagnostics');
+  }
+
+  /**
+   * 获取 Prometheus 文本格式的运行指标
+   * @returns 指标文本（备份数量、运行状态、切换次数等）
+   */
+  static async getMetricsText(): Promise<string> {
+    return invoke('get_metrics_text');
+  }
+
   /**
    * 查找 Antigravity 安装位置
    * @returns 所有可能的安装路径
@@ -21,6 +37,22 @@ export class PlatformCommands {
     return invoke('find_antigravity_installations');
   }
 
+  /**
+   * 检测 Antigravity 与 Windsurf 的安装情况
+   * @returns 每个目标应用的路径检测结果
+   */
+  static async detectTargetApps(): Promise<TargetAppInfo[]> {
+    return invoke('detect_target_apps');
+  }
+
+  /**
+   * 检测已安装的 Antigravity 版本号
+   * @returns 版本号及其来源，无法确定时 version 为 null
+   */
+  static async detectAntigravityVersion(): Promise<AntigravityVersion> {
+    return invoke('detect_antigravity_version');
+  }
+
   /**
    * 检测 Antigravity 数据库路径
    * @returns 检测结果
@@ -29,6 +61,14 @@ export class PlatformCommands {
     return invoke('detect_antigravity_installation');
   }
 
+  /**
+   * 检测 Antigravity 状态数据库：未安装 / 已安装但未创建数据库 / 就绪
+   * @returns 检测结果，未就绪时可据此提示安装或先启动一次 Antigravity
+   */
+  static async getDbStatus(): Promise<AntigravityDbStatus> {
+    return invoke('get_antigravity_db_status');
+  }
+
   /**
    * 检测 Antigravity 可执行文件路径
    * @returns 检测结果
@@ -37,6 +77,14 @@ export class PlatformCommands {
     return invoke('detect_antigravity_executable');
   }
 
+  /**
+   * 检查 Antigravity 是否可以启动（不实际启动）
+   * @returns 检查结果
+   */
+  static async canStart(): Promise<LaunchCheck> {
+    return invoke('can_start_antigravity');
+  }
+
   /**
    * 验证 Antigravity 可执行文件路径
    * @param path 文件路径
@@ -55,6 +103,15 @@ export class PlatformCommands {
     return invoke('save_antigravity_executable', { path });
   }
 
+  /**
+   * 设置额外的 Antigravity 可执行文件搜索路径（整体替换）
+   * @param paths 路径列表，会优先于内置路径尝试
+   * @returns 去重后保存的路径列表
+   */
+  static async saveExtraExecutablePaths(paths: string[]): Promise<string[]> {
+    return invoke('save_extra_executable_paths', { paths });
+  }
+
   /**
    * 获取当前配置的路径
    * @returns 路径配置
@@ -62,4 +119,29 @@ export class PlatformCommands {
   static async getCurrentPaths(): Promise<PathConfig> {
     return invoke('get_current_paths');
   }
+
+  /**
+   * 重新检测 Antigravity 路径，清除已失效的自定义可执行文件路径
+   * @returns 清除的路径及重新检测结果
+   */
+  static async revalidatePaths(): Promise<RevalidatedPaths> {
+    return invoke('revalidate_paths');
+  }
+
+  /**
+   * 获取当前操作的 Antigravity 安装（固定的安装或自动检测的默认安装）
+   * @returns 数据库路径及是否为固定的安装
+   */
+  static async getActiveInstall(): Promise<ActiveInstall> {
+    return invoke('get_active_install');
+  }
+
+  /**
+   * 固定所有操作使用的 Antigravity 安装
+   * @param path 检测到的 state.vscdb 路径，传空字符串恢复自动检测
+   * @returns 设置后的当前安装
+   */
+  static async setActiveInstall(path: string): Promise<ActiveInstall> {
+    return invoke('set_active_install', { path });
+  }
 }
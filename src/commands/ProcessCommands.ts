@@ -1,4 +1,5 @@
 import {invoke} from '@tauri-apps/api/core';
+import type {AntigravityProcess, AntigravityReadiness, KillStaleResult} from './types/process.types';
 
 /**
  * 进程管理命令
@@ -11,4 +12,39 @@ export class ProcessCommands {
   static async isRunning(): Promise<boolean> {
     return invoke('is_antigravity_running');
   }
+
+  /**
+   * 列出被识别为 Antigravity 的进程及匹配依据
+   * @returns 进程列表
+   */
+  static async listProcesses(): Promise<AntigravityProcess[]> {
+    return invoke('list_antigravity_processes');
+  }
+
+  /**
+   * 终止残留的 Antigravity 进程
+   * @param keepNewest 为 true 时保留最新启动的实例及其子进程，为 false 时终止全部
+   * @returns 已终止、保留和终止失败的进程
+   */
+  static async killStale(keepNewest: boolean): Promise<KillStaleResult> {
+    return invoke('kill_stale_antigravity', { keepNewest });
+  }
+
+  /**
+   * 启动 Antigravity
+   * @param args 追加的启动参数（可选）
+   * @returns 启动结果消息
+   */
+  static async start(args?: string[]): Promise<string> {
+    return invoke('start_antigravity', { args });
+  }
+
+  /**
+   * 等待 Antigravity 就绪（进程运行、状态数据库存在、已写入日志）
+   * @param timeoutMs 超时时间（毫秒）
+   * @returns 各项检查结果，超时时 ready 为 false
+   */
+  static async waitForReady(timeoutMs: number): Promise<AntigravityReadiness> {
+    return invoke('wait_for_antigravity_ready', { timeoutMs });
+  }
 }
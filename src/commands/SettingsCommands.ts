@@ -1,5 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { AppSettings } from './types/settings.types';
+import type { AgentConfigImportResult, AppSettings } from './types/settings.types';
 
 /**
  * 设置管理命令
@@ -23,6 +23,163 @@ export class SettingsCommands {
     return invoke('save_silent_start_state', { enabled });
   }
 
+  /**
+   * 取消尚未执行的静默启动隐藏（用户已主动操作窗口）
+   */
+  static async cancelSilentStartHide(): Promise<void> {
+    return invoke('cancel_silent_start_hide');
+  }
+
+  /**
+   * 保存账户变化自动备份状态
+   * @param enabled 是否启用
+   * @returns 保存后的状态
+   */
+  static async saveAutoBackupOnChangeState(enabled: boolean): Promise<boolean> {
+    return invoke('save_auto_backup_on_change_state', { enabled });
+  }
+
+  /**
+   * 保存启动时自动清理备份的设置
+   * @param enabled 是否启用
+   * @param keepLatest 保留的最新备份数量
+   * @returns 保存后的启用状态
+   */
+  static async saveAutoPruneSettings(enabled: boolean, keepLatest: number): Promise<boolean> {
+    return invoke('save_auto_prune_settings', { enabled, keepLatest });
+  }
+
+  /**
+   * 保存 Antigravity 进程监控设置（启用后会推送 antigravity-process-state 事件）
+   * @param enabled 是否启用
+   * @returns 保存后的启用状态
+   */
+  static async saveProcessWatchState(enabled: boolean): Promise<boolean> {
+    return invoke('save_process_watch_state', { enabled });
+  }
+
+  /**
+   * 获取 HTTP 请求超时时间
+   * @returns 超时时间（毫秒）
+   */
+  static async getHttpTimeout(): Promise<number> {
+    return invoke('get_http_timeout');
+  }
+
+  /**
+   * 设置 HTTP 请求超时时间
+   * @param ms 超时时间（毫秒），允许范围 500–60000
+   * @returns 保存后的超时时间
+   */
+  static async setHttpTimeout(ms: number): Promise<number> {
+    return invoke('set_http_timeout', { ms });
+  }
+
+  /**
+   * 设置数据库变化事件的防抖间隔
+   * @param ms 间隔毫秒数（0–60000，0 表示不防抖）
+   * @returns 保存后的间隔
+   */
+  static async setDbMonitorDebounce(ms: number): Promise<number> {
+    return invoke('set_db_monitor_debounce', { ms });
+  }
+
+  /**
+   * 导出应用自身的配置（设置、路径配置、窗口状态等，不含账户备份）
+   * @param destPath 导出文件路径
+   * @returns 已导出的配置文件名
+   */
+  static async exportAgentConfig(destPath: string): Promise<string[]> {
+    return invoke('export_agent_config', { destPath });
+  }
+
+  /**
+   * 从配置归档导入应用配置，覆盖前会把现有配置备份为 .bak
+   *
+   * 账户目录和当前使用的安装保留原值（需通过专用命令修改）；
+   * 自启动、进程监控和账户目录监听会按导入后的设置立即生效
+   * @param srcPath 配置归档路径
+   * @returns 导入结果
+   */
+  static async importAgentConfig(srcPath: string): Promise<AgentConfigImportResult> {
+    return invoke('import_agent_config', { srcPath });
+  }
+
+  /**
+   * 保存增量备份设置（只影响之后的备份）
+   * @param enabled 是否启用
+   * @returns 保存后的启用状态
+   */
+  static async saveDeltaBackupsState(enabled: boolean): Promise<boolean> {
+    return invoke('save_delta_backups_state', { enabled });
+  }
+
+  /**
+   * 保存压缩备份设置（只影响之后的备份，新备份保存为 .json.gz）
+   * @param enabled 是否启用
+   * @returns 保存后的启用状态
+   */
+  static async saveCompressBackupsState(enabled: boolean): Promise<boolean> {
+    return invoke('save_compress_backups_state', { enabled });
+  }
+
+  /**
+   * 保存启动时重新检测 Antigravity 路径的设置（失效的自定义路径会被清除）
+   * @param enabled 是否启用
+   * @returns 保存后的启用状态
+   */
+  static async saveRevalidatePathsOnStartupState(enabled: boolean): Promise<boolean> {
+    return invoke('save_revalidate_paths_on_startup_state', { enabled });
+  }
+
+  /**
+   * 设置备份过期提示阈值
+   * @param days 天数，0 表示关闭过期提示
+   * @returns 保存后的天数
+   */
+  static async setStaleBackupDays(days: number): Promise<number> {
+    return invoke('set_stale_backup_days', { days });
+  }
+
+  /**
+   * 获取系统自启动的实际状态
+   * @returns 是否已注册自启动
+   */
+  static async getAutostart(): Promise<boolean> {
+    return invoke('get_autostart');
+  }
+
+  /**
+   * 设置系统自启动
+   * @param enabled 是否启用
+   * @returns 设置后的实际状态
+   */
+  static async setAutostart(enabled: boolean): Promise<boolean> {
+    return invoke('set_autostart', { enabled });
+  }
+
+  /**
+   * 获取单个应用设置项
+   * @param key 设置项名称
+   * @returns 设置项的值
+   */
+  static async getAppSetting<K extends keyof AppSettings>(key: K): Promise<AppSettings[K]> {
+    return invoke('get_app_setting', { key });
+  }
+
+  /**
+   * 设置单个应用设置项
+   *
+   * read_only、accounts_directory、active_install_db、autostart_enabled、process_watch_enabled
+   * 不能通过此方法修改；超出范围的值或静默启动未启用托盘时返回错误
+   * @param key 设置项名称
+   * @param value 新值
+   * @returns 验证后的实际值
+   */
+  static async setAppSetting<K extends keyof AppSettings>(key: K, value: AppSettings[K]): Promise<AppSettings[K]> {
+    return invoke('set_app_setting', { key, value });
+  }
+
   /**
    * 获取所有应用设置
    * @returns 应用设置对象
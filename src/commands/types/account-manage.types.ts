@@ -48,3 +48,118 @@ export interface RestoreResult {
   /** 失败的备份列表 */
   failed: FailedBackup[];
 }
+
+/**
+ * 账户名解析出的备份文件路径
+ */
+export interface ResolvedAccountPath {
+  /** 恢复/切换时实际使用的备份文件绝对路径 */
+  path: string;
+
+  /** 文件是否存在 */
+  exists: boolean;
+}
+
+/**
+ * 备份清理结果
+ */
+export interface BackupVerification {
+  /** 备份名（文件名去掉 .json） */
+  name: string;
+
+  valid: boolean;
+
+  /** 发现的问题，valid 为 true 时为空 */
+  issues: string[];
+}
+
+/**
+ * 备份修复结果
+ */
+export interface BackupRepair {
+  /** 备份名（文件名去掉 .json） */
+  name: string;
+
+  /** 执行的修复项，为空表示无需修复 */
+  repaired: string[];
+
+  /** 修复后是否仍缺少可用的登录状态 */
+  needs_relogin: boolean;
+}
+
+export interface PruneResult {
+  /** 保留的备份文件名 */
+  kept: string[];
+
+  /** 移动到 pruned/ 的备份文件名 */
+  pruned: string[];
+
+  /** 因属于当前登录账户而强制保留的备份 */
+  protected: string | null;
+}
+
+/**
+ * 旧账户目录迁移报告
+ */
+export interface MigrationReport {
+  /** 执行时间（RFC 3339） */
+  ran_at: string;
+
+  /** 扫描过的旧账户目录 */
+  legacy_dirs: string[];
+
+  /** 成功迁移的文件数 */
+  migrated: number;
+
+  /** 同名冲突后改名写入的文件数 */
+  renamed_on_collision: number;
+
+  /** 新目录已有相同内容而跳过的文件数 */
+  skipped_existing: number;
+
+  /** 之前已迁移过而跳过的文件数 */
+  skipped_already_migrated: number;
+
+  /** 已重命名为 .bak 的旧文件数 */
+  renamed: number;
+
+  /** 失败的文件及原因 */
+  errors: string[];
+}
+
+/**
+ * 旧账户迁移后遗留的 .bak 文件
+ */
+export interface LegacyBackup {
+  /** 文件名 */
+  name: string;
+
+  /** 完整路径 */
+  path: string;
+
+  /** 文件大小（字节） */
+  size: number;
+
+  /** 最后修改时间（RFC 3339） */
+  modified: string | null;
+
+  /** 是否包含可恢复的 jetskiStateSync 数据 */
+  has_agent_state: boolean;
+
+  /** 顶层键数量（无法解析时为 null） */
+  key_count: number | null;
+
+  /** 无法解析时的原因 */
+  error: string | null;
+}
+
+/**
+ * 数据库快照结果
+ */
+export interface DbSnapshot {
+  /** 快照文件路径 */
+  path: string;
+
+  /** 快照文件大小（字节） */
+  size_bytes: number;
+}
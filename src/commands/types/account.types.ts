@@ -10,6 +10,32 @@ export interface AntigravityAccount {
   flags_f5_base64: string
   history: any[]
   user_id_raw_base64: string
+  /** 测试账户标记（由 create_test_account 创建） */
+  test?: boolean
+  /** 是否为 Antigravity 当前登录的账户（仅账户列表返回；无法读取当前账户时均为 false） */
+  is_current?: boolean
+  /** 备份年龄（天，按备份文件修改时间计算；仅账户列表返回） */
+  age_days?: number
+  /** 备份是否超过设置中的过期阈值，建议重新登录并备份 */
+  stale?: boolean
+  /** 备份缺少可用的登录状态（由 repair_backup 标记），需要重新登录后再备份 */
+  needs_relogin?: boolean
+  /** 登出状态备份（由 create_logged_out_backup 创建），恢复后 Antigravity 处于未登录状态 */
+  logged_out?: boolean
+  /** 备份名称（备份文件名，不含后缀；复制出的备份与原备份邮箱相同，以此区分） */
+  backup_name?: string
+  /** 用户备注（仅账户列表返回） */
+  notes?: string
+  /** 用户标签（仅账户列表返回） */
+  tags?: string[]
+}
+
+/**
+ * 账户备注与标签
+ */
+export interface AccountMeta {
+  notes: string
+  tags: string[]
 }
 
 interface Auth {
@@ -57,3 +83,158 @@ interface Plan {
   upgrade_msg: string
   upgrade_url: string
 }
+
+// 恢复预览中单个数据库键的变更情况
+export interface RestorePreviewEntry {
+  /** 目标数据库（state.vscdb / state.vscdb.backup） */
+  db: string
+  key: string
+  /** 当前数据库中是否存在该键 */
+  current_present: boolean
+  /** 恢复时会新建该键 */
+  will_create: boolean
+  /** 恢复时会覆盖已有的不同值 */
+  will_overwrite: boolean
+  /** 恢复时会删除该键 */
+  will_delete: boolean
+}
+
+// 外部备份结果
+export interface ExternalBackupResult {
+  /** 实际写入（或因内容相同而跳过）的文件路径 */
+  path: string
+  email: string
+  /** 是否实际写入了文件（去重跳过时为 false） */
+  written: boolean
+}
+
+// 批量备份中单个安装的结果
+export interface InstallBackupResult {
+  db_path: string
+  /** 已登录账户的邮箱，未登录或读取失败时为 null */
+  email: string | null
+  status: 'backed_up' | 'skipped' | 'failed'
+  message: string
+}
+
+// 数据库校验结果
+export interface DbValidation {
+  /** 是否为可用的 Antigravity 状态数据库 */
+  valid: boolean
+  /** 是否包含 antigravityAuthStatus（已登录） */
+  has_auth_status: boolean
+  /** ItemTable 中的键数量 */
+  key_count: number
+  /** 不可用时的原因 */
+  reason: string | null
+}
+
+// 恢复前是否需要关闭 Antigravity
+export interface RestoreCloseCheck {
+  /** Antigravity 当前是否在运行 */
+  running: boolean
+  /** 恢复前是否需要先关闭 Antigravity */
+  requires_close: boolean
+  message: string
+}
+
+// 账户列表及无法解析的备份文件
+export interface AccountListResult {
+  accounts: AntigravityAccount[]
+  /** 每个无法解析的备份一条说明：`{备份名}: {错误}` */
+  warnings: string[]
+  /** 无法读取当前登录账户时的说明（此时所有账户 is_current 均为 false） */
+  note: string | null
+}
+
+// 数据库结构兼容性检查结果
+export interface DbCompatibility {
+  /** 是否存在带 key/value 列的 ItemTable */
+  compatible: boolean
+  /** 数据库中的所有表 */
+  found_tables: string[]
+  message: string
+}
+
+// 数据库单个键的值
+export interface DbValue {
+  key: string
+  /** 文本值；二进制值以 Base64 表示；键不存在时为 null */
+  value: string | null
+  /** 原始值的字节数 */
+  size: number
+  /** 值是否为二进制（已 Base64 编码） */
+  is_binary: boolean
+  /** 值是否因超过大小上限而被截断 */
+  truncated: boolean
+}
+
+// 部分恢复结果
+export interface PartialRestoreResult {
+  /** 已写入数据库的键 */
+  applied: string[]
+  /** 未写入的键及原因 */
+  skipped: { key: string; reason: string }[]
+}
+
+// 切换并验证的结果
+export interface SwitchVerifyResult {
+  /** 切换流程（关闭、恢复、启动）是否成功 */
+  switched: boolean
+  /** Antigravity 启动并重新写入登录状态后，当前登录邮箱是否与目标账户一致 */
+  verified: boolean
+  /** 目标账户邮箱 */
+  target_email: string
+  /** 验证结束时读取到的登录邮箱 */
+  final_email: string | null
+  /** 切换结果或失败原因 */
+  message: string
+}
+
+// 数据库压缩结果
+export interface CompactResult {
+  db_path: string
+  /** 压缩前的文件大小（字节） */
+  size_before: number
+  /** 压缩后的文件大小（字节） */
+  size_after: number
+  /** 回收的字节数 */
+  bytes_reclaimed: number
+}
+
+// 账户切换历史
+export interface SwitchHistoryEntry {
+  /** 记录时间（RFC 3339） */
+  timestamp: string
+  /** 操作类型：switch（切换到已有备份）/ sign_in（登录新账户） */
+  operation: 'switch' | 'sign_in'
+  /** 目标账户；登录新账户时为 null */
+  email: string | null
+  success: boolean
+}
+
+/**
+ * 单个数据库的清除结果
+ */
+export interface DbClearResult {
+  /** 数据库文件名 */
+  db: string
+  /** 实际删除的键 */
+  deleted_keys: string[]
+  /** 写入的键（如 antigravityOnboarding） */
+  written_keys: string[]
+  /** 受影响的总行数 */
+  rows_affected: number
+}
+
+/**
+ * 清除 Antigravity 登录数据的结果
+ */
+export interface ClearDataResult {
+  /** 各数据库的清除明细 */
+  databases: DbClearResult[]
+  /** 是否删除了 state.vscdb.backup 文件 */
+  backup_db_deleted: boolean
+  /** 汇总消息 */
+  message: string
+}
@@ -51,4 +51,98 @@ export interface DetectionResult {
 export interface PathConfig {
   /** 可执行文件路径 */
   executablePath?: string | null;
+
+  /** 额外的可执行文件搜索路径 */
+  extraExecutablePaths?: string[];
+}
+
+/**
+ * 启动前检查结果
+ */
+export interface LaunchCheck {
+  /** 是否可以启动 */
+  ok: boolean;
+
+  /** 实际会使用的可执行文件路径 */
+  resolved_path: string | null;
+
+  /** 检查结论或失败原因 */
+  reason: string;
+}
+
+/**
+ * 可管理的目标应用
+ */
+export type TargetApp = 'antigravity' | 'windsurf';
+
+/**
+ * 目标应用安装检测结果
+ */
+export interface TargetAppInfo {
+  target: TargetApp;
+
+  /** 应用数据目录 */
+  data_dir: string | null;
+
+  /** 状态数据库路径 */
+  db_path: string | null;
+
+  /** 状态数据库是否存在 */
+  db_exists: boolean;
+
+  /** 找到的可执行文件路径 */
+  executable: string | null;
+}
+
+/**
+ * Antigravity 版本检测结果
+ */
+export interface AntigravityVersion {
+  /** 版本号，无法确定时为 null */
+  version: string | null;
+
+  /** 版本号来源：Info.plist / product.json / package.json / none */
+  source: string;
+
+  /** 读取版本号的文件路径 */
+  path: string | null;
+}
+
+/**
+ * Antigravity 状态数据库检测结果
+ * - not_installed：未找到 Antigravity，需要先安装
+ * - installed_but_no_db：已安装但未创建数据库，需要先启动一次（path 为预期的数据库路径）
+ * - ready：数据库已存在
+ */
+export type AntigravityDbStatus =
+  | { status: 'not_installed' }
+  | { status: 'installed_but_no_db'; path: string }
+  | { status: 'ready'; path: string };
+
+/**
+ * 路径重新检测结果
+ */
+export interface RevalidatedPaths {
+  /** 已失效并被清除的自定义可执行文件路径 */
+  cleared_custom_executable_path: string | null;
+
+  /** 不存在的额外搜索路径（仅提示，不会自动删除） */
+  missing_extra_executable_paths: string[];
+
+  /** 重新检测后实际使用的可执行文件路径 */
+  executable_path: string | null;
+
+  /** 重新检测后的状态数据库 */
+  db_status: AntigravityDbStatus;
+}
+
+/**
+ * 当前操作的 Antigravity 安装
+ */
+export interface ActiveInstall {
+  /** 所有操作使用的 state.vscdb 路径，未检测到安装时为 null */
+  db_path: string | null;
+
+  /** 是否为设置中固定的安装（否则为自动检测结果） */
+  pinned: boolean;
 }
@@ -21,3 +21,54 @@ export interface ProcessInfo {
   /** 模式描述 */
   pattern_description: string;
 }
+
+/**
+ * 被识别为 Antigravity 的进程
+ */
+export interface AntigravityProcess {
+  /** 进程 ID */
+  pid: number;
+
+  /** 进程名称 */
+  name: string;
+
+  /** 可执行文件路径（无权限读取时为 null） */
+  exe: string | null;
+
+  /** 命令行 */
+  cmd: string;
+
+  /** 匹配依据：exe_path / name:<进程名> / cmd:<命令行片段> */
+  matched_by: string;
+}
+
+/**
+ * Antigravity 启动就绪检查结果
+ */
+export interface AntigravityReadiness {
+  /** 进程、数据库、日志均已就绪 */
+  ready: boolean;
+
+  /** 进程是否在运行 */
+  process: boolean;
+
+  /** 状态数据库是否存在 */
+  db: boolean;
+
+  /** 是否已写入日志文件 */
+  log: boolean;
+}
+
+/**
+ * 清理残留进程的结果
+ */
+export interface KillStaleResult {
+  /** 已终止的进程 */
+  killed: AntigravityProcess[];
+
+  /** 保留的进程（最新实例及其子进程） */
+  kept: AntigravityProcess[];
+
+  /** 终止失败的进程 */
+  failed: AntigravityProcess[];
+}
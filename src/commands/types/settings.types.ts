@@ -11,4 +11,63 @@ export interface AppSettings {
 
   /** 静默启动是否启用 */
   silent_start_enabled: boolean;
+
+  /** 账户变化时是否自动备份 */
+  auto_backup_on_change: boolean;
+
+  /** 启动时是否自动清理旧备份 */
+  auto_prune_enabled: boolean;
+
+  /** 自动清理时保留的最新备份数量 */
+  auto_prune_keep_latest: number;
+
+  /** 是否随系统登录自动启动 */
+  autostart_enabled: boolean;
+
+  /** JSON 日志文件的级别过滤指令（重启后生效） */
+  json_log_level: string;
+
+  /** JSON 日志文件保留天数 */
+  log_retention_days: number;
+
+  /** HTTP 请求超时时间（毫秒，500–60000） */
+  http_timeout_ms: number;
+
+  /** 数据库变化事件防抖间隔（毫秒，0–60000，0 表示不防抖） */
+  db_monitor_debounce_ms: number;
+
+  /** 是否使用增量备份（状态数据共享存储，备份文件只保存引用） */
+  delta_backups_enabled: boolean;
+
+  /** default 配置档的账户备份目录，为 null 时使用默认位置 */
+  accounts_directory: string | null;
+
+  /** 是否以 gzip 压缩保存新备份（.json.gz） */
+  compress_backups: boolean;
+
+  /** 备份过期提示阈值（天，0 表示不提示） */
+  stale_backup_days: number;
+
+  /** 是否监控 Antigravity 进程启动/退出 */
+  process_watch_enabled: boolean;
+
+  /** 是否在启动时重新检测 Antigravity 路径 */
+  revalidate_paths_on_startup: boolean;
+
+  /** 固定操作的 Antigravity 安装（state.vscdb 路径），为 null 时自动检测 */
+  active_install_db: string | null;
+
+  /** 只读模式（只能通过编辑配置文件修改） */
+  read_only?: boolean;
+}
+
+/**
+ * 应用配置导入结果
+ */
+export interface AgentConfigImportResult {
+  /** 已导入的配置文件 */
+  imported: string[];
+
+  /** 覆盖前已备份为 .bak 的配置文件 */
+  backed_up: string[];
 }